@@ -0,0 +1,61 @@
+use criterion::{black_box, criterion_group, criterion_main, BatchSize, Criterion};
+use ftc_powerplay_scoring::traditional::{TraditionalAuto, TraditionalEndGame, TraditionalJunction::*};
+use ftc_powerplay_scoring::*;
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+// counts allocations, to check that scoring a match never allocates
+struct CountingAllocator;
+
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+}
+
+#[global_allocator]
+static ALLOCATOR: CountingAllocator = CountingAllocator;
+
+// a busy match, where red completes a circuit and blue beacons in its way
+fn end_game() -> TraditionalEndGame {
+    let mut auto = TraditionalAuto::new([true, true], [true, false], SignalZone::Middle);
+    auto.score_for(Alliance::RED, Y2);
+    auto.score_for(Alliance::BLUE, W4);
+    auto.park_for(MatchIndex::RED_CAPTAIN, SignalZone::Middle);
+    let mut teleop = auto.into_teleop();
+    for junction in [Z1, Y2, X3, W4, V5, Y1, X2, W3] {
+        teleop.score_for(Alliance::RED, junction);
+    }
+    for junction in [V1, V2, W1, W2, X1, Z4, Z5, Y5, X5] {
+        teleop.score_for(Alliance::BLUE, junction);
+        teleop.score_for(Alliance::BLUE, junction);
+    }
+    teleop.add_terminal_for(Alliance::RED, Terminal::Near);
+    teleop.add_terminal_for(Alliance::RED, Terminal::Far);
+    let mut end_game = teleop.into_end_game();
+    end_game.cap_for(MatchIndex::BLUE_CAPTAIN, X3).unwrap();
+    end_game.cap_for(MatchIndex::RED_CAPTAIN, X4).unwrap();
+    end_game.park_in_terminal_for(MatchIndex::RED_FIRST_PICK);
+    end_game
+}
+
+fn benchmark(c: &mut Criterion) {
+    let before = ALLOCATIONS.load(Ordering::Relaxed);
+    black_box(EndGame::end_match(end_game()));
+    assert_eq!(ALLOCATIONS.load(Ordering::Relaxed), before, "Playing a match should not allocate.");
+
+    c.bench_function("play match", |b| b.iter(|| EndGame::end_match(black_box(end_game()))));
+    c.bench_function("end match", |b| {
+        b.iter_batched(end_game, |end_game| EndGame::end_match(black_box(end_game)), BatchSize::SmallInput)
+    });
+}
+
+criterion_group!(benches, benchmark);
+criterion_main!(benches);
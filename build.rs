@@ -0,0 +1,19 @@
+fn main() {
+    // the header is only regenerated when building the C bindings.
+    // build scripts may only write to OUT_DIR, so the copy in include/ is kept in sync by ffi_header_test.
+    #[cfg(feature = "ffi")]
+    {
+        let crate_dir = std::env::var("CARGO_MANIFEST_DIR").unwrap();
+        let out_dir = std::env::var("OUT_DIR").unwrap();
+        let config = cbindgen::Config::from_file(format!("{crate_dir}/cbindgen.toml")).unwrap();
+        // every exported item is in the ffi module, so the rest of the crate does not need to be parsed
+        cbindgen::Builder::new()
+            .with_src(format!("{crate_dir}/src/ffi.rs"))
+            .with_config(config)
+            .generate()
+            .expect("Unable to generate C bindings.")
+            .write_to_file(format!("{out_dir}/ftc_powerplay_scoring.h"));
+        println!("cargo:rerun-if-changed=src/ffi.rs");
+        println!("cargo:rerun-if-changed=cbindgen.toml");
+    }
+}
@@ -0,0 +1,196 @@
+#ifndef FTC_POWERPLAY_SCORING_H
+#define FTC_POWERPLAY_SCORING_H
+
+/* Generated by cbindgen from src/ffi.rs. Do not edit by hand. */
+
+#include <stdarg.h>
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+#include <stdlib.h>
+
+#define FTC_ALLIANCE_RED 0
+
+#define FTC_ALLIANCE_BLUE 1
+
+#define FTC_FIELD_TRADITIONAL 0
+
+#define FTC_FIELD_RED_REMOTE 1
+
+#define FTC_FIELD_BLUE_REMOTE 2
+
+#define FTC_PHASE_AUTO 0
+
+#define FTC_PHASE_TELEOP 1
+
+#define FTC_PHASE_END_GAME 2
+
+#define FTC_PHASE_FINISHED 3
+
+#define FTC_SIGNAL_ZONE_LEFT 0
+
+#define FTC_SIGNAL_ZONE_MIDDLE 1
+
+#define FTC_SIGNAL_ZONE_RIGHT 2
+
+#define FTC_TERMINAL_NEAR 0
+
+#define FTC_TERMINAL_FAR 1
+
+#define FTC_PARK_LEFT_SIGNAL_ZONE 0
+
+#define FTC_PARK_MIDDLE_SIGNAL_ZONE 1
+
+#define FTC_PARK_RIGHT_SIGNAL_ZONE 2
+
+#define FTC_PARK_NEAR_TERMINAL 3
+
+#define FTC_PARK_FAR_TERMINAL 4
+
+#define FTC_PARK_SUBSTATION 5
+
+#define FTC_CARD_NONE 0
+
+#define FTC_CARD_YELLOW 1
+
+#define FTC_CARD_RED 2
+
+typedef enum FtcError {
+  FTC_ERROR_OK = 0,
+  FTC_ERROR_JUNCTION_IS_EMPTY,
+  FTC_ERROR_BEACON_ON_JUNCTION,
+  FTC_ERROR_JUNCTION_IS_CAPPED,
+  FTC_ERROR_BEACON_PREVIOUSLY_SCORED,
+  FTC_ERROR_BEACON_SCORED_OUTSIDE_ENDGAME,
+  FTC_ERROR_WRONG_FIELD,
+  FTC_ERROR_WRONG_PHASE,
+  FTC_ERROR_NOT_IN_MATCH,
+  FTC_ERROR_DUPLICATE_TEAM,
+  FTC_ERROR_NULL_POINTER,
+  FTC_ERROR_INVALID_ARGUMENT,
+  FTC_ERROR_PANIC,
+} FtcError;
+
+/**
+ * A match in any phase.
+ */
+typedef struct FtcMatch FtcMatch;
+
+/**
+ * The status of one robot in a match. `card` is one of the `FTC_CARD_*` constants.
+ */
+typedef struct FtcRobotStatus {
+  bool no_show;
+  bool disabled;
+  uint8_t card;
+  bool disqualified;
+} FtcRobotStatus;
+
+/**
+ * The results of one alliance in a finished match.
+ * Only the first `team_count` entries of `teams` are valid.
+ */
+typedef struct FtcAllianceInfo {
+  uint8_t alliance;
+  uint8_t team_count;
+  int32_t teams[2];
+  uint16_t penalty_points;
+  uint16_t auto_points;
+  uint16_t teleop_points;
+  uint16_t endgame_points;
+} FtcAllianceInfo;
+
+/**
+ * Creates a traditional match in auto. Each array holds the values for the alliance's two robots.
+ */
+enum FtcError ftc_traditional_new(const int32_t *red_teams,
+                                  const bool *red_signal_sleeves,
+                                  const int32_t *blue_teams,
+                                  const bool *blue_signal_sleeves,
+                                  uint8_t signal_zone_value,
+                                  struct FtcMatch **out);
+
+/**
+ * Creates a remote match in auto, for a single team on the given alliance.
+ * `circuit_pattern_value` is the pattern number minus one.
+ */
+enum FtcError ftc_remote_new(uint8_t alliance_value,
+                             int32_t team,
+                             bool has_signal_sleeve,
+                             uint8_t signal_zone_value,
+                             uint8_t circuit_pattern_value,
+                             struct FtcMatch **out);
+
+/**
+ * Releases a match. Passing a null pointer does nothing.
+ */
+void ftc_match_free(struct FtcMatch *m);
+
+/**
+ * Writes one of the `FTC_FIELD_*` constants.
+ */
+enum FtcError ftc_match_field_type(struct FtcMatch *m, uint8_t *out);
+
+/**
+ * Writes one of the `FTC_PHASE_*` constants.
+ */
+enum FtcError ftc_match_phase(struct FtcMatch *m, uint8_t *out);
+
+enum FtcError ftc_score(struct FtcMatch *m, uint8_t alliance_value, uint8_t row, uint8_t column);
+
+/**
+ * Removes the top cone of a junction. If `out_alliance` is not null, the alliance of the removed cone is written to it.
+ */
+enum FtcError ftc_descore(struct FtcMatch *m,
+                          uint8_t row,
+                          uint8_t column,
+                          uint8_t *out_alliance);
+
+enum FtcError ftc_add_terminal(struct FtcMatch *m, uint8_t alliance_value, uint8_t terminal_value);
+
+enum FtcError ftc_cap(struct FtcMatch *m,
+                      uint8_t alliance_value,
+                      uint8_t index,
+                      uint8_t row,
+                      uint8_t column);
+
+enum FtcError ftc_penalize(struct FtcMatch *m, uint8_t alliance_value, uint8_t points);
+
+/**
+ * Parks a robot at one of the `FTC_PARK_*` locations.
+ * Robots can park anywhere in auto, but only in a terminal in end game.
+ */
+enum FtcError ftc_park(struct FtcMatch *m, uint8_t alliance_value, uint8_t index, uint8_t location);
+
+/**
+ * Gives a robot a yellow or red card. A second yellow card in the same match becomes a red card.
+ */
+enum FtcError ftc_card(struct FtcMatch *m,
+                       uint8_t alliance_value,
+                       uint8_t index,
+                       uint8_t card_value);
+
+enum FtcError ftc_get_status(struct FtcMatch *m,
+                             uint8_t alliance_value,
+                             uint8_t index,
+                             struct FtcRobotStatus *out);
+
+enum FtcError ftc_set_status(struct FtcMatch *m,
+                             uint8_t alliance_value,
+                             uint8_t index,
+                             const struct FtcRobotStatus *status);
+
+/**
+ * Moves the match to its next phase, ending it after end game.
+ * If `out_phase` is not null, the new phase is written to it.
+ */
+enum FtcError ftc_advance_phase(struct FtcMatch *m, uint8_t *out_phase);
+
+/**
+ * Writes the results of an alliance in a finished match.
+ */
+enum FtcError ftc_alliance_info(struct FtcMatch *m,
+                                uint8_t alliance_value,
+                                struct FtcAllianceInfo *out);
+
+#endif /* FTC_POWERPLAY_SCORING_H */
@@ -0,0 +1,5 @@
+package ftc.powerplay.scoring;
+
+public enum Alliance {
+    RED, BLUE
+}
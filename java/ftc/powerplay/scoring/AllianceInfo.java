@@ -0,0 +1,30 @@
+package ftc.powerplay.scoring;
+
+import java.util.Arrays;
+
+/** The results of one alliance in a finished match. */
+public final class AllianceInfo {
+    public final Alliance alliance;
+    public final int[] teams;
+    public final int penaltyPoints;
+    public final int autoPoints;
+    public final int teleOpPoints;
+    public final int endGamePoints;
+
+    // unpacks the array returned by NativeMatch.results
+    AllianceInfo(int[] packed) {
+        alliance = Alliance.values()[packed[0]];
+        penaltyPoints = packed[1];
+        autoPoints = packed[2];
+        teleOpPoints = packed[3];
+        endGamePoints = packed[4];
+        teams = Arrays.copyOfRange(packed, 5, packed.length);
+    }
+
+    @Override
+    public String toString() {
+        return "AllianceInfo{alliance=" + alliance + ", teams=" + Arrays.toString(teams)
+                + ", penaltyPoints=" + penaltyPoints + ", autoPoints=" + autoPoints
+                + ", teleOpPoints=" + teleOpPoints + ", endGamePoints=" + endGamePoints + "}";
+    }
+}
@@ -0,0 +1,7 @@
+package ftc.powerplay.scoring;
+
+public enum BlueRemoteJunction implements Junction {
+    V1, V2, V3, V4, V5,
+    W1, W2, W3, W4, W5,
+    X1, X2, X3, X4, X5
+}
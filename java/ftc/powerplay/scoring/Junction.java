@@ -0,0 +1,8 @@
+package ftc.powerplay.scoring;
+
+/** A junction on one kind of field. Every implementation is an enum in the same order as the Rust junctions. */
+public interface Junction {
+    int ordinal();
+
+    String name();
+}
@@ -0,0 +1,65 @@
+package ftc.powerplay.scoring;
+
+/**
+ * One phase of a match on a field with junctions of type {@code J}.
+ * Moving to the next phase closes this object, and every match must be closed once it is no longer needed.
+ */
+public abstract class Match<J extends Junction> implements AutoCloseable {
+    private long handle;
+
+    Match(long handle) {
+        this.handle = handle;
+    }
+
+    final long handle() {
+        if (handle == 0) {
+            throw new IllegalStateException("The match has been closed.");
+        }
+        return handle;
+    }
+
+    // advances the native match and hands it to the next phase
+    final long advance() {
+        NativeMatch.advancePhase(handle());
+        long next = handle;
+        handle = 0;
+        return next;
+    }
+
+    // ends the native match, returning the results for each given alliance
+    final AllianceInfo[] finish(Alliance... alliances) {
+        long finished = advance();
+        try {
+            AllianceInfo[] results = new AllianceInfo[alliances.length];
+            for (int i = 0; i < alliances.length; i++) {
+                results[i] = new AllianceInfo(NativeMatch.results(finished, alliances[i].ordinal()));
+            }
+            return results;
+        } finally {
+            NativeMatch.free(finished);
+        }
+    }
+
+    public void scoreFor(Alliance alliance, J junction) {
+        NativeMatch.score(handle(), alliance.ordinal(), junction.ordinal());
+    }
+
+    /** Removes the top cone of a junction, returning the alliance it belonged to. */
+    public Alliance descore(J junction) {
+        return Alliance.values()[NativeMatch.descore(handle(), junction.ordinal())];
+    }
+
+    public void addTerminalFor(Alliance alliance, Terminal terminal) {
+        NativeMatch.addTerminal(handle(), alliance.ordinal(), terminal.ordinal());
+    }
+
+    public void penalize(Alliance alliance, int points) {
+        NativeMatch.penalize(handle(), alliance.ordinal(), points);
+    }
+
+    @Override
+    public void close() {
+        NativeMatch.free(handle);
+        handle = 0;
+    }
+}
@@ -0,0 +1,38 @@
+package ftc.powerplay.scoring;
+
+/** The native methods behind every match. Enums are passed as their ordinals. */
+final class NativeMatch {
+    static {
+        // lets tests and apps with a custom layout load the library from an exact path
+        String path = System.getProperty("ftc.powerplay.scoring.library");
+        if (path != null) {
+            System.load(path);
+        } else {
+            System.loadLibrary("ftc_powerplay_scoring");
+        }
+    }
+
+    private NativeMatch() {}
+
+    static native long newTraditional(int[] redTeams, boolean[] redSignalSleeves, int[] blueTeams, boolean[] blueSignalSleeves, int signalZone);
+
+    static native long newRemote(int alliance, int team, boolean hasSignalSleeve, int signalZone, int circuitPattern);
+
+    static native void free(long handle);
+
+    static native void score(long handle, int alliance, int junction);
+
+    static native int descore(long handle, int junction);
+
+    static native void addTerminal(long handle, int alliance, int terminal);
+
+    static native void cap(long handle, int team, int junction);
+
+    static native void penalize(long handle, int alliance, int points);
+
+    static native void park(long handle, int team, int location);
+
+    static native int advancePhase(long handle);
+
+    static native int[] results(long handle, int alliance);
+}
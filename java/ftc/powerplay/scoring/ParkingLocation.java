@@ -0,0 +1,13 @@
+package ftc.powerplay.scoring;
+
+public enum ParkingLocation {
+    LEFT_SIGNAL_ZONE, MIDDLE_SIGNAL_ZONE, RIGHT_SIGNAL_ZONE, NEAR_TERMINAL, FAR_TERMINAL, SUBSTATION;
+
+    public static ParkingLocation of(SignalZone zone) {
+        return values()[zone.ordinal()];
+    }
+
+    public static ParkingLocation of(Terminal terminal) {
+        return terminal == Terminal.NEAR ? NEAR_TERMINAL : FAR_TERMINAL;
+    }
+}
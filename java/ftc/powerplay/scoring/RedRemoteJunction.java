@@ -0,0 +1,7 @@
+package ftc.powerplay.scoring;
+
+public enum RedRemoteJunction implements Junction {
+    X1, X2, X3, X4, X5,
+    Y1, Y2, Y3, Y4, Y5,
+    Z1, Z2, Z3, Z4, Z5
+}
@@ -0,0 +1,26 @@
+package ftc.powerplay.scoring;
+
+public final class RemoteAuto<J extends Junction> extends Match<J> {
+    private final Alliance alliance;
+
+    private RemoteAuto(Alliance alliance, int team, boolean hasSignalSleeve, SignalZone signalZone, RemoteCircuitPattern circuitPattern) {
+        super(NativeMatch.newRemote(alliance.ordinal(), team, hasSignalSleeve, signalZone.ordinal(), circuitPattern.ordinal()));
+        this.alliance = alliance;
+    }
+
+    public static RemoteAuto<RedRemoteJunction> red(int team, boolean hasSignalSleeve, SignalZone signalZone, RemoteCircuitPattern circuitPattern) {
+        return new RemoteAuto<>(Alliance.RED, team, hasSignalSleeve, signalZone, circuitPattern);
+    }
+
+    public static RemoteAuto<BlueRemoteJunction> blue(int team, boolean hasSignalSleeve, SignalZone signalZone, RemoteCircuitPattern circuitPattern) {
+        return new RemoteAuto<>(Alliance.BLUE, team, hasSignalSleeve, signalZone, circuitPattern);
+    }
+
+    public void parkFor(int team, ParkingLocation location) {
+        NativeMatch.park(handle(), team, location.ordinal());
+    }
+
+    public RemoteTeleOp<J> intoTeleOp() {
+        return new RemoteTeleOp<>(advance(), alliance);
+    }
+}
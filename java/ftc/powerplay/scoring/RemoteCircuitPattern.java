@@ -0,0 +1,5 @@
+package ftc.powerplay.scoring;
+
+public enum RemoteCircuitPattern {
+    PATTERN_1, PATTERN_2, PATTERN_3, PATTERN_4, PATTERN_5, PATTERN_6
+}
@@ -0,0 +1,24 @@
+package ftc.powerplay.scoring;
+
+public final class RemoteEndGame<J extends Junction> extends Match<J> {
+    private final Alliance alliance;
+
+    RemoteEndGame(long handle, Alliance alliance) {
+        super(handle);
+        this.alliance = alliance;
+    }
+
+    /** Scores a team's beacon on a junction. */
+    public void capFor(int team, J junction) {
+        NativeMatch.cap(handle(), team, junction.ordinal());
+    }
+
+    public void parkFor(int team, Terminal terminal) {
+        NativeMatch.park(handle(), team, ParkingLocation.of(terminal).ordinal());
+    }
+
+    /** Ends the match, closing this object. */
+    public AllianceInfo endMatch() {
+        return finish(alliance)[0];
+    }
+}
@@ -0,0 +1,14 @@
+package ftc.powerplay.scoring;
+
+public final class RemoteTeleOp<J extends Junction> extends Match<J> {
+    private final Alliance alliance;
+
+    RemoteTeleOp(long handle, Alliance alliance) {
+        super(handle);
+        this.alliance = alliance;
+    }
+
+    public RemoteEndGame<J> intoEndGame() {
+        return new RemoteEndGame<>(advance(), alliance);
+    }
+}
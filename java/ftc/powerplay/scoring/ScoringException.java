@@ -0,0 +1,27 @@
+package ftc.powerplay.scoring;
+
+/** Thrown when an action is not allowed in a match. */
+public class ScoringException extends RuntimeException {
+    public enum Reason {
+        JunctionIsEmpty,
+        BeaconOnJunction,
+        JunctionIsCapped,
+        BeaconPreviouslyScored,
+        BeaconScoredOutsideEndgame,
+        WrongField,
+        WrongPhase,
+        NotInMatch
+    }
+
+    private final Reason reason;
+
+    // called from native code with the name of the Rust error
+    ScoringException(String reason) {
+        super(reason);
+        this.reason = Reason.valueOf(reason);
+    }
+
+    public Reason getReason() {
+        return reason;
+    }
+}
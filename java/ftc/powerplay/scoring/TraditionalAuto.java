@@ -0,0 +1,16 @@
+package ftc.powerplay.scoring;
+
+public final class TraditionalAuto extends Match<TraditionalJunction> {
+    /** Creates a match. Each array holds the values for the alliance's two robots. */
+    public TraditionalAuto(int[] redTeams, boolean[] redSignalSleeves, int[] blueTeams, boolean[] blueSignalSleeves, SignalZone signalZone) {
+        super(NativeMatch.newTraditional(redTeams, redSignalSleeves, blueTeams, blueSignalSleeves, signalZone.ordinal()));
+    }
+
+    public void parkFor(int team, ParkingLocation location) {
+        NativeMatch.park(handle(), team, location.ordinal());
+    }
+
+    public TraditionalTeleOp intoTeleOp() {
+        return new TraditionalTeleOp(advance());
+    }
+}
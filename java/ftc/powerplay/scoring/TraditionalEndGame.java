@@ -0,0 +1,28 @@
+package ftc.powerplay.scoring;
+
+import java.util.EnumMap;
+
+public final class TraditionalEndGame extends Match<TraditionalJunction> {
+    TraditionalEndGame(long handle) {
+        super(handle);
+    }
+
+    /** Scores a team's beacon on a junction. */
+    public void capFor(int team, TraditionalJunction junction) {
+        NativeMatch.cap(handle(), team, junction.ordinal());
+    }
+
+    public void parkFor(int team, Terminal terminal) {
+        NativeMatch.park(handle(), team, ParkingLocation.of(terminal).ordinal());
+    }
+
+    /** Ends the match, closing this object. */
+    public EnumMap<Alliance, AllianceInfo> endMatch() {
+        AllianceInfo[] results = finish(Alliance.RED, Alliance.BLUE);
+        EnumMap<Alliance, AllianceInfo> map = new EnumMap<>(Alliance.class);
+        for (AllianceInfo info : results) {
+            map.put(info.alliance, info);
+        }
+        return map;
+    }
+}
@@ -0,0 +1,9 @@
+package ftc.powerplay.scoring;
+
+public enum TraditionalJunction implements Junction {
+    V1, V2, V3, V4, V5,
+    W1, W2, W3, W4, W5,
+    X1, X2, X3, X4, X5,
+    Y1, Y2, Y3, Y4, Y5,
+    Z1, Z2, Z3, Z4, Z5
+}
@@ -0,0 +1,11 @@
+package ftc.powerplay.scoring;
+
+public final class TraditionalTeleOp extends Match<TraditionalJunction> {
+    TraditionalTeleOp(long handle) {
+        super(handle);
+    }
+
+    public TraditionalEndGame intoEndGame() {
+        return new TraditionalEndGame(advance());
+    }
+}
@@ -0,0 +1,58 @@
+package ftc.powerplay.scoring;
+
+import java.util.EnumMap;
+
+/** Run with assertions enabled, and the library path in the ftc.powerplay.scoring.library property. */
+public final class ScoringTest {
+    private static void expectReason(ScoringException.Reason reason, Runnable action) {
+        try {
+            action.run();
+        } catch (ScoringException e) {
+            assert e.getReason() == reason : e.getReason();
+            return;
+        }
+        throw new AssertionError("expected " + reason);
+    }
+
+    public static void main(String[] args) {
+        TraditionalAuto auto = new TraditionalAuto(
+                new int[] {4017, 4018}, new boolean[] {true, false},
+                new int[] {1, 2}, new boolean[] {true, true},
+                SignalZone.LEFT);
+        auto.scoreFor(Alliance.RED, TraditionalJunction.W3);
+        auto.scoreFor(Alliance.BLUE, TraditionalJunction.W3);
+        auto.parkFor(4017, ParkingLocation.LEFT_SIGNAL_ZONE);
+        assert auto.descore(TraditionalJunction.W3) == Alliance.BLUE;
+        expectReason(ScoringException.Reason.JunctionIsEmpty, () -> auto.descore(TraditionalJunction.V1));
+        expectReason(ScoringException.Reason.NotInMatch, () -> auto.parkFor(9999, ParkingLocation.SUBSTATION));
+
+        TraditionalTeleOp teleOp = auto.intoTeleOp();
+        try {
+            auto.scoreFor(Alliance.RED, TraditionalJunction.V1);
+            throw new AssertionError("a previous phase was used");
+        } catch (IllegalStateException expected) {
+        }
+        TraditionalEndGame endGame = teleOp.intoEndGame();
+        endGame.capFor(1, TraditionalJunction.W3);
+        expectReason(ScoringException.Reason.JunctionIsCapped, () -> endGame.capFor(2, TraditionalJunction.W3));
+        EnumMap<Alliance, AllianceInfo> results = endGame.endMatch();
+        assert results.get(Alliance.RED).autoPoints == 25 : results;
+        assert results.get(Alliance.RED).teams[1] == 4018 : results;
+        assert results.get(Alliance.BLUE).endGamePoints == 10 : results;
+
+        try (RemoteAuto<RedRemoteJunction> remote = RemoteAuto.red(4017, true, SignalZone.RIGHT, RemoteCircuitPattern.PATTERN_1)) {
+            remote.scoreFor(Alliance.RED, RedRemoteJunction.Z5);
+            expectReason(ScoringException.Reason.NotInMatch, () -> remote.scoreFor(Alliance.BLUE, RedRemoteJunction.Z5));
+        }
+
+        RemoteEndGame<BlueRemoteJunction> remote = RemoteAuto.blue(4017, false, SignalZone.MIDDLE, RemoteCircuitPattern.PATTERN_2)
+                .intoTeleOp()
+                .intoEndGame();
+        remote.scoreFor(Alliance.BLUE, BlueRemoteJunction.W3);
+        remote.capFor(4017, BlueRemoteJunction.W3);
+        remote.parkFor(4017, Terminal.NEAR);
+        AllianceInfo info = remote.endMatch();
+        assert info.alliance == Alliance.BLUE && info.teams.length == 1 : info;
+        System.out.println("ok");
+    }
+}
@@ -0,0 +1,38 @@
+//! Hosts matches for referee tablets on this machine. See `ftc_powerplay_scoring::server` for the API.
+//!
+//! Usage: `scoring-server [port]`, where the port defaults to 8080. The server only listens on localhost.
+use std::net::Ipv4Addr;
+use std::process::ExitCode;
+use tokio::net::TcpListener;
+
+const DEFAULT_PORT: u16 = 8080;
+
+#[tokio::main]
+async fn main() -> ExitCode {
+    let port = match std::env::args().nth(1).map(|port| port.parse()) {
+        None => DEFAULT_PORT,
+        Some(Ok(port)) => port,
+        Some(Err(error)) => {
+            eprintln!("The port must be a number from 0 to 65535: {error}");
+            return ExitCode::FAILURE;
+        }
+    };
+    let listener = match TcpListener::bind((Ipv4Addr::LOCALHOST, port)).await {
+        Ok(listener) => listener,
+        Err(error) => {
+            eprintln!("Could not listen on port {port}: {error}");
+            return ExitCode::FAILURE;
+        }
+    };
+    // the port is only known now if it was 0
+    if let Ok(address) = listener.local_addr() {
+        println!("Scoring server listening on http://{address}");
+    }
+    match ftc_powerplay_scoring::server::serve(listener).await {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(error) => {
+            eprintln!("The server stopped: {error}");
+            ExitCode::FAILURE
+        }
+    }
+}
@@ -0,0 +1,384 @@
+//! The module for a short text language for scoring actions, so that tablets, chat bots, tests and scripts
+//! can share one syntax.
+//!
+//! A command is a few words separated by spaces, in any case:
+//!
+//! | Command             | Action                                                                  |
+//! |---------------------|-------------------------------------------------------------------------|
+//! | `R W3`              | Red scores a cone on W3.                                                |
+//! | `B -X2`             | Blue's cone is removed from the top of X2.                              |
+//! | `R1 cap V1`         | Red robot 1 caps V1 with its beacon.                                    |
+//! | `B term near`       | Blue scores a cone in its near terminal. `terminal` also works.         |
+//! | `R0 park left`      | Red robot 0 parks in the left signal zone.                              |
+//! | `B0 card yellow`    | Blue robot 0 receives a yellow card.                                    |
+//! | `foul B major G402` | Blue commits a major foul, optionally citing the rule it broke.         |
+//! | `next`              | The match moves to its next phase.                                      |
+//!
+//! Robots are numbered from 0, the captain. Robots park in `left`, `middle` or `right` signal zones,
+//! `near` or `far` terminals, or the `substation`. Minor fouls are worth 10 points and major fouls 30.
+//! The alliance of a descore is for the referee's benefit, and is not checked against the cone that is removed.
+//!
+//! Every error holds the byte offset of the problem in the text, as does every error from the [`FromStr`]
+//! implementations of the junctions, [`Alliance`], [`MatchIndex`], [`ParkingLocation`], [`Terminal`]
+//! and [`SignalZone`].
+use crate::dynamic::{AnyJunction, FieldType, MatchAction};
+use crate::remote::{BlueRemoteJunction, RedRemoteJunction};
+use crate::traditional::TraditionalJunction;
+use crate::*;
+use core::fmt::Write;
+use core::str::FromStr;
+
+/// What the parser was looking for when it failed.
+#[derive(Eq, PartialEq, Copy, Clone, Debug, Hash)]
+pub enum Expected {
+    Command,
+    Alliance,
+    Robot,
+    /// The number after a robot's alliance.
+    RobotNumber,
+    /// What an alliance did, after the alliance.
+    AllianceAction,
+    /// What a robot did, after the robot.
+    RobotAction,
+    Junction,
+    /// The number after a junction's row.
+    Column,
+    Terminal,
+    ParkingLocation,
+    SignalZone,
+    Card,
+    /// Whether a foul was minor or major.
+    Severity,
+    Rule,
+    /// Nothing, since the command was complete.
+    End,
+}
+
+impl Display for Expected {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        f.write_str(match self {
+            Expected::Command => "a command, which starts with an alliance, a robot, foul or next",
+            Expected::Alliance => "an alliance, R or B",
+            Expected::Robot => "a robot, like R0 or B1",
+            Expected::RobotNumber => "a robot number, 0 or 1",
+            Expected::AllianceAction => {
+                "a junction like W3, a junction to descore like -W3, or term"
+            }
+            Expected::RobotAction => "cap, park or card",
+            Expected::Junction => "a junction, like W3",
+            Expected::Column => "a junction column, from 1 to 5",
+            Expected::Terminal => "a terminal, near or far",
+            Expected::ParkingLocation => {
+                "a parking location, left, middle, right, near, far or substation"
+            }
+            Expected::SignalZone => "a signal zone, left, middle or right",
+            Expected::Card => "a card, yellow or red",
+            Expected::Severity => "minor or major",
+            Expected::Rule => "a rule, like G402",
+            Expected::End => "the end of the command",
+        })
+    }
+}
+
+/// A problem with a command, and where it is.
+#[derive(Eq, PartialEq, Copy, Clone, Debug, Hash)]
+pub struct ParseError {
+    /// The byte offset of the problem in the text.
+    pub position: usize,
+    /// The length in bytes of the text that is wrong, which is 0 if something is missing.
+    pub len: usize,
+    pub expected: Expected,
+}
+
+impl Display for ParseError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        match self.len {
+            0 => write!(
+                f,
+                "expected {} at position {}, but found nothing",
+                self.expected, self.position
+            ),
+            _ => write!(
+                f,
+                "expected {} at position {}",
+                self.expected, self.position
+            ),
+        }
+    }
+}
+
+impl ParseError {
+    #[inline]
+    fn new(position: usize, text: &str, expected: Expected) -> Self {
+        Self {
+            position,
+            len: text.len(),
+            expected,
+        }
+    }
+}
+
+// the words of a command, with their positions
+struct Words<'a> {
+    text: &'a str,
+    offset: usize,
+}
+
+impl<'a> Words<'a> {
+    fn next(&mut self) -> Option<(usize, &'a str)> {
+        let rest = &self.text[self.offset..];
+        let start = self.offset + (rest.len() - rest.trim_start().len());
+        let word = self.text[start..]
+            .split(char::is_whitespace)
+            .next()
+            .filter(|word| !word.is_empty())?;
+        self.offset = start + word.len();
+        Some((start, word))
+    }
+
+    fn expect(&mut self, expected: Expected) -> Result<(usize, &'a str), ParseError> {
+        self.next()
+            .ok_or(ParseError::new(self.text.len(), "", expected))
+    }
+
+    fn parse<T>(
+        &mut self,
+        expected: Expected,
+        parse: impl FnOnce(usize, &str) -> Result<T, ParseError>,
+    ) -> Result<T, ParseError> {
+        let (position, word) = self.expect(expected)?;
+        parse(position, word)
+    }
+
+    fn finish(mut self) -> Result<(), ParseError> {
+        match self.next() {
+            Some((position, word)) => Err(ParseError::new(position, word, Expected::End)),
+            None => Ok(()),
+        }
+    }
+}
+
+const ALLIANCES: [(&str, Alliance); 4] = [
+    ("R", Alliance::RED),
+    ("B", Alliance::BLUE),
+    ("red", Alliance::RED),
+    ("blue", Alliance::BLUE),
+];
+const TERMINALS: [(&str, Terminal); 2] = [("near", Terminal::Near), ("far", Terminal::Far)];
+const SIGNAL_ZONES: [(&str, SignalZone); 3] = [
+    ("left", SignalZone::Left),
+    ("middle", SignalZone::Middle),
+    ("right", SignalZone::Right),
+];
+const PARKING_LOCATIONS: [(&str, ParkingLocation); 6] = [
+    ("left", ParkingLocation::LeftSignalZone),
+    ("middle", ParkingLocation::MiddleSignalZone),
+    ("right", ParkingLocation::RightSignalZone),
+    ("near", ParkingLocation::NearTerminal),
+    ("far", ParkingLocation::FarTerminal),
+    ("substation", ParkingLocation::Substation),
+];
+const CARDS: [(&str, Card); 2] = [("yellow", Card::Yellow), ("red", Card::Red)];
+const SEVERITIES: [(&str, u8); 2] = [("minor", 10), ("major", 30)];
+
+#[derive(Copy, Clone)]
+enum RobotAction {
+    Cap,
+    Park,
+    Card,
+}
+const ROBOT_ACTIONS: [(&str, RobotAction); 3] = [
+    ("cap", RobotAction::Cap),
+    ("park", RobotAction::Park),
+    ("card", RobotAction::Card),
+];
+
+fn keyword<T: Copy>(
+    position: usize,
+    word: &str,
+    expected: Expected,
+    keywords: &[(&str, T)],
+) -> Result<T, ParseError> {
+    if word.is_empty() {
+        return Err(ParseError::new(position, word, expected));
+    }
+    keywords
+        .iter()
+        .find(|(name, _)| name.eq_ignore_ascii_case(word))
+        .map(|&(_, value)| value)
+        .ok_or(ParseError::new(position, word, expected))
+}
+
+// the name of a junction, like W3, without allocating
+struct Name {
+    bytes: [u8; 2],
+    len: usize,
+}
+
+impl Write for Name {
+    fn write_str(&mut self, s: &str) -> core::fmt::Result {
+        let end = self.len + s.len();
+        self.bytes
+            .get_mut(self.len..end)
+            .ok_or(core::fmt::Error)?
+            .copy_from_slice(s.as_bytes());
+        self.len = end;
+        Ok(())
+    }
+}
+
+fn junction<T: FieldCoordinate>(position: usize, word: &str) -> Result<T, ParseError> {
+    let names = T::ALL.iter().map(|&junction| {
+        let mut name = Name {
+            bytes: [0; 2],
+            len: 0,
+        };
+        write!(name, "{junction}").expect("junction names are a letter and a digit");
+        (junction, name.bytes)
+    });
+    let Some(&row) = word.as_bytes().first() else {
+        return Err(ParseError::new(position, word, Expected::Junction));
+    };
+    let mut in_row = names
+        .filter(|(_, [letter, _])| letter.eq_ignore_ascii_case(&row))
+        .peekable();
+    if in_row.peek().is_none() {
+        return Err(ParseError::new(position, word, Expected::Junction));
+    }
+    // the row is an ascii letter, so the column starts at the next byte
+    let column = &word[1..];
+    in_row
+        .find(|(_, [_, digit])| column.as_bytes() == [*digit])
+        .map(|(junction, _)| junction)
+        .ok_or(ParseError::new(position + 1, column, Expected::Column))
+}
+
+fn any_junction(field: FieldType, position: usize, word: &str) -> Result<AnyJunction, ParseError> {
+    Ok(match field {
+        FieldType::Traditional => junction::<TraditionalJunction>(position, word)?.into(),
+        FieldType::RedRemote => junction::<RedRemoteJunction>(position, word)?.into(),
+        FieldType::BlueRemote => junction::<BlueRemoteJunction>(position, word)?.into(),
+    })
+}
+
+fn robot(position: usize, word: &str) -> Result<MatchIndex, ParseError> {
+    let alliance = match word.as_bytes().first().map(u8::to_ascii_uppercase) {
+        Some(b'R') => Alliance::RED,
+        Some(b'B') => Alliance::BLUE,
+        _ => return Err(ParseError::new(position, word, Expected::Robot)),
+    };
+    // the alliance is an ascii letter, so the number starts at the next byte
+    match &word[1..] {
+        "0" => Ok(MatchIndex::new(alliance, 0)),
+        "1" => Ok(MatchIndex::new(alliance, 1)),
+        number => Err(ParseError::new(position + 1, number, Expected::RobotNumber)),
+    }
+}
+
+fn rule(position: usize, word: &str) -> Result<(), ParseError> {
+    let digits = word.trim_start_matches(|c: char| c.is_ascii_alphabetic());
+    if digits.len() < word.len() && !digits.is_empty() && digits.bytes().all(|b| b.is_ascii_digit())
+    {
+        Ok(())
+    } else {
+        Err(ParseError::new(position, word, Expected::Rule))
+    }
+}
+
+/// Parses a command for a match on the given field.
+pub fn parse(field: FieldType, command: &str) -> Result<MatchAction, ParseError> {
+    let mut words = Words {
+        text: command,
+        offset: 0,
+    };
+    let (position, first) = words.expect(Expected::Command)?;
+    let action = if first.eq_ignore_ascii_case("next") {
+        MatchAction::AdvancePhase
+    } else if first.eq_ignore_ascii_case("foul") {
+        let alliance = words.parse(Expected::Alliance, |p, w| {
+            keyword(p, w, Expected::Alliance, &ALLIANCES)
+        })?;
+        let points = words.parse(Expected::Severity, |p, w| {
+            keyword(p, w, Expected::Severity, &SEVERITIES)
+        })?;
+        // the rule is only for the record
+        if let Some((position, word)) = words.next() {
+            rule(position, word)?;
+        }
+        MatchAction::Penalize { alliance, points }
+    } else if let Ok(alliance) = keyword(position, first, Expected::Alliance, &ALLIANCES) {
+        let (position, word) = words.expect(Expected::AllianceAction)?;
+        if word.eq_ignore_ascii_case("term") || word.eq_ignore_ascii_case("terminal") {
+            let terminal = words.parse(Expected::Terminal, |p, w| {
+                keyword(p, w, Expected::Terminal, &TERMINALS)
+            })?;
+            MatchAction::AddTerminal { alliance, terminal }
+        } else if let Some(junction) = word.strip_prefix('-') {
+            MatchAction::Descore {
+                junction: any_junction(field, position + 1, junction)?,
+            }
+        } else {
+            let junction =
+                any_junction(field, position, word).map_err(|error| match error.expected {
+                    // anything that is not even the start of a junction could have been any action
+                    Expected::Junction => ParseError::new(position, word, Expected::AllianceAction),
+                    _ => error,
+                })?;
+            MatchAction::Score { alliance, junction }
+        }
+    } else if first.starts_with(['R', 'r', 'B', 'b']) {
+        let robot = robot(position, first)?;
+        let (position, word) = words.expect(Expected::RobotAction)?;
+        match keyword(position, word, Expected::RobotAction, &ROBOT_ACTIONS)? {
+            RobotAction::Cap => MatchAction::Cap {
+                robot,
+                junction: words.parse(Expected::Junction, |p, w| any_junction(field, p, w))?,
+            },
+            RobotAction::Park => MatchAction::Park {
+                robot,
+                location: words.parse(Expected::ParkingLocation, |p, w| {
+                    keyword(p, w, Expected::ParkingLocation, &PARKING_LOCATIONS)
+                })?,
+            },
+            RobotAction::Card => MatchAction::Card {
+                robot,
+                card: words.parse(Expected::Card, |p, w| keyword(p, w, Expected::Card, &CARDS))?,
+            },
+        }
+    } else {
+        return Err(ParseError::new(position, first, Expected::Command));
+    };
+    words.finish()?;
+    Ok(action)
+}
+
+macro_rules! from_str_impl {
+    ($struc:ty, |$s:ident| $body:expr) => {
+        impl FromStr for $struc {
+            type Err = ParseError;
+
+            fn from_str($s: &str) -> Result<Self, Self::Err> {
+                $body
+            }
+        }
+    };
+}
+
+from_str_impl!(TraditionalJunction, |s| junction(0, s));
+from_str_impl!(RedRemoteJunction, |s| junction(0, s));
+from_str_impl!(BlueRemoteJunction, |s| junction(0, s));
+from_str_impl!(Alliance, |s| keyword(0, s, Expected::Alliance, &ALLIANCES));
+from_str_impl!(MatchIndex, |s| robot(0, s));
+from_str_impl!(Terminal, |s| keyword(0, s, Expected::Terminal, &TERMINALS));
+from_str_impl!(SignalZone, |s| keyword(
+    0,
+    s,
+    Expected::SignalZone,
+    &SIGNAL_ZONES
+));
+from_str_impl!(ParkingLocation, |s| keyword(
+    0,
+    s,
+    Expected::ParkingLocation,
+    &PARKING_LOCATIONS
+));
@@ -0,0 +1,572 @@
+//! Matches whose field and phase are only known at runtime.
+//!
+//! The phase types in [`traditional`](crate::traditional) and [`remote`](crate::remote) each change type
+//! when the match moves to the next phase, which makes them hard to store in one place.
+//! [`AnyMatch`] holds any of them behind one type, with one error type for every action.
+use crate::remote::*;
+use crate::traditional::*;
+use crate::*;
+use crate::encoding::{header, Reader};
+use crate::render::Render;
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::mem::replace;
+
+/// The period of a match.
+#[derive(Ord, PartialOrd, Eq, PartialEq, Copy, Clone, Debug, Hash)]
+#[repr(u8)]
+pub enum MatchPhase {
+    Auto,
+    TeleOp,
+    EndGame,
+    Finished,
+}
+crate::display_impl_as_debug!(MatchPhase);
+
+/// The kind of field a match is played on.
+#[derive(Eq, PartialEq, Copy, Clone, Debug, Hash)]
+#[repr(u8)]
+pub enum FieldType {
+    Traditional,
+    RedRemote,
+    BlueRemote,
+}
+crate::display_impl_as_debug!(FieldType);
+
+/// A junction on any field.
+#[derive(Eq, PartialEq, Copy, Clone, Debug, Hash)]
+pub enum AnyJunction {
+    Traditional(TraditionalJunction),
+    RedRemote(RedRemoteJunction),
+    BlueRemote(BlueRemoteJunction),
+}
+crate::display_impl_as_debug!(AnyJunction);
+
+impl AnyJunction {
+    /// The field the junction is on.
+    pub fn field_type(self) -> FieldType {
+        match self {
+            AnyJunction::Traditional(_) => FieldType::Traditional,
+            AnyJunction::RedRemote(_) => FieldType::RedRemote,
+            AnyJunction::BlueRemote(_) => FieldType::BlueRemote,
+        }
+    }
+}
+
+impl From<TraditionalJunction> for AnyJunction {
+    #[inline(always)]
+    fn from(value: TraditionalJunction) -> Self {
+        AnyJunction::Traditional(value)
+    }
+}
+
+impl From<RedRemoteJunction> for AnyJunction {
+    #[inline(always)]
+    fn from(value: RedRemoteJunction) -> Self {
+        AnyJunction::RedRemote(value)
+    }
+}
+
+impl From<BlueRemoteJunction> for AnyJunction {
+    #[inline(always)]
+    fn from(value: BlueRemoteJunction) -> Self {
+        AnyJunction::BlueRemote(value)
+    }
+}
+
+/// Every way an action on an [`AnyMatch`] can fail.
+#[derive(Eq, PartialEq, Copy, Clone, Debug, Hash)]
+#[repr(u8)]
+pub enum MatchError {
+    /// A cone was descored from a junction with no cones.
+    JunctionIsEmpty,
+    /// A cone was scored on or descored from a junction with a beacon.
+    BeaconOnJunction,
+    /// A beacon was scored on a junction that already has one.
+    JunctionIsCapped,
+    /// A robot that already scored its beacon tried to score another.
+    BeaconPreviouslyScored,
+    /// A beacon was scored before end game.
+    BeaconScoredOutsideEndgame,
+    /// The junction is not on this match's field.
+    WrongField,
+    /// The action cannot be taken in the current phase.
+    WrongPhase,
+    /// The robot or alliance is not part of this match.
+    NotInMatch,
+}
+crate::display_impl_as_debug!(MatchError);
+
+impl From<ConeRemovalError> for MatchError {
+    fn from(value: ConeRemovalError) -> Self {
+        match value {
+            ConeRemovalError::JunctionIsEmpty => MatchError::JunctionIsEmpty,
+            ConeRemovalError::BeaconOnJunction => MatchError::BeaconOnJunction,
+        }
+    }
+}
+
+impl From<BeaconError> for MatchError {
+    fn from(value: BeaconError) -> Self {
+        match value {
+            BeaconError::JunctionIsCapped => MatchError::JunctionIsCapped,
+            BeaconError::BeaconPreviouslyScored => MatchError::BeaconPreviouslyScored,
+        }
+    }
+}
+
+impl From<BeaconScoredOutsideEndgame> for MatchError {
+    #[inline(always)]
+    fn from(_: BeaconScoredOutsideEndgame) -> Self {
+        MatchError::BeaconScoredOutsideEndgame
+    }
+}
+
+/// One action on an [`AnyMatch`], so that actions can be stored and replayed.
+#[derive(Eq, PartialEq, Copy, Clone, Debug, Hash)]
+pub enum MatchAction {
+    Score { alliance: Alliance, junction: AnyJunction },
+    Descore { junction: AnyJunction },
+    AddTerminal { alliance: Alliance, terminal: Terminal },
+    Cap { robot: MatchIndex, junction: AnyJunction },
+    Penalize { alliance: Alliance, points: u8 },
+    Park { robot: MatchIndex, location: ParkingLocation },
+    Card { robot: MatchIndex, card: Card },
+    AdvancePhase,
+}
+crate::display_impl_as_debug!(MatchAction);
+
+/// The final results of a finished match.
+#[derive(Eq, PartialEq, Copy, Clone, Debug, Hash)]
+pub enum MatchResult {
+    Traditional(AllianceInfo<2>, AllianceInfo<2>),
+    Remote(AllianceInfo<1>),
+}
+
+/// One match on a given field, in any phase.
+#[derive(Debug, Clone)]
+pub enum MatchState<A, T, E, I> {
+    Auto(A),
+    TeleOp(T),
+    EndGame(E),
+    Finished(I),
+}
+
+pub type TraditionalMatch =
+    MatchState<TraditionalAuto, TraditionalTeleOp, TraditionalEndGame, (AllianceInfo<2>, AllianceInfo<2>)>;
+pub type RedRemoteMatch =
+    MatchState<RedRemoteAuto, RedRemoteTeleOp, RedRemoteEndGame, (AllianceInfo<1>, AllianceInfo<0>)>;
+pub type BlueRemoteMatch =
+    MatchState<BlueRemoteAuto, BlueRemoteTeleOp, BlueRemoteEndGame, (AllianceInfo<0>, AllianceInfo<1>)>;
+
+// a result that only exists while a phase is being replaced,
+// or the result of the missing alliance in a remote match, which has no teams
+fn placeholder_info<const N: usize>(alliance: Alliance) -> AllianceInfo<N> {
+    AllianceInfo {
+        alliance,
+        teams: [FtcTeamID(0); N],
+        penalty_points: 0,
+        auto_points: 0,
+        teleop_points: 0,
+        endgame_points: 0,
+        roster: None,
+        statuses: [RobotStatus::default(); N],
+    }
+}
+
+// like on_phase, but for matches that are known to still be in play
+macro_rules! on_playing_phase {
+    ($state:expr, $m:ident => $body:expr) => {
+        match $state {
+            MatchState::Auto($m) => $body,
+            MatchState::TeleOp($m) => $body,
+            MatchState::EndGame($m) => $body,
+            MatchState::Finished(_) => unreachable!("finished matches are handled separately"),
+        }
+    };
+}
+
+macro_rules! on_phase {
+    ($state:expr, $m:ident => $body:expr) => {
+        match $state {
+            MatchState::Auto($m) => $body,
+            MatchState::TeleOp($m) => $body,
+            MatchState::EndGame($m) => $body,
+            MatchState::Finished(_) => Err(MatchError::WrongPhase),
+        }
+    };
+}
+
+macro_rules! state_impl {
+    ($junction:ty, $r:literal, $b:literal) => {
+        impl<A, T, E> MatchState<A, T, E, (AllianceInfo<$r>, AllianceInfo<$b>)>
+        where
+            A: Auto<$junction, $r, $b, TeleOpType = T, ConeRemovalErrorType = ConeRemovalError>,
+            T: TeleOp<$junction, $r, $b, EndGameType = E, ConeRemovalErrorType = ConeRemovalError>,
+            E: EndGame<$junction, $r, $b, ConeRemovalErrorType = ConeRemovalError>,
+            A::BeaconErrorType: Into<MatchError>,
+            T::BeaconErrorType: Into<MatchError>,
+            E::BeaconErrorType: Into<MatchError>,
+        {
+            pub fn phase(&self) -> MatchPhase {
+                match self {
+                    MatchState::Auto(_) => MatchPhase::Auto,
+                    MatchState::TeleOp(_) => MatchPhase::TeleOp,
+                    MatchState::EndGame(_) => MatchPhase::EndGame,
+                    MatchState::Finished(_) => MatchPhase::Finished,
+                }
+            }
+
+            pub fn teams(&self, alliance: Alliance) -> &[FtcTeamID] {
+                match self {
+                    MatchState::Auto(m) => &m[alliance],
+                    MatchState::TeleOp(m) => &m[alliance],
+                    MatchState::EndGame(m) => &m[alliance],
+                    MatchState::Finished((red, blue)) => match alliance {
+                        Alliance::RED => &red.teams,
+                        Alliance::BLUE => &blue.teams,
+                    },
+                }
+            }
+
+            pub fn index_of(&self, robot: FtcTeamID) -> Option<MatchIndex> {
+                [Alliance::RED, Alliance::BLUE].into_iter().find_map(|alliance| {
+                    self.teams(alliance).iter()
+                        .position(|&team| team == robot)
+                        .map(|i| MatchIndex::new(alliance, i as u8))
+                })
+            }
+
+            fn check_alliance(&self, alliance: Alliance) -> Result<(), MatchError> {
+                if self.teams(alliance).is_empty() {
+                    Err(MatchError::NotInMatch)
+                } else {
+                    Ok(())
+                }
+            }
+
+            fn check_robot(&self, robot: MatchIndex) -> Result<(), MatchError> {
+                if robot.index() < self.teams(robot.alliance()).len() {
+                    Ok(())
+                } else {
+                    Err(MatchError::NotInMatch)
+                }
+            }
+
+            pub fn score_for(&mut self, alliance: Alliance, location: $junction) -> Result<(), MatchError> {
+                self.check_alliance(alliance)?;
+                on_phase!(self, m => if m.score_for(alliance, location) {
+                    Ok(())
+                } else {
+                    Err(MatchError::BeaconOnJunction)
+                })
+            }
+
+            pub fn descore(&mut self, location: $junction) -> Result<Alliance, MatchError> {
+                on_phase!(self, m => m.descore(location).map_err(Into::into))
+            }
+
+            pub fn add_terminal_for(&mut self, alliance: Alliance, terminal: Terminal) -> Result<(), MatchError> {
+                self.check_alliance(alliance)?;
+                on_phase!(self, m => {
+                    m.add_terminal_for(alliance, terminal);
+                    Ok(())
+                })
+            }
+
+            pub fn cap_for(&mut self, robot: MatchIndex, location: $junction) -> Result<(), MatchError> {
+                self.check_robot(robot)?;
+                on_phase!(self, m => m.cap_for(robot, location).map_err(Into::into))
+            }
+
+            pub fn penalize(&mut self, alliance: Alliance, points: u8) -> Result<(), MatchError> {
+                self.check_alliance(alliance)?;
+                on_phase!(self, m => {
+                    m.penalize(alliance, points);
+                    Ok(())
+                })
+            }
+
+            pub fn status_of(&self, robot: MatchIndex) -> Result<RobotStatus, MatchError> {
+                self.check_robot(robot)?;
+                on_phase!(self, m => Ok(m.status_of(robot)))
+            }
+
+            pub fn set_status(&mut self, robot: MatchIndex, status: RobotStatus) -> Result<(), MatchError> {
+                self.check_robot(robot)?;
+                on_phase!(self, m => {
+                    m.set_status(robot, status);
+                    Ok(())
+                })
+            }
+
+            pub fn card_for(&mut self, robot: MatchIndex, card: Card) -> Result<(), MatchError> {
+                self.check_robot(robot)?;
+                on_phase!(self, m => {
+                    m.card_for(robot, card);
+                    Ok(())
+                })
+            }
+
+            /// Parks a robot. Robots can park anywhere in auto, but only in a terminal in end game.
+            pub fn park_for(&mut self, robot: MatchIndex, location: ParkingLocation) -> Result<(), MatchError> {
+                self.check_robot(robot)?;
+                match self {
+                    MatchState::Auto(m) => m.park_for(robot, location),
+                    MatchState::EndGame(m) if location.is_terminal() => m.park_in_terminal_for(robot),
+                    _ => return Err(MatchError::WrongPhase),
+                }
+                Ok(())
+            }
+
+            /// Moves the match to its next phase, ending it after end game.
+            /// Returns the new phase, or an error if the match is already finished.
+            pub fn advance_phase(&mut self) -> Result<MatchPhase, MatchError> {
+                let placeholder = MatchState::Finished((placeholder_info(Alliance::RED), placeholder_info(Alliance::BLUE)));
+                *self = match replace(self, placeholder) {
+                    MatchState::Auto(m) => MatchState::TeleOp(m.into_teleop()),
+                    MatchState::TeleOp(m) => MatchState::EndGame(m.into_end_game()),
+                    MatchState::EndGame(m) => MatchState::Finished(EndGame::end_match(m)),
+                    finished => {
+                        *self = finished;
+                        return Err(MatchError::WrongPhase);
+                    }
+                };
+                Ok(self.phase())
+            }
+        }
+    };
+}
+
+state_impl!(TraditionalJunction, 2, 2);
+state_impl!(RedRemoteJunction, 1, 0);
+state_impl!(BlueRemoteJunction, 0, 1);
+
+/// A traditional or remote match in any phase.
+#[derive(Debug, Clone)]
+// matches store their junctions inline so that they never allocate, which makes traditional matches much larger
+#[allow(clippy::large_enum_variant)]
+pub enum AnyMatch {
+    Traditional(TraditionalMatch),
+    RedRemote(RedRemoteMatch),
+    BlueRemote(BlueRemoteMatch),
+}
+
+macro_rules! on_field {
+    ($this:expr, $m:ident => $body:expr) => {
+        match $this {
+            AnyMatch::Traditional($m) => $body,
+            AnyMatch::RedRemote($m) => $body,
+            AnyMatch::BlueRemote($m) => $body,
+        }
+    };
+}
+
+// applies an action that takes a junction, which must be on this match's field
+macro_rules! with_junction {
+    ($this:expr, $junction:expr, |$m:ident, $j:ident| $body:expr) => {
+        match ($this, $junction) {
+            (AnyMatch::Traditional($m), AnyJunction::Traditional($j)) => $body,
+            (AnyMatch::RedRemote($m), AnyJunction::RedRemote($j)) => $body,
+            (AnyMatch::BlueRemote($m), AnyJunction::BlueRemote($j)) => $body,
+            _ => Err(MatchError::WrongField),
+        }
+    };
+}
+
+impl AnyMatch {
+    pub fn field_type(&self) -> FieldType {
+        match self {
+            AnyMatch::Traditional(_) => FieldType::Traditional,
+            AnyMatch::RedRemote(_) => FieldType::RedRemote,
+            AnyMatch::BlueRemote(_) => FieldType::BlueRemote,
+        }
+    }
+
+    pub fn phase(&self) -> MatchPhase {
+        on_field!(self, m => m.phase())
+    }
+
+    #[inline]
+    pub fn is_finished(&self) -> bool {
+        self.phase() == MatchPhase::Finished
+    }
+
+    /// The teams on an alliance. This is empty for the missing alliance in a remote match.
+    pub fn teams(&self, alliance: Alliance) -> &[FtcTeamID] {
+        on_field!(self, m => m.teams(alliance))
+    }
+
+    pub fn index_of(&self, robot: FtcTeamID) -> Option<MatchIndex> {
+        on_field!(self, m => m.index_of(robot))
+    }
+
+    pub fn score_for(&mut self, alliance: Alliance, location: impl Into<AnyJunction>) -> Result<(), MatchError> {
+        with_junction!(self, location.into(), |m, j| m.score_for(alliance, j))
+    }
+
+    pub fn descore(&mut self, location: impl Into<AnyJunction>) -> Result<Alliance, MatchError> {
+        with_junction!(self, location.into(), |m, j| m.descore(j))
+    }
+
+    pub fn add_terminal_for(&mut self, alliance: Alliance, terminal: Terminal) -> Result<(), MatchError> {
+        on_field!(self, m => m.add_terminal_for(alliance, terminal))
+    }
+
+    pub fn cap_for(&mut self, robot: MatchIndex, location: impl Into<AnyJunction>) -> Result<(), MatchError> {
+        with_junction!(self, location.into(), |m, j| m.cap_for(robot, j))
+    }
+
+    pub fn penalize(&mut self, alliance: Alliance, points: u8) -> Result<(), MatchError> {
+        on_field!(self, m => m.penalize(alliance, points))
+    }
+
+    pub fn status_of(&self, robot: MatchIndex) -> Result<RobotStatus, MatchError> {
+        on_field!(self, m => m.status_of(robot))
+    }
+
+    pub fn set_status(&mut self, robot: MatchIndex, status: RobotStatus) -> Result<(), MatchError> {
+        on_field!(self, m => m.set_status(robot, status))
+    }
+
+    /// Gives a robot a card. A second yellow card in the same match becomes a red card.
+    pub fn card_for(&mut self, robot: MatchIndex, card: Card) -> Result<(), MatchError> {
+        on_field!(self, m => m.card_for(robot, card))
+    }
+
+    /// Parks a robot. Robots can park anywhere in auto, but only in a terminal in end game.
+    pub fn park_for(&mut self, robot: MatchIndex, location: impl Into<ParkingLocation>) -> Result<(), MatchError> {
+        let location = location.into();
+        on_field!(self, m => m.park_for(robot, location))
+    }
+
+    /// Moves the match to its next phase, ending it after end game.
+    /// Returns the new phase, or an error if the match is already finished.
+    pub fn advance_phase(&mut self) -> Result<MatchPhase, MatchError> {
+        on_field!(self, m => m.advance_phase())
+    }
+
+    /// Takes an action, as if the matching method had been called.
+    pub fn apply(&mut self, action: MatchAction) -> Result<(), MatchError> {
+        match action {
+            MatchAction::Score { alliance, junction } => self.score_for(alliance, junction),
+            MatchAction::Descore { junction } => self.descore(junction).map(drop),
+            MatchAction::AddTerminal { alliance, terminal } => self.add_terminal_for(alliance, terminal),
+            MatchAction::Cap { robot, junction } => self.cap_for(robot, junction),
+            MatchAction::Penalize { alliance, points } => self.penalize(alliance, points),
+            MatchAction::Park { robot, location } => self.park_for(robot, location),
+            MatchAction::Card { robot, card } => self.card_for(robot, card),
+            MatchAction::AdvancePhase => self.advance_phase().map(drop),
+        }
+    }
+
+    /// The results of the match, if it is finished.
+    pub fn results(&self) -> Option<MatchResult> {
+        match self {
+            AnyMatch::Traditional(MatchState::Finished((red, blue))) => Some(MatchResult::Traditional(*red, *blue)),
+            AnyMatch::RedRemote(MatchState::Finished((red, _))) => Some(MatchResult::Remote(*red)),
+            AnyMatch::BlueRemote(MatchState::Finished((_, blue))) => Some(MatchResult::Remote(*blue)),
+            _ => None,
+        }
+    }
+
+    /// The field as plain text, or None if the match is finished. See [`crate::render`].
+    pub fn to_ascii(&self) -> Option<String> {
+        (!self.is_finished()).then(|| on_field!(self, m => on_playing_phase!(m, m => m.to_ascii())))
+    }
+
+    /// The field as an SVG image, or None if the match is finished. See [`crate::render`].
+    pub fn to_svg(&self) -> Option<String> {
+        (!self.is_finished()).then(|| on_field!(self, m => on_playing_phase!(m, m => m.to_svg())))
+    }
+}
+
+impl crate::sealed::Sealed for AnyMatch {}
+
+impl AnyMatch {
+    fn encode_into(&self, out: &mut Vec<u8>) {
+        let finished = |out: &mut Vec<u8>| out.push(header(self.field_type(), MatchPhase::Finished));
+        match self {
+            AnyMatch::Traditional(MatchState::Finished((red, blue))) => {
+                finished(out);
+                red.encode_into(out);
+                blue.encode_into(out);
+            }
+            AnyMatch::RedRemote(MatchState::Finished((red, _))) => {
+                finished(out);
+                red.encode_into(out);
+            }
+            AnyMatch::BlueRemote(MatchState::Finished((_, blue))) => {
+                finished(out);
+                blue.encode_into(out);
+            }
+            AnyMatch::Traditional(m) => on_playing_phase!(m, m => m.encode_into(out)),
+            AnyMatch::RedRemote(m) => on_playing_phase!(m, m => m.encode_into(out)),
+            AnyMatch::BlueRemote(m) => on_playing_phase!(m, m => m.encode_into(out)),
+        }
+    }
+
+    fn decode_from(reader: &mut Reader) -> Option<Self> {
+        use FieldType::*;
+        use MatchPhase::*;
+        let first = *reader.peek()?;
+        let (field, phase) = [Traditional, RedRemote, BlueRemote]
+            .into_iter()
+            .flat_map(|field| [Auto, TeleOp, EndGame, Finished].map(|phase| (field, phase)))
+            .find(|&(field, phase)| header(field, phase) == first)
+            .or_else(|| reader.invalid())?;
+        Some(match (field, phase) {
+            (Traditional, Auto) => TraditionalAuto::decode_from(reader)?.into(),
+            (Traditional, TeleOp) => TraditionalTeleOp::decode_from(reader)?.into(),
+            (Traditional, EndGame) => TraditionalEndGame::decode_from(reader)?.into(),
+            (RedRemote, Auto) => RedRemoteAuto::decode_from(reader)?.into(),
+            (RedRemote, TeleOp) => RedRemoteTeleOp::decode_from(reader)?.into(),
+            (RedRemote, EndGame) => RedRemoteEndGame::decode_from(reader)?.into(),
+            (BlueRemote, Auto) => BlueRemoteAuto::decode_from(reader)?.into(),
+            (BlueRemote, TeleOp) => BlueRemoteTeleOp::decode_from(reader)?.into(),
+            (BlueRemote, EndGame) => BlueRemoteEndGame::decode_from(reader)?.into(),
+            (field, Finished) => {
+                reader.byte()?;
+                match field {
+                    Traditional => AnyMatch::Traditional(MatchState::Finished((
+                        AllianceInfo::decode_from(Alliance::RED, reader)?,
+                        AllianceInfo::decode_from(Alliance::BLUE, reader)?,
+                    ))),
+                    RedRemote => AnyMatch::RedRemote(MatchState::Finished((
+                        AllianceInfo::decode_from(Alliance::RED, reader)?,
+                        placeholder_info(Alliance::BLUE),
+                    ))),
+                    BlueRemote => AnyMatch::BlueRemote(MatchState::Finished((
+                        placeholder_info(Alliance::RED),
+                        AllianceInfo::decode_from(Alliance::BLUE, reader)?,
+                    ))),
+                }
+            }
+        })
+    }
+}
+
+crate::encoding::compact_impl!(AnyMatch);
+
+macro_rules! from_phase_impl {
+    ($variant:ident, $phase:ident, $($struc:ty),+) => {
+        $(
+            impl From<$struc> for AnyMatch {
+                #[inline(always)]
+                fn from(value: $struc) -> Self {
+                    AnyMatch::$variant(MatchState::$phase(value))
+                }
+            }
+        )+
+    };
+}
+
+from_phase_impl!(Traditional, Auto, TraditionalAuto);
+from_phase_impl!(Traditional, TeleOp, TraditionalTeleOp);
+from_phase_impl!(Traditional, EndGame, TraditionalEndGame);
+from_phase_impl!(RedRemote, Auto, RedRemoteAuto);
+from_phase_impl!(RedRemote, TeleOp, RedRemoteTeleOp);
+from_phase_impl!(RedRemote, EndGame, RedRemoteEndGame);
+from_phase_impl!(BlueRemote, Auto, BlueRemoteAuto);
+from_phase_impl!(BlueRemote, TeleOp, BlueRemoteTeleOp);
+from_phase_impl!(BlueRemote, EndGame, BlueRemoteEndGame);
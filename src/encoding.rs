@@ -0,0 +1,434 @@
+//! The module for a compact binary format of match states, for storing many matches or sending them over slow links.
+//!
+//! Every phase type implements [`Compact`], as does [`AnyMatch`](crate::dynamic::AnyMatch), which also encodes
+//! finished matches. A traditional match in play takes about 32 bytes, plus 2 for each junction with up to 8 cones,
+//! and a remote match about 14, plus 1 for each junction with cones.
+//! Subscribers and the events of the last action are not encoded.
+//!
+//! # Format
+//! Numbers that are usually small are LEB128 varints, and team numbers are zigzag encoded first, since test
+//! teams are negative. Enums are stored as their `u8` representation, and junctions as their 8-bit encoding.
+//!
+//! A match starts with a byte holding its field in bits 2 and up (0 traditional, 1 red remote, 2 blue remote)
+//! and its phase in bits 0 and 1 (0 auto, 1 teleop, 2 end game, 3 finished).
+//!
+//! Matches in play follow with each alliance, red first, that has teams. An alliance is its team numbers,
+//! penalty points, auto points, and near and far terminal cones, then two bytes for each robot, then its roster.
+//! The first robot byte holds its beacon in bits 0 and 1 (0 none, 1 placed, 2 placed wrongly) and where it is
+//! parked in bits 2 to 4 (0 nowhere, then the left, middle and right signal zones, near and far terminals, and
+//! the substation). The second holds its card in bits 0 and 1 (0 none, 1 yellow, 2 red), then whether it did
+//! not show, was disabled and was disqualified. A placed beacon follows with its junction.
+//! A roster is 0 for none, or the number of its teams in bits 0 to 3 and one more than the position of the
+//! replaced team in bits 4 to 7, followed by the teams.
+//!
+//! Traditional matches then hold which junctions have cones in 4 bytes, with bit `row * 5 + column` set for each,
+//! and each of those stacks in row-major order as its height and its cones from the bottom, one bit each and
+//! set for blue, in as few bytes as they fit. Remote matches hold their circuit pattern, which junctions have
+//! cones in 2 bytes, and how many cones each of those has.
+//! Auto ends with whether each robot has a signal sleeve, one bit each from bit 0, then the signal zone.
+//!
+//! Finished matches hold the results of each alliance with teams: its team numbers, penalty, auto, teleop and
+//! end game points, its roster, and the second robot byte for each robot.
+use crate::junction_map::JunctionMap;
+use crate::MaybeInvalid::{Invalid, Valid};
+use crate::*;
+use alloc::vec::Vec;
+
+/// Every way decoding a match can fail.
+#[derive(Eq, PartialEq, Copy, Clone, Debug, Hash)]
+pub enum DecodeError {
+    /// The bytes ended partway through the match.
+    UnexpectedEnd,
+    /// The byte at this offset is not valid where it is.
+    InvalidByte(usize),
+    /// The bytes hold a match of another field or phase.
+    WrongType,
+    /// The match ended before the bytes did.
+    TrailingBytes,
+}
+crate::display_impl_as_debug!(DecodeError);
+
+/// A match that can be stored in a few dozen bytes.
+pub trait Compact: crate::sealed::Sealed + Sized {
+    fn to_bytes(&self) -> Vec<u8>;
+    fn from_bytes(bytes: &[u8]) -> Result<Self, DecodeError>;
+}
+
+// implements Compact with a write method and a read method that returns None for anything invalid
+macro_rules! compact_impl {
+    ($($struc:ty),+) => {
+        $(
+            impl crate::encoding::Compact for $struc {
+                fn to_bytes(&self) -> alloc::vec::Vec<u8> {
+                    let mut out = alloc::vec::Vec::new();
+                    self.encode_into(&mut out);
+                    out
+                }
+
+                fn from_bytes(bytes: &[u8]) -> Result<Self, crate::encoding::DecodeError> {
+                    crate::encoding::Reader::new(bytes).finish(Self::decode_from)
+                }
+            }
+        )+
+    };
+}
+pub(crate) use compact_impl;
+
+// the first byte of an encoded match
+#[inline]
+pub(crate) fn header(field: dynamic::FieldType, phase: dynamic::MatchPhase) -> u8 {
+    (field as u8) << 2 | phase as u8
+}
+
+pub(crate) const PARKING_LOCATIONS: [ParkingLocation; 6] = {
+    use ParkingLocation::*;
+    [LeftSignalZone, MiddleSignalZone, RightSignalZone, NearTerminal, FarTerminal, Substation]
+};
+
+/// Reads values in order, returning None at the end of the bytes or at an invalid value.
+pub(crate) struct Reader<'a> {
+    bytes: &'a [u8],
+    offset: usize,
+    // set once a value is invalid, rather than missing
+    invalid: bool,
+}
+
+impl<'a> Reader<'a> {
+    pub(crate) fn new(bytes: &'a [u8]) -> Self {
+        Self { bytes, offset: 0, invalid: false }
+    }
+
+    /// Reads a whole value, which has to take up every byte.
+    pub(crate) fn finish<T>(mut self, read: impl FnOnce(&mut Self) -> Option<T>) -> Result<T, DecodeError> {
+        match read(&mut self) {
+            Some(_) if !self.is_empty() => Err(DecodeError::TrailingBytes),
+            Some(value) => Ok(value),
+            None if self.invalid && self.offset == 0 => Err(DecodeError::WrongType),
+            None if self.invalid => Err(DecodeError::InvalidByte(self.offset - 1)),
+            None => Err(DecodeError::UnexpectedEnd),
+        }
+    }
+
+    /// Fails at the last byte read.
+    pub(crate) fn invalid<T>(&mut self) -> Option<T> {
+        self.invalid = true;
+        None
+    }
+
+    /// Reads the header of a match, failing if it is another type of match.
+    pub(crate) fn header(&mut self, field: dynamic::FieldType, phase: dynamic::MatchPhase) -> Option<()> {
+        let byte = self.byte()?;
+        if byte == header(field, phase) {
+            Some(())
+        } else {
+            // a header that does not match is the wrong type, not a bad byte
+            self.offset -= 1;
+            self.invalid()
+        }
+    }
+
+    pub(crate) fn is_empty(&self) -> bool {
+        self.offset == self.bytes.len()
+    }
+
+    pub(crate) fn peek(&self) -> Option<&u8> {
+        self.bytes.get(self.offset)
+    }
+
+    pub(crate) fn byte(&mut self) -> Option<u8> {
+        let byte = *self.bytes.get(self.offset)?;
+        self.offset += 1;
+        Some(byte)
+    }
+
+    pub(crate) fn bytes<const N: usize>(&mut self) -> Option<[u8; N]> {
+        let bytes = self.bytes.get(self.offset..)?.first_chunk::<N>()?;
+        self.offset += N;
+        Some(*bytes)
+    }
+
+    pub(crate) fn variant<T: Copy>(&mut self, variants: &[T], encoding: fn(T) -> u8) -> Option<T> {
+        let byte = self.byte()?;
+        match variants.iter().copied().find(|&variant| encoding(variant) == byte) {
+            Some(variant) => Some(variant),
+            None => self.invalid(),
+        }
+    }
+
+    pub(crate) fn flag(&mut self) -> Option<bool> {
+        self.variant(&[false, true], |flag| flag as u8)
+    }
+
+    pub(crate) fn varint(&mut self) -> Option<u32> {
+        let mut value = 0u32;
+        for shift in (0..35).step_by(7) {
+            let byte = self.byte()?;
+            // the fifth byte only has room for the top four bits, and anything above them would be lost
+            if shift == 28 && byte & 0x70 != 0 {
+                return self.invalid();
+            }
+            value |= ((byte & 0x7f) as u32) << shift;
+            if byte & 0x80 == 0 {
+                return Some(value);
+            }
+        }
+        self.invalid()
+    }
+
+    pub(crate) fn small(&mut self) -> Option<u16> {
+        match self.varint()?.try_into() {
+            Ok(value) => Some(value),
+            Err(_) => self.invalid(),
+        }
+    }
+
+    pub(crate) fn team(&mut self) -> Option<FtcTeamID> {
+        let zigzag = self.varint()?;
+        Some(FtcTeamID((zigzag >> 1) as i32 ^ -((zigzag & 1) as i32)))
+    }
+
+    pub(crate) fn signal_zone(&mut self) -> Option<SignalZone> {
+        self.variant(&[SignalZone::Left, SignalZone::Middle, SignalZone::Right], |z| z as u8)
+    }
+
+    pub(crate) fn junction<T: FieldCoordinate>(&mut self) -> Option<T> {
+        self.variant(T::ALL, junction_encoding)
+    }
+}
+
+/// The 8-bit encoding of any junction.
+#[inline]
+pub(crate) fn junction_encoding<T: FieldCoordinate>(junction: T) -> u8 {
+    junction.row() << 5 | junction.column() << 2 | (junction.points() - 2)
+}
+
+pub(crate) fn write_varint(out: &mut Vec<u8>, mut value: u32) {
+    while value >= 0x80 {
+        out.push(value as u8 | 0x80);
+        value >>= 7;
+    }
+    out.push(value as u8);
+}
+
+pub(crate) fn write_team(out: &mut Vec<u8>, team: FtcTeamID) {
+    write_varint(out, ((team.0 << 1) ^ (team.0 >> 31)) as u32);
+}
+
+fn status_byte(status: RobotStatus) -> u8 {
+    let card = match status.card {
+        None => 0,
+        Some(Card::Yellow) => 1,
+        Some(Card::Red) => 2,
+    };
+    card | (status.no_show as u8) << 2 | (status.disabled as u8) << 3 | (status.disqualified as u8) << 4
+}
+
+fn read_status(reader: &mut Reader) -> Option<RobotStatus> {
+    let byte = reader.byte()?;
+    let card = match byte & 0b11 {
+        0 => None,
+        1 => Some(Card::Yellow),
+        2 => Some(Card::Red),
+        _ => return reader.invalid(),
+    };
+    if byte >> 5 != 0 {
+        return reader.invalid();
+    }
+    Some(RobotStatus {
+        no_show: byte & 0b100 != 0,
+        disabled: byte & 0b1000 != 0,
+        card,
+        disqualified: byte & 0b1_0000 != 0,
+    })
+}
+
+fn write_roster(out: &mut Vec<u8>, roster: Option<AllianceRoster>) {
+    match roster {
+        None => out.push(0),
+        Some(roster) => {
+            let teams = roster.teams();
+            let replaced = roster.replaced().map_or(0, |team| teams.iter().position(|&t| t == team).unwrap() + 1);
+            out.push(teams.len() as u8 | (replaced as u8) << 4);
+            for &team in teams {
+                write_team(out, team);
+            }
+        }
+    }
+}
+
+fn read_roster(reader: &mut Reader) -> Option<Option<AllianceRoster>> {
+    let byte = reader.byte()?;
+    if byte == 0 {
+        return Some(None);
+    }
+    let (len, replaced) = ((byte & 0xf) as usize, (byte >> 4) as usize);
+    if !(2..=4).contains(&len) || replaced > len {
+        return reader.invalid();
+    }
+    let mut teams = [FtcTeamID(0); 4];
+    for team in &mut teams[..len] {
+        *team = reader.team()?;
+    }
+    // the backup robot is always added last
+    let (picks, backup) = if replaced == 0 { (len, None) } else { (len - 1, Some(teams[len - 1])) };
+    let roster = AllianceRoster::new(teams[0], teams[1], (picks > 2).then_some(teams[2])).and_then(|mut roster| {
+        if let Some(backup) = backup {
+            roster.substitute(teams[replaced - 1], backup)?;
+        }
+        Ok(roster)
+    });
+    match roster {
+        Ok(roster) => Some(Some(roster)),
+        Err(_) => reader.invalid(),
+    }
+}
+
+impl<T: FieldCoordinate, const N: usize> InternalAllianceInfo<T, N> {
+    pub(crate) fn encode_into(&self, out: &mut Vec<u8>) {
+        for &team in &self.teams {
+            write_team(out, team);
+        }
+        write_varint(out, self.penalty_points as u32);
+        write_varint(out, self.auto_points as u32);
+        out.extend(self.terminal_amounts);
+        for i in 0..N {
+            let beacon = match self.beacon_placements[i] {
+                MaybeInvalid::None => 0,
+                Valid(_) => 1,
+                Invalid => 2,
+            };
+            let parking = self.parking_locations[i]
+                .map_or(0, |location| PARKING_LOCATIONS.iter().position(|&l| l == location).unwrap() as u8 + 1);
+            out.extend([beacon | parking << 2, status_byte(self.statuses[i])]);
+            if let Valid(junction) = self.beacon_placements[i] {
+                out.push(junction_encoding(junction));
+            }
+        }
+        write_roster(out, self.roster);
+    }
+
+    pub(crate) fn decode_from(reader: &mut Reader) -> Option<Self> {
+        let mut teams = [FtcTeamID(0); N];
+        for team in &mut teams {
+            *team = reader.team()?;
+        }
+        let mut info = Self::new(teams);
+        info.penalty_points = reader.small()?;
+        info.auto_points = reader.small()?;
+        info.terminal_amounts = reader.bytes()?;
+        for i in 0..N {
+            let byte = reader.byte()?;
+            let parking = (byte >> 2) as usize;
+            if byte >> 5 != 0 || parking > PARKING_LOCATIONS.len() {
+                return reader.invalid();
+            }
+            info.parking_locations[i] = parking.checked_sub(1).map(|i| PARKING_LOCATIONS[i]);
+            info.statuses[i] = read_status(reader)?;
+            info.beacon_placements[i] = match byte & 0b11 {
+                0 => MaybeInvalid::None,
+                1 => Valid(reader.junction()?),
+                2 => Invalid,
+                _ => return reader.invalid(),
+            };
+        }
+        info.roster = read_roster(reader)?;
+        Some(info)
+    }
+}
+
+impl<const N: usize> AllianceInfo<N> {
+    pub(crate) fn encode_into(&self, out: &mut Vec<u8>) {
+        for &team in &self.teams {
+            write_team(out, team);
+        }
+        for points in [self.penalty_points, self.auto_points, self.teleop_points, self.endgame_points] {
+            write_varint(out, points as u32);
+        }
+        write_roster(out, self.roster);
+        out.extend(self.statuses.map(status_byte));
+    }
+
+    pub(crate) fn decode_from(alliance: Alliance, reader: &mut Reader) -> Option<Self> {
+        let mut teams = [FtcTeamID(0); N];
+        for team in &mut teams {
+            *team = reader.team()?;
+        }
+        let [penalty_points, auto_points, teleop_points, endgame_points] =
+            [reader.small()?, reader.small()?, reader.small()?, reader.small()?];
+        let roster = read_roster(reader)?;
+        let mut statuses = [RobotStatus::default(); N];
+        for status in &mut statuses {
+            *status = read_status(reader)?;
+        }
+        Some(Self { alliance, teams, penalty_points, auto_points, teleop_points, endgame_points, roster, statuses })
+    }
+}
+
+/// Writes which junctions have a value, then each value in row-major order.
+pub(crate) fn write_junctions<T: FieldCoordinate, V>(
+    out: &mut Vec<u8>,
+    map: &JunctionMap<T, V>,
+    mut value: impl FnMut(&mut Vec<u8>, &V),
+) {
+    let set = map
+        .iter()
+        .fold(0u32, |set, (junction, _)| set | 1 << (junction.row() * T::COLUMNS + junction.column()));
+    out.extend_from_slice(&set.to_le_bytes()[..(T::ROWS * T::COLUMNS).div_ceil(8) as usize]);
+    for (_, v) in map.iter() {
+        value(out, v);
+    }
+}
+
+pub(crate) fn read_junctions<T: FieldCoordinate, V>(
+    reader: &mut Reader,
+    mut value: impl FnMut(&mut Reader) -> Option<V>,
+) -> Option<JunctionMap<T, V>> {
+    let len = (T::ROWS * T::COLUMNS) as usize;
+    let mut set = [0; 4];
+    for byte in &mut set[..len.div_ceil(8)] {
+        *byte = reader.byte()?;
+    }
+    let set = u32::from_le_bytes(set);
+    if set >> len != 0 {
+        return reader.invalid();
+    }
+    let mut map = JunctionMap::default();
+    // T::ALL is in row-major order
+    for (i, &junction) in T::ALL.iter().enumerate() {
+        if set & 1 << i != 0 {
+            map.insert(junction, value(reader)?);
+        }
+    }
+    Some(map)
+}
+
+impl ConeStack {
+    pub(crate) fn encode_into(&self, out: &mut Vec<u8>) {
+        let height = as_u8(self.top_idx);
+        out.push(height);
+        // cones start at the second bit
+        for chunk in self.data[1..=height as usize].chunks(8) {
+            out.push(chunk.load_le::<u8>());
+        }
+    }
+
+    pub(crate) fn decode_from(reader: &mut Reader) -> Option<Self> {
+        let height = reader.byte()? as usize;
+        // the stack holds at most 63 cones
+        if !(1..64).contains(&height) {
+            return reader.invalid();
+        }
+        let mut cones = BitArray::<[u8; 8], Lsb0>::ZERO;
+        for i in 0..height.div_ceil(8) {
+            cones.as_raw_mut_slice()[i] = reader.byte()?;
+        }
+        if cones[height..].any() {
+            return reader.invalid();
+        }
+        let mut stack = ConeStack::new(Alliance::from(cones[0]));
+        for cone in &cones[1..height] {
+            stack.push(Alliance::from(*cone));
+        }
+        Some(stack)
+    }
+}
@@ -0,0 +1,169 @@
+//! The module for reacting to a match as it is played.
+//! Every phase type implements [`Observable`], which delivers a [`MatchEvent`] to each subscriber as soon as
+//! the action behind it happens. Subscribers stay with the match from one phase to the next.
+use crate::dynamic::MatchPhase;
+use crate::{Alliance, FieldCoordinate, MatchIndex, Terminal};
+use alloc::boxed::Box;
+use alloc::vec::Vec;
+use core::fmt::{Debug, Display, Formatter};
+
+/// A change in who possesses the field, caused by scoring or removing a cone or beacon.
+#[derive(Eq, PartialEq, Copy, Clone, Debug, Hash)]
+pub enum FieldEvent<T: FieldCoordinate> {
+    /// A junction changed hands. Either side is None when the junction is empty.
+    OwnershipChanged {
+        junction: T,
+        previous: Option<Alliance>,
+        current: Option<Alliance>,
+    },
+    /// The alliance now scores a circuit.
+    CircuitFormed(Alliance),
+    /// The alliance no longer scores a circuit.
+    CircuitBroken(Alliance),
+}
+// cannot use macro because of the type parameter
+impl<T: FieldCoordinate> Display for FieldEvent<T> {
+    #[inline(always)]
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        <Self as Debug>::fmt(self, f)
+    }
+}
+
+/// Something that happened in a match.
+/// Actions that change possession are followed by a [`MatchEvent::Field`] for each change.
+#[derive(Eq, PartialEq, Copy, Clone, Debug, Hash)]
+pub enum MatchEvent<T: FieldCoordinate> {
+    ConeScored { alliance: Alliance, junction: T },
+    /// `alliance` is the alliance whose cone was removed.
+    ConeRemoved { alliance: Alliance, junction: T },
+    TerminalScored { alliance: Alliance, terminal: Terminal },
+    BeaconPlaced { robot: MatchIndex, junction: T },
+    Field(FieldEvent<T>),
+    /// The match moved to this phase. Ending the match moves it to [`MatchPhase::Finished`].
+    PhaseChanged(MatchPhase),
+}
+// cannot use macro because of the type parameter
+impl<T: FieldCoordinate> Display for MatchEvent<T> {
+    #[inline(always)]
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        <Self as Debug>::fmt(self, f)
+    }
+}
+
+impl<T: FieldCoordinate> MatchEvent<T> {
+    /// Moves the event onto another field, for fields that share a representation.
+    pub(crate) fn map<U: FieldCoordinate>(
+        self,
+        junction: impl Fn(T) -> U,
+        alliance: impl Fn(Alliance) -> Alliance,
+    ) -> MatchEvent<U> {
+        let field = |event| match event {
+            FieldEvent::OwnershipChanged { junction: j, previous, current } => FieldEvent::OwnershipChanged {
+                junction: junction(j),
+                previous: previous.map(&alliance),
+                current: current.map(&alliance),
+            },
+            FieldEvent::CircuitFormed(a) => FieldEvent::CircuitFormed(alliance(a)),
+            FieldEvent::CircuitBroken(a) => FieldEvent::CircuitBroken(alliance(a)),
+        };
+        match self {
+            MatchEvent::ConeScored { alliance: a, junction: j } => {
+                MatchEvent::ConeScored { alliance: alliance(a), junction: junction(j) }
+            }
+            MatchEvent::ConeRemoved { alliance: a, junction: j } => {
+                MatchEvent::ConeRemoved { alliance: alliance(a), junction: junction(j) }
+            }
+            MatchEvent::TerminalScored { alliance: a, terminal } => {
+                MatchEvent::TerminalScored { alliance: alliance(a), terminal }
+            }
+            MatchEvent::BeaconPlaced { robot, junction: j } => MatchEvent::BeaconPlaced {
+                robot: MatchIndex::new(alliance(robot.alliance()), robot.index() as u8),
+                junction: junction(j),
+            },
+            MatchEvent::Field(event) => MatchEvent::Field(field(event)),
+            MatchEvent::PhaseChanged(phase) => MatchEvent::PhaseChanged(phase),
+        }
+    }
+}
+
+/// Identifies a subscriber, so that it can be removed later.
+#[derive(Ord, PartialOrd, Eq, PartialEq, Copy, Clone, Debug, Hash)]
+pub struct SubscriptionId(u32);
+crate::display_impl_as_debug!(SubscriptionId);
+
+type Subscriber<T> = Box<dyn FnMut(&MatchEvent<T>) -> bool + Send + Sync>;
+
+/// The subscribers of a single match.
+// copies of a match start without subscribers, so that forks and simulations never notify anyone
+pub(crate) struct Subscribers<T: FieldCoordinate> {
+    subscribers: Vec<(SubscriptionId, Subscriber<T>)>,
+    next_id: u32,
+}
+
+impl<T: FieldCoordinate> Default for Subscribers<T> {
+    fn default() -> Self {
+        Self { subscribers: Vec::new(), next_id: 0 }
+    }
+}
+
+impl<T: FieldCoordinate> Clone for Subscribers<T> {
+    fn clone(&self) -> Self {
+        Self::default()
+    }
+}
+
+impl<T: FieldCoordinate> Debug for Subscribers<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        write!(f, "Subscribers({})", self.subscribers.len())
+    }
+}
+
+impl<T: FieldCoordinate> Subscribers<T> {
+    pub(crate) fn add(&mut self, subscriber: Subscriber<T>) -> SubscriptionId {
+        let id = SubscriptionId(self.next_id);
+        self.next_id += 1;
+        self.subscribers.push((id, subscriber));
+        id
+    }
+
+    pub(crate) fn remove(&mut self, id: SubscriptionId) -> bool {
+        let len = self.subscribers.len();
+        self.subscribers.retain(|(other, _)| *other != id);
+        self.subscribers.len() != len
+    }
+
+    #[inline]
+    pub(crate) fn publish(&mut self, event: MatchEvent<T>) {
+        if !self.subscribers.is_empty() {
+            self.subscribers.retain_mut(|(_, subscriber)| subscriber(&event));
+        }
+    }
+}
+
+/// A match that can be watched as it is played.
+pub trait Observable<T: FieldCoordinate>: crate::sealed::Sealed {
+    /// Calls `subscriber` with every event from now on, until it returns false.
+    fn subscribe_until(
+        &mut self,
+        subscriber: impl FnMut(&MatchEvent<T>) -> bool + Send + Sync + 'static,
+    ) -> SubscriptionId;
+    /// Removes a subscriber, returning whether it was still subscribed.
+    fn unsubscribe(&mut self, id: SubscriptionId) -> bool;
+    /// Calls `subscriber` with every event from now on.
+    fn subscribe(&mut self, mut subscriber: impl FnMut(&MatchEvent<T>) + Send + Sync + 'static) -> SubscriptionId {
+        self.subscribe_until(move |event| {
+            subscriber(event);
+            true
+        })
+    }
+    /// Sends every event from now on to the returned receiver, until it is dropped.
+    #[cfg(feature = "std")]
+    fn event_channel(&mut self) -> std::sync::mpsc::Receiver<MatchEvent<T>>
+    where
+        T: Send,
+    {
+        let (sender, receiver) = std::sync::mpsc::channel();
+        self.subscribe_until(move |event| sender.send(*event).is_ok());
+        receiver
+    }
+}
@@ -0,0 +1,434 @@
+//! C bindings for the scoring engine.
+//!
+//! Matches are opaque [`FtcMatch`] handles, created by `ftc_traditional_new` or `ftc_remote_new`
+//! and released with `ftc_match_free`. Every function returns an [`FtcError`], and writes any other output
+//! through pointers. Panics never cross into C; they are reported as [`FtcError::Panic`].
+//!
+//! Enumerations are passed as `uint8_t`s, using the `FTC_*` constants.
+//! Junctions are passed as the row and column of their [`FieldCoordinate`].
+//!
+//! # Safety
+//! Every pointer passed to these functions must be null or valid, and a match must not be used after it is freed
+//! or from two threads at once. Null pointers are reported as [`FtcError::NullPointer`] unless documented otherwise.
+// the safety requirements are shared by every function, so they are documented once above
+#![allow(clippy::missing_safety_doc)]
+use crate::dynamic::*;
+use crate::remote::*;
+use crate::traditional::*;
+use crate::*;
+use std::panic::{catch_unwind, AssertUnwindSafe};
+
+pub const FTC_ALLIANCE_RED: u8 = 0;
+pub const FTC_ALLIANCE_BLUE: u8 = 1;
+
+pub const FTC_FIELD_TRADITIONAL: u8 = 0;
+pub const FTC_FIELD_RED_REMOTE: u8 = 1;
+pub const FTC_FIELD_BLUE_REMOTE: u8 = 2;
+
+pub const FTC_PHASE_AUTO: u8 = 0;
+pub const FTC_PHASE_TELEOP: u8 = 1;
+pub const FTC_PHASE_END_GAME: u8 = 2;
+pub const FTC_PHASE_FINISHED: u8 = 3;
+
+pub const FTC_SIGNAL_ZONE_LEFT: u8 = 0;
+pub const FTC_SIGNAL_ZONE_MIDDLE: u8 = 1;
+pub const FTC_SIGNAL_ZONE_RIGHT: u8 = 2;
+
+pub const FTC_TERMINAL_NEAR: u8 = 0;
+pub const FTC_TERMINAL_FAR: u8 = 1;
+
+pub const FTC_PARK_LEFT_SIGNAL_ZONE: u8 = 0;
+pub const FTC_PARK_MIDDLE_SIGNAL_ZONE: u8 = 1;
+pub const FTC_PARK_RIGHT_SIGNAL_ZONE: u8 = 2;
+pub const FTC_PARK_NEAR_TERMINAL: u8 = 3;
+pub const FTC_PARK_FAR_TERMINAL: u8 = 4;
+pub const FTC_PARK_SUBSTATION: u8 = 5;
+
+pub const FTC_CARD_NONE: u8 = 0;
+pub const FTC_CARD_YELLOW: u8 = 1;
+pub const FTC_CARD_RED: u8 = 2;
+
+/// A match in any phase.
+pub struct FtcMatch(AnyMatch);
+
+#[derive(Eq, PartialEq, Copy, Clone, Debug, Hash)]
+#[repr(C)]
+pub enum FtcError {
+    Ok = 0,
+    JunctionIsEmpty,
+    BeaconOnJunction,
+    JunctionIsCapped,
+    BeaconPreviouslyScored,
+    BeaconScoredOutsideEndgame,
+    WrongField,
+    WrongPhase,
+    NotInMatch,
+    DuplicateTeam,
+    NullPointer,
+    InvalidArgument,
+    Panic,
+}
+
+impl From<MatchError> for FtcError {
+    fn from(value: MatchError) -> Self {
+        match value {
+            MatchError::JunctionIsEmpty => FtcError::JunctionIsEmpty,
+            MatchError::BeaconOnJunction => FtcError::BeaconOnJunction,
+            MatchError::JunctionIsCapped => FtcError::JunctionIsCapped,
+            MatchError::BeaconPreviouslyScored => FtcError::BeaconPreviouslyScored,
+            MatchError::BeaconScoredOutsideEndgame => FtcError::BeaconScoredOutsideEndgame,
+            MatchError::WrongField => FtcError::WrongField,
+            MatchError::WrongPhase => FtcError::WrongPhase,
+            MatchError::NotInMatch => FtcError::NotInMatch,
+        }
+    }
+}
+
+/// The results of one alliance in a finished match.
+/// Only the first `team_count` entries of `teams` are valid.
+#[derive(Eq, PartialEq, Copy, Clone, Debug, Hash)]
+#[repr(C)]
+pub struct FtcAllianceInfo {
+    pub alliance: u8,
+    pub team_count: u8,
+    pub teams: [i32; 2],
+    pub penalty_points: u16,
+    pub auto_points: u16,
+    pub teleop_points: u16,
+    pub endgame_points: u16,
+}
+
+impl<const N: usize> From<&AllianceInfo<N>> for FtcAllianceInfo {
+    fn from(value: &AllianceInfo<N>) -> Self {
+        let mut teams = [0; 2];
+        for (i, team) in value.teams.iter().enumerate() {
+            teams[i] = team.0;
+        }
+        Self {
+            alliance: value.alliance as u8,
+            team_count: N as u8,
+            teams,
+            penalty_points: value.penalty_points,
+            auto_points: value.auto_points,
+            teleop_points: value.teleop_points,
+            endgame_points: value.endgame_points,
+        }
+    }
+}
+
+/// The status of one robot in a match. `card` is one of the `FTC_CARD_*` constants.
+#[derive(Eq, PartialEq, Copy, Clone, Debug, Hash)]
+#[repr(C)]
+pub struct FtcRobotStatus {
+    pub no_show: bool,
+    pub disabled: bool,
+    pub card: u8,
+    pub disqualified: bool,
+}
+
+// runs the body of an exported function, catching any panic
+fn guard(body: impl FnOnce() -> Result<(), FtcError>) -> FtcError {
+    match catch_unwind(AssertUnwindSafe(body)) {
+        Ok(Ok(())) => FtcError::Ok,
+        Ok(Err(error)) => error,
+        Err(_) => FtcError::Panic,
+    }
+}
+
+unsafe fn handle<'a>(handle: *mut FtcMatch) -> Result<&'a mut AnyMatch, FtcError> {
+    handle.as_mut().map(|m| &mut m.0).ok_or(FtcError::NullPointer)
+}
+
+unsafe fn write<T>(out: *mut T, value: T) -> Result<(), FtcError> {
+    if out.is_null() {
+        Err(FtcError::NullPointer)
+    } else {
+        out.write(value);
+        Ok(())
+    }
+}
+
+fn alliance(value: u8) -> Result<Alliance, FtcError> {
+    match value {
+        FTC_ALLIANCE_RED => Ok(Alliance::RED),
+        FTC_ALLIANCE_BLUE => Ok(Alliance::BLUE),
+        _ => Err(FtcError::InvalidArgument),
+    }
+}
+
+fn robot(alliance_value: u8, index: u8) -> Result<MatchIndex, FtcError> {
+    // larger indices would not fit in a MatchIndex
+    if index > 1 {
+        Err(FtcError::NotInMatch)
+    } else {
+        Ok(MatchIndex::new(alliance(alliance_value)?, index))
+    }
+}
+
+fn signal_zone(value: u8) -> Result<SignalZone, FtcError> {
+    match value {
+        FTC_SIGNAL_ZONE_LEFT => Ok(SignalZone::Left),
+        FTC_SIGNAL_ZONE_MIDDLE => Ok(SignalZone::Middle),
+        FTC_SIGNAL_ZONE_RIGHT => Ok(SignalZone::Right),
+        _ => Err(FtcError::InvalidArgument),
+    }
+}
+
+fn terminal(value: u8) -> Result<Terminal, FtcError> {
+    match value {
+        FTC_TERMINAL_NEAR => Ok(Terminal::Near),
+        FTC_TERMINAL_FAR => Ok(Terminal::Far),
+        _ => Err(FtcError::InvalidArgument),
+    }
+}
+
+fn parking_location(value: u8) -> Result<ParkingLocation, FtcError> {
+    match value {
+        FTC_PARK_LEFT_SIGNAL_ZONE => Ok(ParkingLocation::LeftSignalZone),
+        FTC_PARK_MIDDLE_SIGNAL_ZONE => Ok(ParkingLocation::MiddleSignalZone),
+        FTC_PARK_RIGHT_SIGNAL_ZONE => Ok(ParkingLocation::RightSignalZone),
+        FTC_PARK_NEAR_TERMINAL => Ok(ParkingLocation::NearTerminal),
+        FTC_PARK_FAR_TERMINAL => Ok(ParkingLocation::FarTerminal),
+        FTC_PARK_SUBSTATION => Ok(ParkingLocation::Substation),
+        _ => Err(FtcError::InvalidArgument),
+    }
+}
+
+fn card(value: u8) -> Result<Option<Card>, FtcError> {
+    match value {
+        FTC_CARD_NONE => Ok(None),
+        FTC_CARD_YELLOW => Ok(Some(Card::Yellow)),
+        FTC_CARD_RED => Ok(Some(Card::Red)),
+        _ => Err(FtcError::InvalidArgument),
+    }
+}
+
+fn circuit_pattern(value: u8) -> Result<RemoteCircuitPattern, FtcError> {
+    match value {
+        0 => Ok(RemoteCircuitPattern::Pattern1),
+        1 => Ok(RemoteCircuitPattern::Pattern2),
+        2 => Ok(RemoteCircuitPattern::Pattern3),
+        3 => Ok(RemoteCircuitPattern::Pattern4),
+        4 => Ok(RemoteCircuitPattern::Pattern5),
+        5 => Ok(RemoteCircuitPattern::Pattern6),
+        _ => Err(FtcError::InvalidArgument),
+    }
+}
+
+// junctions off the field are reported as being on the wrong field
+fn junction(m: &AnyMatch, row: u8, column: u8) -> Result<AnyJunction, FtcError> {
+    match m.field_type() {
+        FieldType::Traditional => TraditionalJunction::from_coordinate(row, column).map(AnyJunction::from),
+        FieldType::RedRemote => RedRemoteJunction::from_coordinate(row, column).map(AnyJunction::from),
+        FieldType::BlueRemote => BlueRemoteJunction::from_coordinate(row, column).map(AnyJunction::from),
+    }.ok_or(FtcError::WrongField)
+}
+
+unsafe fn new_handle(out: *mut *mut FtcMatch, m: AnyMatch) -> Result<(), FtcError> {
+    write(out, Box::into_raw(Box::new(FtcMatch(m))))
+}
+
+/// Creates a traditional match in auto. Each array holds the values for the alliance's two robots.
+#[no_mangle]
+pub unsafe extern "C" fn ftc_traditional_new(
+    red_teams: *const i32,
+    red_signal_sleeves: *const bool,
+    blue_teams: *const i32,
+    blue_signal_sleeves: *const bool,
+    signal_zone_value: u8,
+    out: *mut *mut FtcMatch,
+) -> FtcError {
+    guard(|| {
+        if red_teams.is_null() || red_signal_sleeves.is_null() || blue_teams.is_null() || blue_signal_sleeves.is_null() {
+            return Err(FtcError::NullPointer);
+        }
+        let robots = |teams: *const i32, sleeves: *const bool| {
+            [0, 1].map(|i| (FtcTeamID(*teams.add(i)), *sleeves.add(i)))
+        };
+        let auto = TraditionalAuto::try_from_teams(
+            robots(red_teams, red_signal_sleeves),
+            robots(blue_teams, blue_signal_sleeves),
+            signal_zone(signal_zone_value)?,
+        ).ok_or(FtcError::DuplicateTeam)?;
+        new_handle(out, auto.into())
+    })
+}
+
+/// Creates a remote match in auto, for a single team on the given alliance.
+/// `circuit_pattern_value` is the pattern number minus one.
+#[no_mangle]
+pub unsafe extern "C" fn ftc_remote_new(
+    alliance_value: u8,
+    team: i32,
+    has_signal_sleeve: bool,
+    signal_zone_value: u8,
+    circuit_pattern_value: u8,
+    out: *mut *mut FtcMatch,
+) -> FtcError {
+    guard(|| {
+        let team = FtcTeamID(team);
+        let signal_zone = signal_zone(signal_zone_value)?;
+        let pattern = circuit_pattern(circuit_pattern_value)?;
+        let m: AnyMatch = match alliance(alliance_value)? {
+            Alliance::RED => RedRemoteAuto::with_team(team, has_signal_sleeve, signal_zone, pattern).into(),
+            Alliance::BLUE => BlueRemoteAuto::with_team(team, has_signal_sleeve, signal_zone, pattern).into(),
+        };
+        new_handle(out, m)
+    })
+}
+
+/// Releases a match. Passing a null pointer does nothing.
+#[no_mangle]
+pub unsafe extern "C" fn ftc_match_free(m: *mut FtcMatch) {
+    if !m.is_null() {
+        drop(Box::from_raw(m));
+    }
+}
+
+/// Writes one of the `FTC_FIELD_*` constants.
+#[no_mangle]
+pub unsafe extern "C" fn ftc_match_field_type(m: *mut FtcMatch, out: *mut u8) -> FtcError {
+    guard(|| {
+        let field = match handle(m)?.field_type() {
+            FieldType::Traditional => FTC_FIELD_TRADITIONAL,
+            FieldType::RedRemote => FTC_FIELD_RED_REMOTE,
+            FieldType::BlueRemote => FTC_FIELD_BLUE_REMOTE,
+        };
+        write(out, field)
+    })
+}
+
+/// Writes one of the `FTC_PHASE_*` constants.
+#[no_mangle]
+pub unsafe extern "C" fn ftc_match_phase(m: *mut FtcMatch, out: *mut u8) -> FtcError {
+    guard(|| write(out, handle(m)?.phase() as u8))
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn ftc_score(m: *mut FtcMatch, alliance_value: u8, row: u8, column: u8) -> FtcError {
+    guard(|| {
+        let m = handle(m)?;
+        let location = junction(m, row, column)?;
+        Ok(m.score_for(alliance(alliance_value)?, location)?)
+    })
+}
+
+/// Removes the top cone of a junction. If `out_alliance` is not null, the alliance of the removed cone is written to it.
+#[no_mangle]
+pub unsafe extern "C" fn ftc_descore(m: *mut FtcMatch, row: u8, column: u8, out_alliance: *mut u8) -> FtcError {
+    guard(|| {
+        let m = handle(m)?;
+        let location = junction(m, row, column)?;
+        let alliance = m.descore(location)?;
+        if !out_alliance.is_null() {
+            write(out_alliance, alliance as u8)?;
+        }
+        Ok(())
+    })
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn ftc_add_terminal(m: *mut FtcMatch, alliance_value: u8, terminal_value: u8) -> FtcError {
+    guard(|| Ok(handle(m)?.add_terminal_for(alliance(alliance_value)?, terminal(terminal_value)?)?))
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn ftc_cap(m: *mut FtcMatch, alliance_value: u8, index: u8, row: u8, column: u8) -> FtcError {
+    guard(|| {
+        let m = handle(m)?;
+        let location = junction(m, row, column)?;
+        Ok(m.cap_for(robot(alliance_value, index)?, location)?)
+    })
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn ftc_penalize(m: *mut FtcMatch, alliance_value: u8, points: u8) -> FtcError {
+    guard(|| Ok(handle(m)?.penalize(alliance(alliance_value)?, points)?))
+}
+
+/// Parks a robot at one of the `FTC_PARK_*` locations.
+/// Robots can park anywhere in auto, but only in a terminal in end game.
+#[no_mangle]
+pub unsafe extern "C" fn ftc_park(m: *mut FtcMatch, alliance_value: u8, index: u8, location: u8) -> FtcError {
+    guard(|| Ok(handle(m)?.park_for(robot(alliance_value, index)?, parking_location(location)?)?))
+}
+
+/// Gives a robot a yellow or red card. A second yellow card in the same match becomes a red card.
+#[no_mangle]
+pub unsafe extern "C" fn ftc_card(m: *mut FtcMatch, alliance_value: u8, index: u8, card_value: u8) -> FtcError {
+    guard(|| {
+        let card = card(card_value)?.ok_or(FtcError::InvalidArgument)?;
+        Ok(handle(m)?.card_for(robot(alliance_value, index)?, card)?)
+    })
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn ftc_get_status(
+    m: *mut FtcMatch,
+    alliance_value: u8,
+    index: u8,
+    out: *mut FtcRobotStatus,
+) -> FtcError {
+    guard(|| {
+        let status = handle(m)?.status_of(robot(alliance_value, index)?)?;
+        write(out, FtcRobotStatus {
+            no_show: status.no_show,
+            disabled: status.disabled,
+            card: match status.card {
+                None => FTC_CARD_NONE,
+                Some(Card::Yellow) => FTC_CARD_YELLOW,
+                Some(Card::Red) => FTC_CARD_RED,
+            },
+            disqualified: status.disqualified,
+        })
+    })
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn ftc_set_status(
+    m: *mut FtcMatch,
+    alliance_value: u8,
+    index: u8,
+    status: *const FtcRobotStatus,
+) -> FtcError {
+    guard(|| {
+        let status = status.as_ref().ok_or(FtcError::NullPointer)?;
+        let status = RobotStatus {
+            no_show: status.no_show,
+            disabled: status.disabled,
+            card: card(status.card)?,
+            disqualified: status.disqualified,
+        };
+        Ok(handle(m)?.set_status(robot(alliance_value, index)?, status)?)
+    })
+}
+
+/// Moves the match to its next phase, ending it after end game.
+/// If `out_phase` is not null, the new phase is written to it.
+#[no_mangle]
+pub unsafe extern "C" fn ftc_advance_phase(m: *mut FtcMatch, out_phase: *mut u8) -> FtcError {
+    guard(|| {
+        let phase = handle(m)?.advance_phase()?;
+        if !out_phase.is_null() {
+            write(out_phase, phase as u8)?;
+        }
+        Ok(())
+    })
+}
+
+/// Writes the results of an alliance in a finished match.
+#[no_mangle]
+pub unsafe extern "C" fn ftc_alliance_info(m: *mut FtcMatch, alliance_value: u8, out: *mut FtcAllianceInfo) -> FtcError {
+    guard(|| {
+        let alliance = alliance(alliance_value)?;
+        let info = match handle(m)?.results().ok_or(FtcError::WrongPhase)? {
+            MatchResult::Traditional(red, blue) => match alliance {
+                Alliance::RED => FtcAllianceInfo::from(&red),
+                Alliance::BLUE => FtcAllianceInfo::from(&blue),
+            },
+            MatchResult::Remote(info) if info.alliance == alliance => FtcAllianceInfo::from(&info),
+            MatchResult::Remote(_) => return Err(FtcError::NotInMatch),
+        };
+        write(out, info)
+    })
+}
@@ -0,0 +1,392 @@
+//! Physical positions on the field, for robot software.
+//!
+//! Lengths are in millimeters unless a name says otherwise, and points are on the floor,
+//! measured from the center of the field. Every position can be given in one of two [`Frame`]s:
+//! - [`Frame::Audience`]: x points to the audience's right, and y points away from the audience.
+//! - [`Frame::AllianceStation`]: x points to the drivers' right, and y points away from their alliance station.
+//!
+//! Seen from the audience, the red alliance station is on the right and the blue alliance station on the left.
+//! Junction rows run from V, next to the blue alliance station, to Z, next to the red alliance station,
+//! and junction columns run from 1, next to the audience, to 5.
+//! Tiles are named the same way, from A1, the blue corner next to the audience, to F6.
+//! Remote junctions stand where the junctions with the same names stand on a full field.
+use crate::remote::{BlueRemoteJunction, RedRemoteJunction};
+use crate::traditional::TraditionalJunction;
+use crate::*;
+
+pub mod parking;
+
+pub const MM_PER_INCH: f32 = 25.4;
+/// The side of one foam tile, which is 24 inches.
+pub const TILE_SIZE: f32 = 24.0 * MM_PER_INCH;
+/// The side of the full field, which is 6 tiles.
+pub const FIELD_SIZE: f32 = 6.0 * TILE_SIZE;
+
+const HALF_FIELD: f32 = FIELD_SIZE / 2.0;
+
+#[inline(always)]
+pub fn mm_to_inches(mm: f32) -> f32 {
+    mm / MM_PER_INCH
+}
+
+#[inline(always)]
+pub fn inches_to_mm(inches: f32) -> f32 {
+    inches * MM_PER_INCH
+}
+
+/// A frame of reference for positions, centered on the field.
+#[derive(Eq, PartialEq, Copy, Clone, Debug, Hash)]
+pub enum Frame {
+    Audience,
+    /// The frame of an alliance's drivers.
+    AllianceStation(Alliance),
+}
+display_impl_as_debug!(Frame);
+
+#[derive(PartialEq, Copy, Clone, Debug, Default)]
+pub struct Point {
+    pub x: f32,
+    pub y: f32,
+}
+display_impl_as_debug!(Point);
+
+impl Point {
+    #[inline(always)]
+    pub const fn new(x: f32, y: f32) -> Self {
+        Self { x, y }
+    }
+
+    #[inline]
+    pub fn from_inches(x: f32, y: f32) -> Self {
+        Self::new(inches_to_mm(x), inches_to_mm(y))
+    }
+
+    /// The point's x and y, in inches.
+    #[inline]
+    pub fn inches(self) -> (f32, f32) {
+        (mm_to_inches(self.x), mm_to_inches(self.y))
+    }
+
+    /// The square of the distance between two points, which orders points the same way the distance does.
+    #[inline]
+    pub fn distance_squared(self, other: Point) -> f32 {
+        let (dx, dy) = (self.x - other.x, self.y - other.y);
+        dx * dx + dy * dy
+    }
+
+    /// Converts a point from one frame to another.
+    pub fn convert(self, from: Frame, to: Frame) -> Self {
+        // every frame is the audience frame turned by a quarter turn
+        let Point { x, y } = match from {
+            Frame::Audience => self,
+            Frame::AllianceStation(Alliance::RED) => Point::new(-self.y, self.x),
+            Frame::AllianceStation(_) => Point::new(self.y, -self.x),
+        };
+        match to {
+            Frame::Audience => Point::new(x, y),
+            Frame::AllianceStation(Alliance::RED) => Point::new(y, -x),
+            Frame::AllianceStation(_) => Point::new(-y, x),
+        }
+    }
+}
+
+/// Where a robot is, and which way it faces.
+#[derive(PartialEq, Copy, Clone, Debug, Default)]
+pub struct Pose {
+    pub position: Point,
+    /// Radians counterclockwise from the x axis, from -π to π.
+    pub heading: f32,
+}
+display_impl_as_debug!(Pose);
+
+impl Pose {
+    #[inline(always)]
+    pub const fn new(position: Point, heading: f32) -> Self {
+        Self { position, heading }
+    }
+
+    /// Converts a pose from one frame to another.
+    pub fn convert(self, from: Frame, to: Frame) -> Self {
+        let turn = |frame| match frame {
+            Frame::Audience => 0.0,
+            Frame::AllianceStation(Alliance::RED) => -core::f32::consts::FRAC_PI_2,
+            Frame::AllianceStation(_) => core::f32::consts::FRAC_PI_2,
+        };
+        let mut heading = self.heading - turn(from) + turn(to);
+        if heading > core::f32::consts::PI {
+            heading -= core::f32::consts::TAU;
+        } else if heading <= -core::f32::consts::PI {
+            heading += core::f32::consts::TAU;
+        }
+        Self {
+            position: self.position.convert(from, to),
+            heading,
+        }
+    }
+
+    /// Places a point given relative to the robot, with x pointing forwards and y to the robot's left,
+    /// in the frame of the pose.
+    pub fn place(self, relative: Point) -> Point {
+        let (sin, cos) = (libm::sinf(self.heading), libm::cosf(self.heading));
+        Point::new(
+            self.position.x + relative.x * cos - relative.y * sin,
+            self.position.y + relative.x * sin + relative.y * cos,
+        )
+    }
+}
+
+/// How tall a junction is, which decides its points.
+#[derive(Ord, PartialOrd, Eq, PartialEq, Copy, Clone, Debug, Hash)]
+#[repr(u8)]
+pub enum JunctionLevel {
+    Ground,
+    Low,
+    Medium,
+    High,
+}
+display_impl_as_debug!(JunctionLevel);
+
+// indexed by level
+const HEIGHTS_INCHES: [f32; 4] = [0.56, 13.5, 23.5, 33.5];
+
+impl JunctionLevel {
+    pub fn of(junction: impl FieldCoordinate) -> Self {
+        [Self::Ground, Self::Low, Self::Medium, Self::High][junction.points() as usize - 2]
+    }
+
+    /// The height of the top of the junction above the floor.
+    #[inline]
+    pub fn height(self) -> f32 {
+        inches_to_mm(self.height_inches())
+    }
+
+    #[inline]
+    pub fn height_inches(self) -> f32 {
+        HEIGHTS_INCHES[self as usize]
+    }
+}
+
+/// One of the 36 foam tiles of a full field.
+#[derive(Ord, PartialOrd, Eq, PartialEq, Copy, Clone, Debug, Hash)]
+pub struct Tile {
+    row: u8,
+    column: u8,
+}
+
+impl Display for Tile {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        write!(f, "{}{}", (b'A' + self.row) as char, self.column + 1)
+    }
+}
+
+impl Tile {
+    /// Finds a tile by its zero-indexed row, from A, and column, from 1.
+    pub const fn new(row: u8, column: u8) -> Option<Self> {
+        if row < 6 && column < 6 {
+            Some(Self { row, column })
+        } else {
+            None
+        }
+    }
+
+    #[inline(always)]
+    pub const fn row(self) -> u8 {
+        self.row
+    }
+
+    #[inline(always)]
+    pub const fn column(self) -> u8 {
+        self.column
+    }
+
+    /// The tile under a point, or None if it is off the field.
+    pub fn containing(point: Point, frame: Frame) -> Option<Self> {
+        let Point { x, y } = point.convert(frame, Frame::Audience);
+        let (row, column) = (x / TILE_SIZE + 3.0, y / TILE_SIZE + 3.0);
+        if (0.0..6.0).contains(&row) && (0.0..6.0).contains(&column) {
+            Self::new(row as u8, column as u8)
+        } else {
+            None
+        }
+    }
+
+    pub fn center(self, frame: Frame) -> Point {
+        Point::new(
+            (self.row as f32 - 2.5) * TILE_SIZE,
+            (self.column as f32 - 2.5) * TILE_SIZE,
+        )
+        .convert(Frame::Audience, frame)
+    }
+
+    /// The corners of this tile, going around it.
+    pub fn corners(self, frame: Frame) -> [Point; 4] {
+        let center = self.center(Frame::Audience);
+        let half = TILE_SIZE / 2.0;
+        [(-half, -half), (half, -half), (half, half), (-half, half)].map(|(dx, dy)| {
+            Point::new(center.x + dx, center.y + dy).convert(Frame::Audience, frame)
+        })
+    }
+
+    /// The junctions at the corners of this tile, which may be none for an edge tile of a remote field.
+    pub fn junctions<T: JunctionGeometry>(self) -> impl Iterator<Item = T> {
+        T::ALL.iter().copied().filter(move |junction| {
+            let (row, column) = junction.full_field_coordinate();
+            (row == self.row || row + 1 == self.row)
+                && (column == self.column || column + 1 == self.column)
+        })
+    }
+}
+
+/// Where junctions physically stand.
+pub trait JunctionGeometry: FieldCoordinate {
+    /// The junction's row and column on a full field, zero-indexed from V1.
+    #[doc(hidden)]
+    fn full_field_coordinate(self) -> (u8, u8);
+
+    /// Where the center of the junction's base is.
+    fn position(self, frame: Frame) -> Point {
+        let (row, column) = self.full_field_coordinate();
+        Point::new(
+            (row as f32 - 2.0) * TILE_SIZE,
+            (column as f32 - 2.0) * TILE_SIZE,
+        )
+        .convert(Frame::Audience, frame)
+    }
+
+    #[inline]
+    fn level(self) -> JunctionLevel {
+        JunctionLevel::of(self)
+    }
+
+    /// The height of the top of the junction above the floor.
+    #[inline]
+    fn height(self) -> f32 {
+        self.level().height()
+    }
+
+    /// The junction closest to a position, such as a robot's, on or off the field.
+    fn nearest(position: Point, frame: Frame) -> Self {
+        let position = position.convert(frame, Frame::Audience);
+        Self::ALL
+            .iter()
+            .copied()
+            .min_by(|a, b| {
+                let distance = |junction: &Self| {
+                    junction
+                        .position(Frame::Audience)
+                        .distance_squared(position)
+                };
+                distance(a).total_cmp(&distance(b))
+            })
+            .expect("every field has junctions")
+    }
+
+    /// The four tiles that meet at this junction.
+    fn tiles(self) -> [Tile; 4] {
+        let (row, column) = self.full_field_coordinate();
+        [(0, 0), (0, 1), (1, 0), (1, 1)].map(|(dr, dc)| Tile {
+            row: row + dr,
+            column: column + dc,
+        })
+    }
+}
+
+impl JunctionGeometry for TraditionalJunction {
+    #[inline(always)]
+    fn full_field_coordinate(self) -> (u8, u8) {
+        self.coordinate()
+    }
+}
+
+impl JunctionGeometry for RedRemoteJunction {
+    #[inline(always)]
+    fn full_field_coordinate(self) -> (u8, u8) {
+        (self.row() + 2, self.column())
+    }
+}
+
+impl JunctionGeometry for BlueRemoteJunction {
+    // rows are stored backwards
+    #[inline(always)]
+    fn full_field_coordinate(self) -> (u8, u8) {
+        (2 - self.row(), self.column())
+    }
+}
+
+/// The audience or rear half of an alliance's side of the field, where a robot starts.
+#[derive(Eq, PartialEq, Copy, Clone, Debug, Hash)]
+pub enum Side {
+    Audience,
+    Rear,
+}
+display_impl_as_debug!(Side);
+
+// the sign of a position across the field, towards an alliance's station or a side's wall
+#[inline(always)]
+fn toward_alliance(alliance: Alliance) -> f32 {
+    if alliance.is_blue() {
+        -1.0
+    } else {
+        1.0
+    }
+}
+
+#[inline(always)]
+fn toward_side(side: Side) -> f32 {
+    match side {
+        Side::Audience => -1.0,
+        Side::Rear => 1.0,
+    }
+}
+
+/// The corner of the field holding one of an alliance's terminals.
+/// Near terminals are next to the audience on the alliance's side, and far terminals are diagonally opposite.
+pub fn terminal(alliance: Alliance, terminal: Terminal, frame: Frame) -> Point {
+    let sign = match terminal {
+        Terminal::Near => 1.0,
+        Terminal::Far => -1.0,
+    };
+    Point::new(
+        sign * toward_alliance(alliance) * HALF_FIELD,
+        -sign * HALF_FIELD,
+    )
+    .convert(Frame::Audience, frame)
+}
+
+/// The middle of an alliance's wall, where its substation is.
+pub fn substation(alliance: Alliance, frame: Frame) -> Point {
+    Point::new(toward_alliance(alliance) * HALF_FIELD, 0.0).convert(Frame::Audience, frame)
+}
+
+/// Where an alliance's stack of five cones stands against the audience or rear wall,
+/// on the tile row next to the middle of the field.
+pub fn cone_stack(alliance: Alliance, side: Side, frame: Frame) -> Point {
+    Point::new(
+        toward_alliance(alliance) * TILE_SIZE / 2.0,
+        toward_side(side) * HALF_FIELD,
+    )
+    .convert(Frame::Audience, frame)
+}
+
+/// The tile of a signal zone for a robot starting on one side of an alliance's half of the field.
+/// The zones are the three tiles of that side in the third row out from the alliance's wall,
+/// and left and right are as the drivers see them.
+pub fn signal_zone(alliance: Alliance, side: Side, zone: SignalZone) -> Tile {
+    let row = if alliance.is_blue() { 2 } else { 3 };
+    let offset = match zone {
+        SignalZone::Left => 0,
+        SignalZone::Middle => 1,
+        SignalZone::Right => 2,
+    };
+    // red drivers see the audience on their left, and blue drivers on their right
+    let offset = if alliance.is_blue() {
+        2 - offset
+    } else {
+        offset
+    };
+    let column = match side {
+        Side::Audience => offset,
+        Side::Rear => offset + 3,
+    };
+    Tile { row, column }
+}
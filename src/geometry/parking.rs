@@ -0,0 +1,134 @@
+//! Deciding where a robot is parked from its footprint and pose, for referee assist and for checking
+//! autonomous routines.
+//!
+//! A robot is parked in a signal zone only when it is completely in the zone's tile, but it is parked in a
+//! terminal or a substation when any part of it is in one, following the game manual.
+//! Footprints are outlines of the robot as seen from above, given relative to the robot's pose
+//! with x pointing forwards and y to the robot's left. See [`Pose::place`].
+use super::*;
+use alloc::vec::Vec;
+
+/// The outline of a rectangular robot centered on its pose, such as an 18 inch cube.
+pub fn rectangle(length: f32, width: f32) -> [Point; 4] {
+    let (x, y) = (length / 2.0, width / 2.0);
+    [
+        Point::new(x, y),
+        Point::new(-x, y),
+        Point::new(-x, -y),
+        Point::new(x, -y),
+    ]
+}
+
+/// The tape triangle across the corner tile holding one of an alliance's terminals.
+pub fn terminal_area(alliance: Alliance, terminal: Terminal, frame: Frame) -> [Point; 3] {
+    let corner = super::terminal(alliance, terminal, Frame::Audience);
+    let (dx, dy) = (
+        -corner.x.signum() * TILE_SIZE,
+        -corner.y.signum() * TILE_SIZE,
+    );
+    [
+        corner,
+        Point::new(corner.x + dx, corner.y),
+        Point::new(corner.x, corner.y + dy),
+    ]
+    .map(|point| point.convert(Frame::Audience, frame))
+}
+
+/// The tape triangle in front of the middle two tiles of an alliance's wall, where its substation is.
+pub fn substation_area(alliance: Alliance, frame: Frame) -> [Point; 3] {
+    let wall = super::substation(alliance, Frame::Audience);
+    let tip = wall.x - toward_alliance(alliance) * TILE_SIZE;
+    [
+        Point::new(wall.x, -TILE_SIZE),
+        Point::new(wall.x, TILE_SIZE),
+        Point::new(tip, 0.0),
+    ]
+    .map(|point| point.convert(Frame::Audience, frame))
+}
+
+/// Where a robot of `alliance` that started on `side` of the field is parked,
+/// or None if it is not parked anywhere that scores. The result can be given straight to `park_for`.
+/// Signal zones come first, then terminals, then the substation.
+pub fn classify(
+    footprint: &[Point],
+    pose: Pose,
+    frame: Frame,
+    alliance: Alliance,
+    side: Side,
+) -> Option<ParkingLocation> {
+    if footprint.len() < 3 {
+        return None;
+    }
+    let pose = pose.convert(frame, Frame::Audience);
+    let outline: Vec<Point> = footprint.iter().map(|&point| pose.place(point)).collect();
+    [SignalZone::Left, SignalZone::Middle, SignalZone::Right]
+        .into_iter()
+        .find(|&zone| {
+            let tile = signal_zone(alliance, side, zone).corners(Frame::Audience);
+            outline.iter().all(|&point| contains(&tile, point))
+        })
+        .map(ParkingLocation::from)
+        .or_else(|| {
+            [Terminal::Near, Terminal::Far]
+                .into_iter()
+                .find(|&terminal| {
+                    overlaps(
+                        &outline,
+                        &terminal_area(alliance, terminal, Frame::Audience),
+                    )
+                })
+                .map(ParkingLocation::from)
+        })
+        .or_else(|| {
+            overlaps(&outline, &substation_area(alliance, Frame::Audience))
+                .then_some(ParkingLocation::Substation)
+        })
+}
+
+// the cross product of b - a and c - a, which is positive if c is to the left of a line from a to b
+#[inline]
+fn cross(a: Point, b: Point, c: Point) -> f32 {
+    (b.x - a.x) * (c.y - a.y) - (b.y - a.y) * (c.x - a.x)
+}
+
+fn edges(polygon: &[Point]) -> impl Iterator<Item = (Point, Point)> + '_ {
+    polygon
+        .iter()
+        .copied()
+        .zip(polygon.iter().copied().cycle().skip(1))
+}
+
+/// Whether a point is inside a polygon, or on its edge.
+fn contains(polygon: &[Point], point: Point) -> bool {
+    let mut inside = false;
+    for (a, b) in edges(polygon) {
+        let on_edge = cross(a, b, point) == 0.0
+            && point.x >= a.x.min(b.x)
+            && point.x <= a.x.max(b.x)
+            && point.y >= a.y.min(b.y)
+            && point.y <= a.y.max(b.y);
+        if on_edge {
+            return true;
+        }
+        // count crossings of a ray going right from the point
+        if (a.y > point.y) != (b.y > point.y)
+            && point.x < a.x + (point.y - a.y) / (b.y - a.y) * (b.x - a.x)
+        {
+            inside = !inside;
+        }
+    }
+    inside
+}
+
+fn segments_cross(a: Point, b: Point, c: Point, d: Point) -> bool {
+    let (c_side, d_side) = (cross(a, b, c), cross(a, b, d));
+    let (a_side, b_side) = (cross(c, d, a), cross(c, d, b));
+    c_side * d_side < 0.0 && a_side * b_side < 0.0
+}
+
+/// Whether two polygons share any area or touch.
+fn overlaps(a: &[Point], b: &[Point]) -> bool {
+    a.iter().any(|&point| contains(b, point))
+        || b.iter().any(|&point| contains(a, point))
+        || edges(a).any(|(p, q)| edges(b).any(|(r, s)| segments_cross(p, q, r, s)))
+}
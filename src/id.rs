@@ -3,10 +3,11 @@
 //! while [`MatchIndex`] represents a team's role in a specific match.
 //! Prefer using [`MatchIndex`] if possible,
 //! since it does not require checking if a given ID is in the match.
+//! [`AllianceRoster`] represents a playoff alliance, which can have more teams than play in a single match.
 use crate::{FieldCoordinate, Match};
-use std::fmt;
-use std::fmt::{Display, Formatter};
-use std::mem::transmute;
+use core::fmt;
+use core::fmt::{Display, Formatter};
+use core::mem::transmute;
 
 #[repr(u8)]
 #[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
@@ -30,6 +31,10 @@ impl Alliance {
     pub(crate) fn from(value: bool) -> Alliance {
         unsafe { transmute(value) }
     }
+    #[inline(always)]
+    pub(crate) fn opponent(self) -> Alliance {
+        Self::from(!self.into_bool())
+    }
 }
 
 #[repr(transparent)]
@@ -109,3 +114,113 @@ impl Display for MatchIndex {
             .finish()
     }
 }
+
+#[derive(Eq, PartialEq, Copy, Clone, Debug, Hash)]
+#[repr(u8)]
+pub enum RosterError {
+    /// A team appears more than once in a roster, or in both alliances of a match.
+    DuplicateTeam,
+    /// A team is not on the roster, or was replaced by a backup robot.
+    NotOnRoster,
+    /// The alliance already used its backup robot.
+    BackupAlreadyUsed,
+}
+crate::display_impl_as_debug!(RosterError);
+
+/// The teams of a playoff alliance: a captain, a first pick, and optionally a second pick.
+/// Any two of them can play in a given match, and a backup robot can permanently replace one of them.
+/// Every team on the roster, including a replaced team, shares the alliance's results.
+#[derive(Eq, PartialEq, Copy, Clone, Debug, Hash)]
+pub struct AllianceRoster {
+    // captain, first pick, then the second pick and backup in the order they were added
+    teams: [FtcTeamID; 4],
+    len: u8,
+    replaced: Option<FtcTeamID>,
+}
+
+impl AllianceRoster {
+    /// Creates a new roster, returning an error if a team occurs more than once.
+    pub fn new(captain: FtcTeamID, first_pick: FtcTeamID, second_pick: Option<FtcTeamID>) -> Result<Self, RosterError> {
+        let mut roster = Self {
+            teams: [captain, first_pick, FtcTeamID(0), FtcTeamID(0)],
+            len: 2,
+            replaced: None,
+        };
+        if captain == first_pick {
+            return Err(RosterError::DuplicateTeam);
+        }
+        if let Some(second_pick) = second_pick {
+            roster.push(second_pick)?;
+        }
+        Ok(roster)
+    }
+
+    fn push(&mut self, team: FtcTeamID) -> Result<(), RosterError> {
+        if self.contains(team) {
+            Err(RosterError::DuplicateTeam)
+        } else {
+            self.teams[self.len as usize] = team;
+            self.len += 1;
+            Ok(())
+        }
+    }
+
+    #[inline(always)]
+    pub fn captain(&self) -> FtcTeamID {
+        self.teams[0]
+    }
+
+    /// Every team the alliance's results are attributed to.
+    #[inline]
+    pub fn teams(&self) -> &[FtcTeamID] {
+        &self.teams[..self.len as usize]
+    }
+
+    /// The team that was replaced by a backup robot, if any.
+    #[inline(always)]
+    pub fn replaced(&self) -> Option<FtcTeamID> {
+        self.replaced
+    }
+
+    #[inline]
+    pub fn contains(&self, team: FtcTeamID) -> bool {
+        self.teams().contains(&team)
+    }
+
+    /// Whether a team can be selected to play in a match.
+    #[inline]
+    pub fn can_play(&self, team: FtcTeamID) -> bool {
+        self.contains(team) && self.replaced != Some(team)
+    }
+
+    /// Checks that two teams can play together for this alliance in a match.
+    pub fn select(&self, playing: [FtcTeamID; 2]) -> Result<(), RosterError> {
+        let [first, second] = playing;
+        if first == second {
+            Err(RosterError::DuplicateTeam)
+        } else if self.can_play(first) && self.can_play(second) {
+            Ok(())
+        } else {
+            Err(RosterError::NotOnRoster)
+        }
+    }
+
+    /// Permanently replaces a team with a backup robot. The replaced team stays on the roster,
+    /// but can no longer play.
+    pub fn substitute(&mut self, replaced: FtcTeamID, backup: FtcTeamID) -> Result<(), RosterError> {
+        if self.replaced.is_some() {
+            Err(RosterError::BackupAlreadyUsed)
+        } else if !self.can_play(replaced) {
+            Err(RosterError::NotOnRoster)
+        } else {
+            self.push(backup)?;
+            self.replaced = Some(replaced);
+            Ok(())
+        }
+    }
+
+    /// Whether two rosters share no teams, and so can face each other.
+    pub fn is_disjoint(&self, other: &AllianceRoster) -> bool {
+        !self.teams().iter().any(|&team| other.contains(team))
+    }
+}
@@ -0,0 +1,295 @@
+//! JNI bindings for the Java wrapper classes in `java/ftc/powerplay/scoring`.
+//!
+//! Every native method belongs to the package-private `NativeMatch` class, and operates on a match stored
+//! behind a `long` handle. Enums are passed as their Java ordinals, so the Java enums must list their
+//! constants in the same order as the arrays in this module. Junctions are listed in the order of
+//! [`FieldCoordinate::ALL`].
+//!
+//! For the Android robot controller, build with `cargo ndk` or an NDK linker for `aarch64-linux-android`
+//! or `armv7-linux-androideabi`, and load the resulting library before using any match.
+//!
+//! # Safety
+//! Handles must come from `newTraditional` or `newRemote` and must not be used after they are freed.
+//! The Java wrappers uphold this by clearing their handle when a match is closed or moves to its next phase.
+// the safety requirements are shared by every function, so they are documented once above
+#![allow(clippy::missing_safety_doc)]
+use crate::dynamic::*;
+use crate::remote::*;
+use crate::traditional::*;
+use crate::*;
+use jni::objects::{JBooleanArray, JClass, JIntArray};
+use jni::sys::{jboolean, jint, jintArray, jlong};
+use jni::JNIEnv;
+use std::panic::{catch_unwind, AssertUnwindSafe};
+
+const ALLIANCES: [Alliance; 2] = [Alliance::RED, Alliance::BLUE];
+const SIGNAL_ZONES: [SignalZone; 3] = [SignalZone::Left, SignalZone::Middle, SignalZone::Right];
+const TERMINALS: [Terminal; 2] = [Terminal::Near, Terminal::Far];
+const PARKING_LOCATIONS: [ParkingLocation; 6] = {
+    use ParkingLocation::*;
+    [LeftSignalZone, MiddleSignalZone, RightSignalZone, NearTerminal, FarTerminal, Substation]
+};
+const CIRCUIT_PATTERNS: [RemoteCircuitPattern; 6] = {
+    use RemoteCircuitPattern::*;
+    [Pattern1, Pattern2, Pattern3, Pattern4, Pattern5, Pattern6]
+};
+
+enum Failure {
+    Match(MatchError),
+    InvalidArgument(&'static str),
+    Closed,
+    // the exception is already pending in Java
+    Java,
+}
+
+impl From<MatchError> for Failure {
+    #[inline(always)]
+    fn from(value: MatchError) -> Self {
+        Failure::Match(value)
+    }
+}
+
+impl From<jni::errors::Error> for Failure {
+    #[inline(always)]
+    fn from(_: jni::errors::Error) -> Self {
+        Failure::Java
+    }
+}
+
+// runs the body of a native method, turning failures and panics into Java exceptions
+fn run<T: Default>(env: &mut JNIEnv, body: impl FnOnce(&mut JNIEnv) -> Result<T, Failure>) -> T {
+    let result = catch_unwind(AssertUnwindSafe(|| body(env)));
+    let (class, message) = match result {
+        Ok(Ok(value)) => return value,
+        Ok(Err(Failure::Match(error))) => ("ftc/powerplay/scoring/ScoringException", error.to_string()),
+        Ok(Err(Failure::InvalidArgument(message))) => ("java/lang/IllegalArgumentException", message.to_string()),
+        Ok(Err(Failure::Closed)) => ("java/lang/IllegalStateException", "The match has been closed.".to_string()),
+        Ok(Err(Failure::Java)) if env.exception_check().unwrap_or(true) => return T::default(),
+        Ok(Err(Failure::Java)) => ("java/lang/RuntimeException", "A JNI call failed.".to_string()),
+        Err(_) => ("java/lang/RuntimeException", "The scoring engine panicked.".to_string()),
+    };
+    // if even this fails, the JVM is in no state to report anything
+    let _ = env.throw_new(class, message);
+    T::default()
+}
+
+fn get<T: Copy>(values: &[T], ordinal: jint, name: &'static str) -> Result<T, Failure> {
+    usize::try_from(ordinal)
+        .ok()
+        .and_then(|i| values.get(i))
+        .copied()
+        .ok_or(Failure::InvalidArgument(name))
+}
+
+// the handle is the address of a boxed match, and is only freed by the Java wrapper's close()
+unsafe fn handle<'a>(handle: jlong) -> Result<&'a mut AnyMatch, Failure> {
+    (handle as *mut AnyMatch).as_mut().ok_or(Failure::Closed)
+}
+
+fn into_handle(m: AnyMatch) -> jlong {
+    Box::into_raw(Box::new(m)) as jlong
+}
+
+fn junction(m: &AnyMatch, ordinal: jint) -> Result<AnyJunction, Failure> {
+    const NAME: &str = "junction";
+    match m.field_type() {
+        FieldType::Traditional => get(TraditionalJunction::ALL, ordinal, NAME).map(AnyJunction::from),
+        FieldType::RedRemote => get(RedRemoteJunction::ALL, ordinal, NAME).map(AnyJunction::from),
+        FieldType::BlueRemote => get(BlueRemoteJunction::ALL, ordinal, NAME).map(AnyJunction::from),
+    }
+}
+
+fn robot(m: &AnyMatch, team: jint) -> Result<MatchIndex, Failure> {
+    m.index_of(FtcTeamID(team)).ok_or(Failure::Match(MatchError::NotInMatch))
+}
+
+fn robots(env: &mut JNIEnv, teams: &JIntArray, sleeves: &JBooleanArray) -> Result<[(FtcTeamID, bool); 2], Failure> {
+    if env.get_array_length(teams)? != 2 || env.get_array_length(sleeves)? != 2 {
+        return Err(Failure::InvalidArgument("An alliance must have exactly two robots."));
+    }
+    let mut team_ids = [0; 2];
+    let mut has_sleeves = [0; 2];
+    env.get_int_array_region(teams, 0, &mut team_ids)?;
+    env.get_boolean_array_region(sleeves, 0, &mut has_sleeves)?;
+    Ok([0, 1].map(|i| (FtcTeamID(team_ids[i]), has_sleeves[i] != 0)))
+}
+
+#[no_mangle]
+pub extern "system" fn Java_ftc_powerplay_scoring_NativeMatch_newTraditional<'local>(
+    mut env: JNIEnv<'local>,
+    _class: JClass<'local>,
+    red_teams: JIntArray<'local>,
+    red_signal_sleeves: JBooleanArray<'local>,
+    blue_teams: JIntArray<'local>,
+    blue_signal_sleeves: JBooleanArray<'local>,
+    signal_zone: jint,
+) -> jlong {
+    run(&mut env, |env| {
+        let red = robots(env, &red_teams, &red_signal_sleeves)?;
+        let blue = robots(env, &blue_teams, &blue_signal_sleeves)?;
+        let auto = TraditionalAuto::try_from_teams(red, blue, get(&SIGNAL_ZONES, signal_zone, "signal zone")?)
+            .ok_or(Failure::InvalidArgument("A team cannot be in a match more than once."))?;
+        Ok(into_handle(auto.into()))
+    })
+}
+
+#[no_mangle]
+pub extern "system" fn Java_ftc_powerplay_scoring_NativeMatch_newRemote<'local>(
+    mut env: JNIEnv<'local>,
+    _class: JClass<'local>,
+    alliance: jint,
+    team: jint,
+    has_signal_sleeve: jboolean,
+    signal_zone: jint,
+    circuit_pattern: jint,
+) -> jlong {
+    run(&mut env, |_| {
+        let team = FtcTeamID(team);
+        let has_signal_sleeve = has_signal_sleeve != 0;
+        let signal_zone = get(&SIGNAL_ZONES, signal_zone, "signal zone")?;
+        let pattern = get(&CIRCUIT_PATTERNS, circuit_pattern, "circuit pattern")?;
+        let m: AnyMatch = match get(&ALLIANCES, alliance, "alliance")? {
+            Alliance::RED => RedRemoteAuto::with_team(team, has_signal_sleeve, signal_zone, pattern).into(),
+            Alliance::BLUE => BlueRemoteAuto::with_team(team, has_signal_sleeve, signal_zone, pattern).into(),
+        };
+        Ok(into_handle(m))
+    })
+}
+
+#[no_mangle]
+pub unsafe extern "system" fn Java_ftc_powerplay_scoring_NativeMatch_free<'local>(
+    _env: JNIEnv<'local>,
+    _class: JClass<'local>,
+    handle: jlong,
+) {
+    if handle != 0 {
+        drop(Box::from_raw(handle as *mut AnyMatch));
+    }
+}
+
+#[no_mangle]
+pub unsafe extern "system" fn Java_ftc_powerplay_scoring_NativeMatch_score<'local>(
+    mut env: JNIEnv<'local>,
+    _class: JClass<'local>,
+    m: jlong,
+    alliance: jint,
+    location: jint,
+) {
+    run(&mut env, |_| {
+        let m = handle(m)?;
+        let location = junction(m, location)?;
+        Ok(m.score_for(get(&ALLIANCES, alliance, "alliance")?, location)?)
+    })
+}
+
+#[no_mangle]
+pub unsafe extern "system" fn Java_ftc_powerplay_scoring_NativeMatch_descore<'local>(
+    mut env: JNIEnv<'local>,
+    _class: JClass<'local>,
+    m: jlong,
+    location: jint,
+) -> jint {
+    run(&mut env, |_| {
+        let m = handle(m)?;
+        let location = junction(m, location)?;
+        Ok(m.descore(location)? as jint)
+    })
+}
+
+#[no_mangle]
+pub unsafe extern "system" fn Java_ftc_powerplay_scoring_NativeMatch_addTerminal<'local>(
+    mut env: JNIEnv<'local>,
+    _class: JClass<'local>,
+    m: jlong,
+    alliance: jint,
+    terminal: jint,
+) {
+    run(&mut env, |_| {
+        let alliance = get(&ALLIANCES, alliance, "alliance")?;
+        Ok(handle(m)?.add_terminal_for(alliance, get(&TERMINALS, terminal, "terminal")?)?)
+    })
+}
+
+#[no_mangle]
+pub unsafe extern "system" fn Java_ftc_powerplay_scoring_NativeMatch_cap<'local>(
+    mut env: JNIEnv<'local>,
+    _class: JClass<'local>,
+    m: jlong,
+    team: jint,
+    location: jint,
+) {
+    run(&mut env, |_| {
+        let m = handle(m)?;
+        let location = junction(m, location)?;
+        Ok(m.cap_for(robot(m, team)?, location)?)
+    })
+}
+
+#[no_mangle]
+pub unsafe extern "system" fn Java_ftc_powerplay_scoring_NativeMatch_penalize<'local>(
+    mut env: JNIEnv<'local>,
+    _class: JClass<'local>,
+    m: jlong,
+    alliance: jint,
+    points: jint,
+) {
+    run(&mut env, |_| {
+        let alliance = get(&ALLIANCES, alliance, "alliance")?;
+        let points = u8::try_from(points).map_err(|_| Failure::InvalidArgument("Penalties must be from 0 to 255 points."))?;
+        Ok(handle(m)?.penalize(alliance, points)?)
+    })
+}
+
+#[no_mangle]
+pub unsafe extern "system" fn Java_ftc_powerplay_scoring_NativeMatch_park<'local>(
+    mut env: JNIEnv<'local>,
+    _class: JClass<'local>,
+    m: jlong,
+    team: jint,
+    location: jint,
+) {
+    run(&mut env, |_| {
+        let m = handle(m)?;
+        let location = get(&PARKING_LOCATIONS, location, "parking location")?;
+        Ok(m.park_for(robot(m, team)?, location)?)
+    })
+}
+
+#[no_mangle]
+pub unsafe extern "system" fn Java_ftc_powerplay_scoring_NativeMatch_advancePhase<'local>(
+    mut env: JNIEnv<'local>,
+    _class: JClass<'local>,
+    m: jlong,
+) -> jint {
+    run(&mut env, |_| Ok(handle(m)?.advance_phase()? as jint))
+}
+
+/// Returns `[alliance, penalty points, auto points, teleop points, end game points, teams...]`,
+/// or null if the match has no results for the alliance.
+#[no_mangle]
+pub unsafe extern "system" fn Java_ftc_powerplay_scoring_NativeMatch_results<'local>(
+    mut env: JNIEnv<'local>,
+    _class: JClass<'local>,
+    m: jlong,
+    alliance: jint,
+) -> jintArray {
+    run(&mut env, |env| {
+        fn pack<const N: usize>(info: &AllianceInfo<N>) -> Vec<jint> {
+            let points = [info.penalty_points, info.auto_points, info.teleop_points, info.endgame_points];
+            let mut packed = vec![info.alliance as jint];
+            packed.extend(points.map(jint::from));
+            packed.extend(info.teams.iter().map(|team| team.0));
+            packed
+        }
+        let alliance = get(&ALLIANCES, alliance, "alliance")?;
+        let packed = match handle(m)?.results().ok_or(MatchError::WrongPhase)? {
+            MatchResult::Traditional(red, _) if alliance == Alliance::RED => pack(&red),
+            MatchResult::Traditional(_, blue) => pack(&blue),
+            MatchResult::Remote(info) if info.alliance == alliance => pack(&info),
+            MatchResult::Remote(_) => return Ok(None),
+        };
+        let array = env.new_int_array(packed.len() as jint)?;
+        env.set_int_array_region(&array, 0, &packed)?;
+        Ok(Some(array.into_raw()))
+    }).unwrap_or(std::ptr::null_mut())
+}
@@ -0,0 +1,381 @@
+//! The module for journaling live matches to disk, so that they survive a crash of the scoring computer.
+//!
+//! A [`JournaledMatch`] tries every action on a copy of the match, then appends it to a file and flushes it to disk
+//! before taking it. Reopening the file replays the actions, which rebuilds the match in the exact phase it was in.
+//! Failed actions are journaled too, since some of them still change the match, but an action that panics is not.
+//!
+//! # Format
+//! A journal starts with the magic bytes `PPJ1`, followed by records. Each record is a length byte,
+//! that many bytes of payload, and a Fletcher-16 checksum of the length and payload.
+//! The first record is the [`MatchSetup`], and every later record is one [`MatchAction`].
+//! A crash while writing can leave the last record short or with a bad checksum. Such a record is reported when
+//! the journal is opened, and stays in the file until it is truncated. A bad record followed by a complete action
+//! means the journal is corrupt, since only the record being written when the crash happened can be cut short.
+//!
+//! A setup starts with its field: 0 for traditional, 1 for red remote and 2 for blue remote.
+//! Traditional setups follow with each robot, red before blue, as its team number (a little-endian i32) and
+//! whether it has a signal sleeve, then the signal zone. Remote setups follow with the team number, whether it
+//! has a signal sleeve, the signal zone and the circuit pattern.
+//!
+//! An action starts with a tag, followed by its fields in order:
+//! 0 score, 1 descore, 2 terminal, 3 cap, 4 penalty, 5 park, 6 card and 7 advance phase.
+//! Enums are stored as their `u8` representation, junctions as their 8-bit encoding,
+//! and robots as their index shifted left by one, plus one for blue.
+use crate::dynamic::*;
+use crate::encoding::{Reader, PARKING_LOCATIONS};
+use crate::remote::*;
+use crate::traditional::*;
+use crate::*;
+use std::fs::{File, OpenOptions};
+use std::io;
+use std::io::{Read, Write};
+use std::panic::{catch_unwind, AssertUnwindSafe};
+use std::path::Path;
+
+const MAGIC: &[u8; 4] = b"PPJ1";
+
+/// Everything needed to create a match before its actions are replayed.
+#[derive(Eq, PartialEq, Copy, Clone, Debug, Hash)]
+pub enum MatchSetup {
+    Traditional {
+        red: [(FtcTeamID, bool); 2],
+        blue: [(FtcTeamID, bool); 2],
+        signal_zone: SignalZone,
+    },
+    RedRemote {
+        team: FtcTeamID,
+        has_signal_sleeve: bool,
+        signal_zone: SignalZone,
+        circuit_pattern: RemoteCircuitPattern,
+    },
+    BlueRemote {
+        team: FtcTeamID,
+        has_signal_sleeve: bool,
+        signal_zone: SignalZone,
+        circuit_pattern: RemoteCircuitPattern,
+    },
+}
+crate::display_impl_as_debug!(MatchSetup);
+
+impl MatchSetup {
+    /// Creates the match, returning None if a team occurs more than once in it.
+    pub fn build(self) -> Option<AnyMatch> {
+        Some(match self {
+            MatchSetup::Traditional { red, blue, signal_zone } => {
+                TraditionalAuto::try_from_teams(red, blue, signal_zone)?.into()
+            }
+            MatchSetup::RedRemote { team, has_signal_sleeve, signal_zone, circuit_pattern } => {
+                RedRemoteAuto::with_team(team, has_signal_sleeve, signal_zone, circuit_pattern).into()
+            }
+            MatchSetup::BlueRemote { team, has_signal_sleeve, signal_zone, circuit_pattern } => {
+                BlueRemoteAuto::with_team(team, has_signal_sleeve, signal_zone, circuit_pattern).into()
+            }
+        })
+    }
+
+    fn field_type(self) -> FieldType {
+        match self {
+            MatchSetup::Traditional { .. } => FieldType::Traditional,
+            MatchSetup::RedRemote { .. } => FieldType::RedRemote,
+            MatchSetup::BlueRemote { .. } => FieldType::BlueRemote,
+        }
+    }
+
+    fn encode(self, out: &mut Vec<u8>) {
+        out.push(self.field_type() as u8);
+        match self {
+            MatchSetup::Traditional { red, blue, signal_zone } => {
+                for (team, has_signal_sleeve) in red.into_iter().chain(blue) {
+                    out.extend(team.0.to_le_bytes());
+                    out.push(has_signal_sleeve as u8);
+                }
+                out.push(signal_zone as u8);
+            }
+            MatchSetup::RedRemote { team, has_signal_sleeve, signal_zone, circuit_pattern }
+            | MatchSetup::BlueRemote { team, has_signal_sleeve, signal_zone, circuit_pattern } => {
+                out.extend(team.0.to_le_bytes());
+                out.extend([has_signal_sleeve as u8, signal_zone as u8, circuit_pattern as u8]);
+            }
+        }
+    }
+
+    fn decode(reader: &mut Reader) -> Option<Self> {
+        let field = reader.byte()?;
+        Some(if field == FieldType::Traditional as u8 {
+            let mut robot = || Some((team(reader)?, reader.flag()?));
+            let red = [robot()?, robot()?];
+            let blue = [robot()?, robot()?];
+            MatchSetup::Traditional { red, blue, signal_zone: reader.signal_zone()? }
+        } else {
+            let team = team(reader)?;
+            let has_signal_sleeve = reader.flag()?;
+            let signal_zone = reader.signal_zone()?;
+            use RemoteCircuitPattern::*;
+            let circuit_pattern =
+                reader.variant(&[Pattern1, Pattern2, Pattern3, Pattern4, Pattern5, Pattern6], |p| p as u8)?;
+            match field {
+                1 => MatchSetup::RedRemote { team, has_signal_sleeve, signal_zone, circuit_pattern },
+                2 => MatchSetup::BlueRemote { team, has_signal_sleeve, signal_zone, circuit_pattern },
+                _ => return None,
+            }
+        })
+    }
+}
+
+// journals store team numbers at a fixed size, unlike the compact encoding
+fn team(reader: &mut Reader) -> Option<FtcTeamID> {
+    reader.bytes().map(|bytes| FtcTeamID(i32::from_le_bytes(bytes)))
+}
+
+fn alliance(reader: &mut Reader) -> Option<Alliance> {
+    reader.variant(&[Alliance::RED, Alliance::BLUE], |a| a as u8)
+}
+
+fn robot(reader: &mut Reader) -> Option<MatchIndex> {
+    reader.byte().map(MatchIndex)
+}
+
+fn encode_action(action: MatchAction, out: &mut Vec<u8>) {
+    let junction = |junction: AnyJunction| match junction {
+        AnyJunction::Traditional(j) => j as u8,
+        AnyJunction::RedRemote(j) => j as u8,
+        AnyJunction::BlueRemote(j) => j as u8,
+    };
+    match action {
+        MatchAction::Score { alliance, junction: j } => out.extend([0, alliance as u8, junction(j)]),
+        MatchAction::Descore { junction: j } => out.extend([1, junction(j)]),
+        MatchAction::AddTerminal { alliance, terminal } => out.extend([2, alliance as u8, terminal as u8]),
+        MatchAction::Cap { robot, junction: j } => out.extend([3, robot.0, junction(j)]),
+        MatchAction::Penalize { alliance, points } => out.extend([4, alliance as u8, points]),
+        MatchAction::Park { robot, location } => out.extend([5, robot.0, location as u8]),
+        MatchAction::Card { robot, card } => out.extend([6, robot.0, card as u8]),
+        MatchAction::AdvancePhase => out.push(7),
+    }
+}
+
+fn junction(field: FieldType, reader: &mut Reader) -> Option<AnyJunction> {
+    Some(match field {
+        FieldType::Traditional => reader.junction::<TraditionalJunction>()?.into(),
+        FieldType::RedRemote => reader.junction::<RedRemoteJunction>()?.into(),
+        FieldType::BlueRemote => reader.junction::<BlueRemoteJunction>()?.into(),
+    })
+}
+
+fn decode_action(field: FieldType, reader: &mut Reader) -> Option<MatchAction> {
+    Some(match reader.byte()? {
+        0 => MatchAction::Score { alliance: alliance(reader)?, junction: junction(field, reader)? },
+        1 => MatchAction::Descore { junction: junction(field, reader)? },
+        2 => MatchAction::AddTerminal {
+            alliance: alliance(reader)?,
+            terminal: reader.variant(&[Terminal::Near, Terminal::Far], |t| t as u8)?,
+        },
+        3 => MatchAction::Cap { robot: robot(reader)?, junction: junction(field, reader)? },
+        4 => MatchAction::Penalize { alliance: alliance(reader)?, points: reader.byte()? },
+        5 => MatchAction::Park { robot: robot(reader)?, location: reader.variant(&PARKING_LOCATIONS, |l| l as u8)? },
+        6 => MatchAction::Card { robot: robot(reader)?, card: reader.variant(&[Card::Yellow, Card::Red], |c| c as u8)? },
+        7 => MatchAction::AdvancePhase,
+        _ => return None,
+    })
+}
+
+fn checksum(bytes: &[u8]) -> [u8; 2] {
+    let (mut low, mut high) = (0u16, 0u16);
+    for &byte in bytes {
+        low = (low + byte as u16) % 255;
+        high = (high + low) % 255;
+    }
+    [low as u8, high as u8]
+}
+
+// wraps a payload, written after the length byte, into a record
+fn seal(record: &mut Vec<u8>) {
+    record[0] = (record.len() - 1) as u8;
+    let sum = checksum(record);
+    record.extend(sum);
+}
+
+enum Record<'a> {
+    Complete(&'a [u8]),
+    // cut short or damaged
+    Bad,
+    End,
+}
+
+fn next_record(bytes: &[u8]) -> (Record<'_>, usize) {
+    let Some(&len) = bytes.first() else {
+        return (Record::End, 0);
+    };
+    let len = len as usize;
+    match bytes.get(..len + 3) {
+        Some(record) if checksum(&record[..len + 1]) == record[len + 1..] => {
+            (Record::Complete(&record[1..len + 1]), len + 3)
+        }
+        _ => (Record::Bad, len + 3),
+    }
+}
+
+// decodes the payload of a record, which holds exactly one action
+fn parse_action(field: FieldType, payload: &[u8]) -> Option<MatchAction> {
+    let mut reader = Reader::new(payload);
+    decode_action(field, &mut reader).filter(|_| reader.is_empty())
+}
+
+// whether a bad record, and everything after it, could have been left by a crash while it was written.
+// a damaged length can make any record reach the end of the file, so this looks for an action after it instead.
+fn is_torn(field: FieldType, rest: &[u8]) -> bool {
+    !(1..rest.len()).any(|start| match next_record(&rest[start..]).0 {
+        Record::Complete(payload) => parse_action(field, payload).is_some(),
+        Record::Bad | Record::End => false,
+    })
+}
+
+/// Every way journaling a match can fail.
+#[derive(Debug)]
+pub enum JournalError {
+    Io(io::Error),
+    /// The action was journaled, but failed in the match.
+    Match(MatchError),
+    /// A team occurs more than once in the match setup.
+    InvalidSetup,
+    /// The file does not start with a journal header and match setup.
+    NotAJournal,
+    /// A record before the last one is damaged, or holds an action the match panics on. This is its offset in the file.
+    Corrupt(u64),
+    /// The match panicked on the action, which was not journaled.
+    Panic,
+    /// The journal still ends with a partly written record, which [`JournaledMatch::truncate`] has to remove
+    /// before more actions can follow it.
+    Torn,
+}
+crate::display_impl_as_debug!(JournalError);
+
+impl From<io::Error> for JournalError {
+    #[inline(always)]
+    fn from(value: io::Error) -> Self {
+        JournalError::Io(value)
+    }
+}
+
+/// What was found when a journal was reopened.
+#[derive(Eq, PartialEq, Clone, Debug, Hash)]
+pub struct Recovery {
+    /// The number of actions that were replayed.
+    pub actions: usize,
+    /// The bytes of a partly written last record, if there was one. They stay in the file until
+    /// [`JournaledMatch::truncate`] removes them.
+    pub torn: Option<Vec<u8>>,
+}
+crate::display_impl_as_debug!(Recovery);
+
+/// A match that journals every action to disk before taking it.
+#[derive(Debug)]
+pub struct JournaledMatch {
+    game: AnyMatch,
+    file: File,
+    // the offset of a partly written last record, which new records cannot follow
+    torn: Option<u64>,
+}
+
+impl JournaledMatch {
+    /// Starts a new journal at `path`, failing if a file is already there.
+    pub fn create(path: impl AsRef<Path>, setup: MatchSetup) -> Result<Self, JournalError> {
+        let game = setup.build().ok_or(JournalError::InvalidSetup)?;
+        let mut file = OpenOptions::new().append(true).create_new(true).open(path)?;
+        let mut record = vec![0];
+        setup.encode(&mut record);
+        seal(&mut record);
+        file.write_all(&[&MAGIC[..], &record].concat())?;
+        file.sync_all()?;
+        Ok(Self { game, file, torn: None })
+    }
+
+    /// Reopens a journal, replaying its actions to rebuild the match. The file is not changed.
+    /// A partly written last record is returned in the [`Recovery`], and has to be removed with
+    /// [`truncate`](Self::truncate) before more actions can be journaled.
+    pub fn open(path: impl AsRef<Path>) -> Result<(Self, Recovery), JournalError> {
+        let mut file = OpenOptions::new().read(true).append(true).open(path)?;
+        let mut bytes = Vec::new();
+        file.read_to_end(&mut bytes)?;
+        let rest = bytes.strip_prefix(MAGIC).ok_or(JournalError::NotAJournal)?;
+        let (Record::Complete(payload), len) = next_record(rest) else {
+            return Err(JournalError::NotAJournal);
+        };
+        let mut reader = Reader::new(payload);
+        let setup = MatchSetup::decode(&mut reader)
+            .filter(|_| reader.is_empty())
+            .ok_or(JournalError::NotAJournal)?;
+        let mut game = setup.build().ok_or(JournalError::InvalidSetup)?;
+
+        let mut offset = MAGIC.len() + len;
+        let mut recovery = Recovery { actions: 0, torn: None };
+        let mut torn = None;
+        loop {
+            let (record, len) = next_record(&bytes[offset..]);
+            match record {
+                Record::Complete(payload) => {
+                    let action = parse_action(setup.field_type(), payload).ok_or(JournalError::Corrupt(offset as u64))?;
+                    // failures are part of the journal, and happen again the same way
+                    let _ = catch_unwind(AssertUnwindSafe(|| game.apply(action))).map_err(|_| JournalError::Corrupt(offset as u64))?;
+                    recovery.actions += 1;
+                }
+                Record::Bad if is_torn(setup.field_type(), &bytes[offset..]) => {
+                    recovery.torn = Some(bytes[offset..].to_vec());
+                    torn = Some(offset as u64);
+                    break;
+                }
+                Record::Bad => return Err(JournalError::Corrupt(offset as u64)),
+                Record::End => break,
+            }
+            offset += len;
+        }
+        Ok((Self { game, file, torn }, recovery))
+    }
+
+    /// Removes the partly written last record that [`open`](Self::open) found, so that new actions follow the last
+    /// complete one. Does nothing if there was none.
+    pub fn truncate(&mut self) -> Result<(), JournalError> {
+        if let Some(offset) = self.torn {
+            self.file.set_len(offset)?;
+            self.file.sync_all()?;
+            self.torn = None;
+        }
+        Ok(())
+    }
+
+    /// Journals an action and then takes it.
+    /// Actions on a junction from another field, and actions the match panics on, are rejected without being journaled.
+    pub fn apply(&mut self, action: MatchAction) -> Result<(), JournalError> {
+        if self.torn.is_some() {
+            return Err(JournalError::Torn);
+        }
+        if let MatchAction::Score { junction, .. } | MatchAction::Descore { junction } | MatchAction::Cap { junction, .. } =
+            action
+        {
+            if junction.field_type() != self.game.field_type() {
+                return Err(JournalError::Match(MatchError::WrongField));
+            }
+        }
+        // a journaled panic would happen again every time the journal is opened
+        let mut game = self.game.clone();
+        let result = catch_unwind(AssertUnwindSafe(|| game.apply(action))).map_err(|_| JournalError::Panic)?;
+        let mut record = vec![0];
+        encode_action(action, &mut record);
+        seal(&mut record);
+        self.file.write_all(&record)?;
+        self.file.sync_data()?;
+        self.game = game;
+        result.map_err(JournalError::Match)
+    }
+
+    /// The match as of the last action.
+    #[inline]
+    pub fn game(&self) -> &AnyMatch {
+        &self.game
+    }
+
+    /// Stops journaling, returning the match.
+    #[inline]
+    pub fn into_game(self) -> AnyMatch {
+        self.game
+    }
+}
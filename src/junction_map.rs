@@ -0,0 +1,72 @@
+//! A fixed-capacity map keyed by junction, so that a match never needs a heap-allocated map.
+use crate::FieldCoordinate;
+
+/// Enough slots for every junction on a 5x5 field.
+const CAPACITY: usize = 25;
+
+/// A map from junctions to values, with one slot for each junction on the field.
+#[derive(Debug, Clone)]
+pub(crate) struct JunctionMap<K: FieldCoordinate, V> {
+    slots: [Option<(K, V)>; CAPACITY],
+    len: u8,
+}
+
+impl<K: FieldCoordinate, V> Default for JunctionMap<K, V> {
+    fn default() -> Self {
+        const {
+            assert!((K::ROWS * K::COLUMNS) as usize <= CAPACITY, "The field is too large for a junction map.");
+        }
+        Self {
+            slots: [const { None }; CAPACITY],
+            len: 0,
+        }
+    }
+}
+
+impl<K: FieldCoordinate, V> JunctionMap<K, V> {
+    #[inline(always)]
+    fn slot(key: K) -> usize {
+        (key.row() * K::COLUMNS + key.column()) as usize
+    }
+
+    #[inline]
+    pub(crate) fn get(&self, key: &K) -> Option<&V> {
+        self.slots[Self::slot(*key)].as_ref().map(|(_, value)| value)
+    }
+
+    #[inline]
+    pub(crate) fn get_mut(&mut self, key: &K) -> Option<&mut V> {
+        self.slots[Self::slot(*key)].as_mut().map(|(_, value)| value)
+    }
+
+    #[inline]
+    pub(crate) fn contains_key(&self, key: &K) -> bool {
+        self.slots[Self::slot(*key)].is_some()
+    }
+
+    pub(crate) fn insert(&mut self, key: K, value: V) -> Option<V> {
+        let previous = self.slots[Self::slot(key)].replace((key, value));
+        if previous.is_none() {
+            self.len += 1;
+        }
+        previous.map(|(_, value)| value)
+    }
+
+    pub(crate) fn remove(&mut self, key: &K) -> Option<V> {
+        let previous = self.slots[Self::slot(*key)].take();
+        if previous.is_some() {
+            self.len -= 1;
+        }
+        previous.map(|(_, value)| value)
+    }
+
+    #[inline(always)]
+    pub(crate) fn len(&self) -> usize {
+        self.len as usize
+    }
+
+    /// Iterates over every junction with a value, in row-major order.
+    pub(crate) fn iter(&self) -> impl Iterator<Item = (&K, &V)> {
+        self.slots.iter().flatten().map(|(key, value)| (key, value))
+    }
+}
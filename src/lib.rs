@@ -1,43 +1,75 @@
-use crate::id::*;
-use crate::locations::*;
+#![cfg_attr(not(feature = "std"), no_std)]
+extern crate alloc;
+
+pub use crate::events::*;
+pub use crate::id::*;
+pub use crate::locations::*;
+pub use crate::status::*;
 use bitvec::prelude::*;
-use std::fmt::{Debug, Display, Formatter};
-use std::mem::transmute;
-use std::num::NonZeroU8;
-use std::ops::Index;
+use core::fmt::{Debug, Display, Formatter};
+use core::mem::transmute;
+use core::num::NonZeroU8;
+use core::ops::Index;
 
 #[cfg(test)]
 mod tests;
 
+mod events;
 mod id;
+mod junction_map;
 mod locations;
-mod remote;
-mod traditional;
+mod status;
+pub mod command;
+pub mod dynamic;
+pub mod encoding;
+#[cfg(feature = "ffi")]
+pub mod ffi;
+pub mod geometry;
+#[cfg(feature = "jni")]
+pub mod java;
+#[cfg(feature = "std")]
+pub mod journal;
+#[cfg(feature = "python")]
+pub mod python;
+pub mod reconcile;
+pub mod remote;
+pub mod render;
+#[cfg(feature = "server")]
+pub mod server;
+pub mod traditional;
+#[cfg(feature = "wasm")]
+pub mod wasm;
 
 // allows for abstraction over any field type
 // (0, 0) is one coordinate of the field
 // TODO decide public trait bounds on this type. Copy is sadly probably needed
 pub trait FieldCoordinate:
-    Ord + Copy + nohash::IsEnabled + Display + Debug + sealed::Sealed
+    Ord + Copy + Display + Debug + sealed::Sealed + 'static
 {
     const ROWS: u8;
     const COLUMNS: u8;
+    /// Every junction on the field.
+    const ALL: &'static [Self];
     fn points(self) -> u8;
     fn row(self) -> u8;
     fn column(self) -> u8;
     fn coordinate(self) -> (u8, u8) {
         (self.row(), self.column())
     }
+    /// Finds the junction at a coordinate, returning None if it is not on the field.
+    fn from_coordinate(row: u8, column: u8) -> Option<Self> {
+        Self::ALL.iter().copied().find(|junction| junction.coordinate() == (row, column))
+    }
 }
 
 #[macro_export]
 #[doc(hidden)]
 macro_rules! display_impl_as_debug {
     ($struc:ty) => {
-        impl std::fmt::Display for $struc {
+        impl core::fmt::Display for $struc {
             #[inline(always)]
-            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-                <Self as std::fmt::Debug>::fmt(self, f)
+            fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+                <Self as core::fmt::Debug>::fmt(self, f)
             }
         }
     };
@@ -46,15 +78,14 @@ macro_rules! display_impl_as_debug {
 #[macro_export]
 #[doc(hidden)]
 macro_rules! junction_impl {
-    ($struc:ty, $rows:literal, $columns:literal) => {
+    ($struc:ty, $rows:literal, $columns:literal, $all:expr) => {
         impl Sealed for $struc {}
-        impl nohash::IsEnabled for $struc {}
 
         crate::display_impl_as_debug!($struc);
 
-        // for safety with nohash
-        impl std::hash::Hash for $struc {
-            fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        // hashes only the encoding, which is unique to each junction
+        impl core::hash::Hash for $struc {
+            fn hash<H: core::hash::Hasher>(&self, state: &mut H) {
                 state.write_u8(*self as u8);
             }
         }
@@ -62,6 +93,7 @@ macro_rules! junction_impl {
         impl FieldCoordinate for $struc {
             const ROWS: u8 = $rows;
             const COLUMNS: u8 = $columns;
+            const ALL: &'static [Self] = $all;
             fn points(self) -> u8 {
                 (self as u8 & 0b11) + 2
             }
@@ -97,6 +129,23 @@ pub trait Match<T: FieldCoordinate>:
         self.index_of(robot).map(|i| i.alliance())
     }
     fn index_of(&self, robot: FtcTeamID) -> Option<MatchIndex>;
+    fn status_of(&self, robot: MatchIndex) -> RobotStatus;
+    fn set_status(&mut self, robot: MatchIndex, status: RobotStatus);
+    /// Gives a robot a card. A second yellow card in the same match becomes a red card.
+    fn card_for(&mut self, robot: MatchIndex, card: Card) {
+        let mut status = self.status_of(robot);
+        status.give_card(card);
+        self.set_status(robot, status);
+    }
+    /// Creates an independent copy of this match, so hypothetical actions can be applied to it
+    /// and compared against the original.
+    #[inline(always)]
+    fn fork(&self) -> Self
+    where
+        Self: Clone,
+    {
+        self.clone()
+    }
 }
 pub trait Auto<T: FieldCoordinate, const R: usize, const B: usize>: Match<T> {
     type TeleOpType: TeleOp<T, R, B>; // FIXME we can't make this extend From<Self> without making this no longer object safe
@@ -129,7 +178,7 @@ fn as_u8(n: Option<NonZeroU8>) -> u8 {
 impl ConeStack {
     fn new(value: Alliance) -> ConeStack {
         let mut stack = ConeStack {
-            data: BitArray::new([value as u8, 0, 0, 0, 0, 0, 0, 0]),
+            data: BitArray::new([(value as u8) << 1, 0, 0, 0, 0, 0, 0, 0]),
             top_idx: NonZeroU8::new(1),
             red_count: 0,
             blue_count: 0,
@@ -188,6 +237,10 @@ impl ConeStack {
     fn is_empty(&self) -> bool {
         self.top_idx.is_none()
     }
+    /// The cones from the bottom up.
+    fn cones(&self) -> impl Iterator<Item = Alliance> + '_ {
+        (1..=as_u8(self.top_idx) as usize).map(|i| Alliance::from(self.data[i]))
+    }
 }
 
 #[derive(Eq, PartialEq, Copy, Clone, Debug, Hash)]
@@ -211,7 +264,7 @@ pub enum ConeRemovalError {
 display_impl_as_debug!(ConeRemovalError);
 
 // possession is handled by the Match implementation
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 struct InternalAllianceInfo<T: FieldCoordinate, const N: usize> {
     teams: [FtcTeamID; N],
     penalty_points: u16,
@@ -219,6 +272,8 @@ struct InternalAllianceInfo<T: FieldCoordinate, const N: usize> {
     terminal_amounts: [u8; 2],
     beacon_placements: [MaybeInvalid<T>; N],
     parking_locations: [Option<ParkingLocation>; N],
+    roster: Option<AllianceRoster>,
+    statuses: [RobotStatus; N],
 }
 
 impl<T: FieldCoordinate, const N: usize> InternalAllianceInfo<T, N> {
@@ -230,6 +285,8 @@ impl<T: FieldCoordinate, const N: usize> InternalAllianceInfo<T, N> {
             terminal_amounts: [0; 2],
             beacon_placements: [MaybeInvalid::None; N],
             parking_locations: [None; N],
+            roster: None,
+            statuses: [RobotStatus::default(); N],
         }
     }
 
@@ -263,11 +320,14 @@ pub struct AllianceInfo<const N: usize> {
     pub auto_points: u16,
     pub teleop_points: u16,
     pub endgame_points: u16,
+    /// The full playoff alliance, if this was a playoff match.
+    pub roster: Option<AllianceRoster>,
+    pub statuses: [RobotStatus; N],
 }
 // cannot use macro because of the type parameter
 impl<const N: usize> Display for AllianceInfo<N> {
     #[inline(always)]
-    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
         <Self as Debug>::fmt(self, f)
     }
 }
@@ -1,4 +1,4 @@
-use std::mem::transmute;
+use core::mem::transmute;
 
 #[derive(Eq, PartialEq, Copy, Clone, Debug, Hash)]
 #[repr(u8)]
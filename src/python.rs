@@ -0,0 +1,371 @@
+//! Python bindings for the scoring engine, built with pyo3.
+//!
+//! Every match is a `Match`, created with `Match.traditional` or `Match.remote` and moved through its phases
+//! with `advance_phase` and `end_match`. Junctions, alliances and locations are Python [`enum`]s,
+//! although their names as strings are also accepted. Robots are identified by their team number.
+//!
+//! Failed actions raise subclasses of `ScoringError`: `ConeRemovalError` and `BeaconError` each have a subclass
+//! for every variant of their Rust counterparts, and `BeaconScoredOutsideEndgame` is its own exception.
+//! Junctions from the wrong field and teams that are not in the match raise `ValueError`.
+//!
+//! [`enum`]: https://docs.python.org/3/library/enum.html
+use crate::dynamic::*;
+use crate::remote::*;
+use crate::traditional::*;
+use crate::*;
+use pyo3::create_exception;
+use pyo3::exceptions::{PyException, PyValueError};
+use pyo3::prelude::*;
+use pyo3::sync::GILOnceCell;
+use pyo3::types::PyDict;
+
+const MODULE: &str = "ftc_powerplay_scoring";
+
+create_exception!(ftc_powerplay_scoring, ScoringError, PyException, "An action that is not allowed in a match.");
+create_exception!(ftc_powerplay_scoring, ConeRemovalError, ScoringError, "A cone could not be removed from a junction.");
+create_exception!(ftc_powerplay_scoring, JunctionIsEmptyError, ConeRemovalError);
+create_exception!(ftc_powerplay_scoring, BeaconOnJunctionError, ConeRemovalError);
+create_exception!(ftc_powerplay_scoring, BeaconError, ScoringError, "A beacon could not be scored.");
+create_exception!(ftc_powerplay_scoring, JunctionIsCappedError, BeaconError);
+create_exception!(ftc_powerplay_scoring, BeaconPreviouslyScoredError, BeaconError);
+create_exception!(ftc_powerplay_scoring, BeaconScoredOutsideEndgame, ScoringError);
+create_exception!(ftc_powerplay_scoring, WrongPhaseError, ScoringError, "The action cannot be taken in the current phase.");
+
+fn error(error: MatchError) -> PyErr {
+    let message = error.to_string();
+    match error {
+        MatchError::JunctionIsEmpty => JunctionIsEmptyError::new_err(message),
+        MatchError::BeaconOnJunction => BeaconOnJunctionError::new_err(message),
+        MatchError::JunctionIsCapped => JunctionIsCappedError::new_err(message),
+        MatchError::BeaconPreviouslyScored => BeaconPreviouslyScoredError::new_err(message),
+        MatchError::BeaconScoredOutsideEndgame => BeaconScoredOutsideEndgame::new_err(message),
+        MatchError::WrongPhase => WrongPhaseError::new_err(message),
+        MatchError::WrongField | MatchError::NotInMatch => PyValueError::new_err(message),
+    }
+}
+
+// each enum class is created the first time it is needed, with its members named and valued like the Rust variants
+macro_rules! python_enum {
+    ($fn_name:ident, $class:literal, $all:expr) => {
+        fn $fn_name(py: Python<'_>) -> PyResult<&Bound<'_, PyAny>> {
+            static CLASS: GILOnceCell<Py<PyAny>> = GILOnceCell::new();
+            CLASS.get_or_try_init(py, || {
+                let members: Vec<(String, String)> = $all.iter().map(|v| (v.to_string(), v.to_string())).collect();
+                let class = py.import("enum")?.getattr("Enum")?.call1(($class, members))?;
+                class.setattr("__module__", MODULE)?;
+                Ok(class.unbind())
+            }).map(|class| class.bind(py))
+        }
+    };
+}
+
+python_enum!(alliance_class, "Alliance", [Alliance::RED, Alliance::BLUE]);
+python_enum!(signal_zone_class, "SignalZone", [SignalZone::Left, SignalZone::Middle, SignalZone::Right]);
+python_enum!(terminal_class, "Terminal", [Terminal::Near, Terminal::Far]);
+python_enum!(parking_location_class, "ParkingLocation", {
+    use ParkingLocation::*;
+    [LeftSignalZone, MiddleSignalZone, RightSignalZone, NearTerminal, FarTerminal, Substation]
+});
+python_enum!(card_class, "Card", [Card::Yellow, Card::Red]);
+python_enum!(phase_class, "MatchPhase", {
+    use MatchPhase::*;
+    [Auto, TeleOp, EndGame, Finished]
+});
+python_enum!(field_type_class, "FieldType", [FieldType::Traditional, FieldType::RedRemote, FieldType::BlueRemote]);
+python_enum!(circuit_pattern_class, "RemoteCircuitPattern", {
+    use RemoteCircuitPattern::*;
+    [Pattern1, Pattern2, Pattern3, Pattern4, Pattern5, Pattern6]
+});
+python_enum!(traditional_junction_class, "TraditionalJunction", TraditionalJunction::ALL);
+python_enum!(red_remote_junction_class, "RedRemoteJunction", RedRemoteJunction::ALL);
+python_enum!(blue_remote_junction_class, "BlueRemoteJunction", BlueRemoteJunction::ALL);
+
+fn to_python<'py, T: Display>(class: &Bound<'py, PyAny>, value: T) -> PyResult<Bound<'py, PyAny>> {
+    class.getattr(value.to_string().as_str())
+}
+
+/// Converts a member of `class`, or a string naming one of `all` in any case.
+fn from_python<T: Display + Copy>(class: &Bound<'_, PyAny>, all: &[T], value: &Bound<'_, PyAny>) -> PyResult<T> {
+    let found = if let Ok(name) = value.extract::<String>() {
+        all.iter().copied().find(|v| v.to_string().eq_ignore_ascii_case(&name))
+    } else if value.is_instance(class)? {
+        let name: String = value.getattr("name")?.extract()?;
+        all.iter().copied().find(|v| v.to_string() == name)
+    } else {
+        None
+    };
+    match found {
+        Some(found) => Ok(found),
+        None => Err(PyValueError::new_err(format!("{value} is not a valid {}.", class.getattr("__name__")?))),
+    }
+}
+
+fn alliance(value: &Bound<'_, PyAny>) -> PyResult<Alliance> {
+    from_python(alliance_class(value.py())?, &[Alliance::RED, Alliance::BLUE], value)
+}
+
+fn signal_zone(value: &Bound<'_, PyAny>) -> PyResult<SignalZone> {
+    from_python(signal_zone_class(value.py())?, &[SignalZone::Left, SignalZone::Middle, SignalZone::Right], value)
+}
+
+/// The results of one alliance in a finished match.
+#[pyclass(name = "AllianceInfo", module = "ftc_powerplay_scoring", frozen)]
+#[derive(Debug, Clone)]
+pub struct PyAllianceInfo {
+    alliance: Alliance,
+    #[pyo3(get)]
+    teams: Vec<i32>,
+    #[pyo3(get)]
+    penalty_points: u16,
+    #[pyo3(get)]
+    auto_points: u16,
+    #[pyo3(get)]
+    teleop_points: u16,
+    #[pyo3(get)]
+    endgame_points: u16,
+}
+
+impl<const N: usize> From<AllianceInfo<N>> for PyAllianceInfo {
+    fn from(value: AllianceInfo<N>) -> Self {
+        Self {
+            alliance: value.alliance,
+            teams: value.teams.iter().map(|team| team.0).collect(),
+            penalty_points: value.penalty_points,
+            auto_points: value.auto_points,
+            teleop_points: value.teleop_points,
+            endgame_points: value.endgame_points,
+        }
+    }
+}
+
+#[pymethods]
+impl PyAllianceInfo {
+    #[getter]
+    fn alliance<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyAny>> {
+        to_python(alliance_class(py)?, self.alliance)
+    }
+
+    /// The points this alliance scored, without penalty points committed by the opponent.
+    #[getter]
+    fn points(&self) -> u16 {
+        self.auto_points + self.teleop_points + self.endgame_points
+    }
+
+    fn __repr__(&self) -> String {
+        format!(
+            "AllianceInfo(alliance={}, teams={:?}, penalty_points={}, auto_points={}, teleop_points={}, endgame_points={})",
+            self.alliance, self.teams, self.penalty_points, self.auto_points, self.teleop_points, self.endgame_points
+        )
+    }
+}
+
+/// A match on any field, in any phase.
+#[pyclass(name = "Match", module = "ftc_powerplay_scoring")]
+#[derive(Debug, Clone)]
+pub struct PyMatch(AnyMatch);
+
+impl PyMatch {
+    fn junction(&self, value: &Bound<'_, PyAny>) -> PyResult<AnyJunction> {
+        let py = value.py();
+        // a junction from another field is reported as being on the wrong field
+        let junction = match self.0.field_type() {
+            FieldType::Traditional => {
+                from_python(traditional_junction_class(py)?, TraditionalJunction::ALL, value).map(AnyJunction::from)
+            }
+            FieldType::RedRemote => {
+                from_python(red_remote_junction_class(py)?, RedRemoteJunction::ALL, value).map(AnyJunction::from)
+            }
+            FieldType::BlueRemote => {
+                from_python(blue_remote_junction_class(py)?, BlueRemoteJunction::ALL, value).map(AnyJunction::from)
+            }
+        };
+        junction.map_err(|_| error(MatchError::WrongField))
+    }
+
+    fn robot(&self, team: i32) -> PyResult<MatchIndex> {
+        self.0.index_of(FtcTeamID(team)).ok_or_else(|| error(MatchError::NotInMatch))
+    }
+
+    fn results_dict<'py>(&self, py: Python<'py>) -> PyResult<Option<Bound<'py, PyDict>>> {
+        let Some(results) = self.0.results() else {
+            return Ok(None);
+        };
+        let dict = PyDict::new(py);
+        let class = alliance_class(py)?;
+        match results {
+            MatchResult::Traditional(red, blue) => {
+                dict.set_item(to_python(class, Alliance::RED)?, PyAllianceInfo::from(red))?;
+                dict.set_item(to_python(class, Alliance::BLUE)?, PyAllianceInfo::from(blue))?;
+            }
+            MatchResult::Remote(info) => {
+                dict.set_item(to_python(class, info.alliance)?, PyAllianceInfo::from(info))?;
+            }
+        }
+        Ok(Some(dict))
+    }
+}
+
+#[pymethods]
+impl PyMatch {
+    /// Creates a traditional match in auto.
+    /// Each alliance is a pair of `(team, has_signal_sleeve)` tuples.
+    #[staticmethod]
+    fn traditional(
+        red: [(i32, bool); 2],
+        blue: [(i32, bool); 2],
+        signal_zone: &Bound<'_, PyAny>,
+    ) -> PyResult<Self> {
+        let robots = |alliance: [(i32, bool); 2]| alliance.map(|(team, sleeve)| (FtcTeamID(team), sleeve));
+        TraditionalAuto::try_from_teams(robots(red), robots(blue), self::signal_zone(signal_zone)?)
+            .map(|auto| PyMatch(auto.into()))
+            .ok_or_else(|| PyValueError::new_err("A team cannot be in a match more than once."))
+    }
+
+    /// Creates a remote match in auto, for a single team on the given alliance.
+    #[staticmethod]
+    fn remote(
+        alliance: &Bound<'_, PyAny>,
+        team: i32,
+        has_signal_sleeve: bool,
+        signal_zone: &Bound<'_, PyAny>,
+        circuit_pattern: &Bound<'_, PyAny>,
+    ) -> PyResult<Self> {
+        use RemoteCircuitPattern::*;
+        let team = FtcTeamID(team);
+        let signal_zone = self::signal_zone(signal_zone)?;
+        let pattern = from_python(
+            circuit_pattern_class(circuit_pattern.py())?,
+            &[Pattern1, Pattern2, Pattern3, Pattern4, Pattern5, Pattern6],
+            circuit_pattern,
+        )?;
+        Ok(PyMatch(match self::alliance(alliance)? {
+            Alliance::RED => RedRemoteAuto::with_team(team, has_signal_sleeve, signal_zone, pattern).into(),
+            Alliance::BLUE => BlueRemoteAuto::with_team(team, has_signal_sleeve, signal_zone, pattern).into(),
+        }))
+    }
+
+    #[getter]
+    fn field_type<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyAny>> {
+        to_python(field_type_class(py)?, self.0.field_type())
+    }
+
+    #[getter]
+    fn phase<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyAny>> {
+        to_python(phase_class(py)?, self.0.phase())
+    }
+
+    /// The teams on an alliance. This is empty for the missing alliance in a remote match.
+    fn teams(&self, alliance: &Bound<'_, PyAny>) -> PyResult<Vec<i32>> {
+        Ok(self.0.teams(self::alliance(alliance)?).iter().map(|team| team.0).collect())
+    }
+
+    /// An independent copy of this match, for trying out what might happen next.
+    fn copy(&self) -> Self {
+        self.clone()
+    }
+
+    fn __copy__(&self) -> Self {
+        self.clone()
+    }
+
+    fn score(&mut self, alliance: &Bound<'_, PyAny>, junction: &Bound<'_, PyAny>) -> PyResult<()> {
+        let location = self.junction(junction)?;
+        self.0.score_for(self::alliance(alliance)?, location).map_err(error)
+    }
+
+    /// Removes the top cone of a junction, returning the alliance it belonged to.
+    fn descore<'py>(&mut self, junction: &Bound<'py, PyAny>) -> PyResult<Bound<'py, PyAny>> {
+        let location = self.junction(junction)?;
+        let alliance = self.0.descore(location).map_err(error)?;
+        to_python(alliance_class(junction.py())?, alliance)
+    }
+
+    fn add_terminal(&mut self, alliance: &Bound<'_, PyAny>, terminal: &Bound<'_, PyAny>) -> PyResult<()> {
+        let terminal = from_python(terminal_class(terminal.py())?, &[Terminal::Near, Terminal::Far], terminal)?;
+        self.0.add_terminal_for(self::alliance(alliance)?, terminal).map_err(error)
+    }
+
+    /// Scores a team's beacon on a junction.
+    fn cap(&mut self, team: i32, junction: &Bound<'_, PyAny>) -> PyResult<()> {
+        let location = self.junction(junction)?;
+        self.0.cap_for(self.robot(team)?, location).map_err(error)
+    }
+
+    fn penalize(&mut self, alliance: &Bound<'_, PyAny>, points: u8) -> PyResult<()> {
+        self.0.penalize(self::alliance(alliance)?, points).map_err(error)
+    }
+
+    /// Parks a team. Robots can park anywhere in auto, but only in a terminal in end game.
+    fn park(&mut self, team: i32, location: &Bound<'_, PyAny>) -> PyResult<()> {
+        use ParkingLocation::*;
+        let location = from_python(
+            parking_location_class(location.py())?,
+            &[LeftSignalZone, MiddleSignalZone, RightSignalZone, NearTerminal, FarTerminal, Substation],
+            location,
+        )?;
+        self.0.park_for(self.robot(team)?, location).map_err(error)
+    }
+
+    /// Gives a team a card. A second yellow card in the same match becomes a red card.
+    fn card(&mut self, team: i32, card: &Bound<'_, PyAny>) -> PyResult<()> {
+        let card = from_python(card_class(card.py())?, &[Card::Yellow, Card::Red], card)?;
+        self.0.card_for(self.robot(team)?, card).map_err(error)
+    }
+
+    /// Moves the match to its next phase, ending it after end game, and returns the new phase.
+    fn advance_phase<'py>(&mut self, py: Python<'py>) -> PyResult<Bound<'py, PyAny>> {
+        let phase = self.0.advance_phase().map_err(error)?;
+        to_python(phase_class(py)?, phase)
+    }
+
+    /// Ends a match in end game, returning each alliance's results keyed by `Alliance`.
+    fn end_match<'py>(&mut self, py: Python<'py>) -> PyResult<Bound<'py, PyDict>> {
+        if self.0.phase() != MatchPhase::EndGame {
+            return Err(error(MatchError::WrongPhase));
+        }
+        self.0.advance_phase().map_err(error)?;
+        Ok(self.results_dict(py)?.unwrap())
+    }
+
+    /// The results of the match, keyed by `Alliance`, or `None` if the match is not finished.
+    fn results<'py>(&self, py: Python<'py>) -> PyResult<Option<Bound<'py, PyDict>>> {
+        self.results_dict(py)
+    }
+
+    fn __repr__(&self) -> String {
+        format!("Match(field_type={}, phase={})", self.0.field_type(), self.0.phase())
+    }
+}
+
+#[pymodule]
+#[pyo3(name = "ftc_powerplay_scoring")]
+pub fn python_module(module: &Bound<'_, PyModule>) -> PyResult<()> {
+    let py = module.py();
+    module.add_class::<PyMatch>()?;
+    module.add_class::<PyAllianceInfo>()?;
+    for (name, class) in [
+        ("Alliance", alliance_class(py)?),
+        ("SignalZone", signal_zone_class(py)?),
+        ("Terminal", terminal_class(py)?),
+        ("ParkingLocation", parking_location_class(py)?),
+        ("Card", card_class(py)?),
+        ("MatchPhase", phase_class(py)?),
+        ("FieldType", field_type_class(py)?),
+        ("RemoteCircuitPattern", circuit_pattern_class(py)?),
+        ("TraditionalJunction", traditional_junction_class(py)?),
+        ("RedRemoteJunction", red_remote_junction_class(py)?),
+        ("BlueRemoteJunction", blue_remote_junction_class(py)?),
+    ] {
+        module.add(name, class)?;
+    }
+    module.add("ScoringError", py.get_type::<ScoringError>())?;
+    module.add("ConeRemovalError", py.get_type::<ConeRemovalError>())?;
+    module.add("JunctionIsEmptyError", py.get_type::<JunctionIsEmptyError>())?;
+    module.add("BeaconOnJunctionError", py.get_type::<BeaconOnJunctionError>())?;
+    module.add("BeaconError", py.get_type::<BeaconError>())?;
+    module.add("JunctionIsCappedError", py.get_type::<JunctionIsCappedError>())?;
+    module.add("BeaconPreviouslyScoredError", py.get_type::<BeaconPreviouslyScoredError>())?;
+    module.add("BeaconScoredOutsideEndgame", py.get_type::<BeaconScoredOutsideEndgame>())?;
+    module.add("WrongPhaseError", py.get_type::<WrongPhaseError>())?;
+    Ok(())
+}
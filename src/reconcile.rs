@@ -0,0 +1,376 @@
+//! The module for merging the scoring of several referees into one authoritative match.
+//!
+//! Each referee records what they see into their own copy of the match, watching either one alliance or
+//! the whole field. When a phase ends, the [`Reconciler`] compares what the referees recorded about each
+//! [`Subject`]. Actions everyone agrees on are applied to the authoritative match, and every disagreement
+//! becomes a [`Conflict`] in a review queue for the head referee. The authoritative match only moves past a
+//! phase once its conflicts are resolved, so a match with conflicts cannot end until they are reviewed.
+//! Whenever the authoritative match finishes a phase, every referee's copy starts again from it, so referees
+//! watching one alliance still see the other alliance's cones, and nobody keeps recording against a version
+//! the head referee overruled.
+use crate::dynamic::*;
+use crate::*;
+use alloc::collections::VecDeque;
+use alloc::vec::Vec;
+use core::mem::take;
+
+/// Identifies a referee of a [`Reconciler`].
+#[derive(Ord, PartialOrd, Eq, PartialEq, Copy, Clone, Debug, Hash)]
+pub struct RefereeId(usize);
+crate::display_impl_as_debug!(RefereeId);
+
+/// Identifies a conflict in the review queue.
+#[derive(Ord, PartialOrd, Eq, PartialEq, Copy, Clone, Debug, Hash)]
+pub struct ConflictId(u32);
+crate::display_impl_as_debug!(ConflictId);
+
+/// The part of a match an action is about. Referees are compared one subject at a time.
+#[derive(Eq, PartialEq, Copy, Clone, Debug, Hash)]
+pub enum Subject {
+    /// The cones and beacon on a junction.
+    Junction(AnyJunction),
+    Terminal(Alliance, Terminal),
+    /// The parking and cards of a robot.
+    Robot(MatchIndex),
+    /// The penalties an alliance committed.
+    Penalties(Alliance),
+}
+crate::display_impl_as_debug!(Subject);
+
+impl Subject {
+    /// The subject of an action, or None if it moves the match to the next phase.
+    pub fn of(action: MatchAction) -> Option<Self> {
+        match action {
+            MatchAction::Score { junction, .. } | MatchAction::Descore { junction } | MatchAction::Cap { junction, .. } => {
+                Some(Subject::Junction(junction))
+            }
+            MatchAction::AddTerminal { alliance, terminal } => Some(Subject::Terminal(alliance, terminal)),
+            MatchAction::Park { robot, .. } | MatchAction::Card { robot, .. } => Some(Subject::Robot(robot)),
+            MatchAction::Penalize { alliance, .. } => Some(Subject::Penalties(alliance)),
+            MatchAction::AdvancePhase => None,
+        }
+    }
+
+    /// The alliance the subject belongs to, or None for junctions, which both alliances share.
+    pub fn alliance(self) -> Option<Alliance> {
+        match self {
+            Subject::Junction(_) => None,
+            Subject::Terminal(alliance, _) | Subject::Penalties(alliance) => Some(alliance),
+            Subject::Robot(robot) => Some(robot.alliance()),
+        }
+    }
+}
+
+/// Referees who disagree about a subject in one phase, waiting for the head referee.
+#[derive(Eq, PartialEq, Clone, Debug, Hash)]
+pub struct Conflict {
+    pub id: ConflictId,
+    pub phase: MatchPhase,
+    pub subject: Subject,
+    /// What each referee watching the subject recorded about it, in order.
+    pub versions: Vec<(RefereeId, Vec<MatchAction>)>,
+}
+crate::display_impl_as_debug!(Conflict);
+
+/// Every way recording or reviewing an action can fail.
+#[derive(Eq, PartialEq, Copy, Clone, Debug, Hash)]
+pub enum ReconcileError {
+    /// The action cannot be taken in the referee's copy of the match,
+    /// or in the authoritative match when resolving a conflict.
+    Match(MatchError),
+    /// The action is for an alliance the referee is not watching.
+    NotWatching,
+    /// The action is not about the conflict's subject, or moves the match to the next phase,
+    /// which only [`Reconciler::end_phase`] does.
+    WrongSubject,
+    NoSuchReferee,
+    NoSuchConflict,
+    /// Conflicts from an earlier phase have to be resolved first.
+    EarlierConflicts,
+}
+crate::display_impl_as_debug!(ReconcileError);
+
+impl From<MatchError> for ReconcileError {
+    #[inline(always)]
+    fn from(value: MatchError) -> Self {
+        ReconcileError::Match(value)
+    }
+}
+
+#[derive(Debug, Clone)]
+struct Referee {
+    watching: Option<Alliance>,
+    view: AnyMatch,
+    // the actions of the current phase, with the alliance each one was for
+    recorded: Vec<(MatchAction, Alliance)>,
+}
+
+impl Referee {
+    #[inline]
+    fn watches(&self, alliance: Alliance) -> bool {
+        self.watching.is_none_or(|watching| watching == alliance)
+    }
+
+    fn actions_for(&self, subject: Subject, alliance: Option<Alliance>) -> Vec<MatchAction> {
+        self.recorded
+            .iter()
+            .filter(|&&(action, of)| Subject::of(action) == Some(subject) && alliance.is_none_or(|a| a == of))
+            .map(|&(action, _)| action)
+            .collect()
+    }
+}
+
+// a subject of a phase every referee has finished, with the actions they agreed on, if any
+#[derive(Debug, Clone)]
+struct Reviewed {
+    subject: Subject,
+    agreed: Option<Vec<MatchAction>>,
+    versions: Vec<(RefereeId, Vec<MatchAction>)>,
+}
+
+// a phase every referee has finished, which the authoritative match has not
+#[derive(Debug, Clone)]
+struct ClosedPhase {
+    phase: MatchPhase,
+    subjects: Vec<Reviewed>,
+}
+
+/// Merges the actions of several referees into one authoritative match.
+#[derive(Debug, Clone)]
+pub struct Reconciler {
+    authority: AnyMatch,
+    referees: Vec<Referee>,
+    // the phase the referees are recording
+    phase: MatchPhase,
+    // the authoritative match is partway through the first of these
+    closed: VecDeque<ClosedPhase>,
+    review: Vec<Conflict>,
+    next_conflict: u32,
+}
+
+impl Reconciler {
+    pub fn new(game: impl Into<AnyMatch>) -> Self {
+        let authority = game.into();
+        Self {
+            phase: authority.phase(),
+            authority,
+            referees: Vec::new(),
+            closed: VecDeque::new(),
+            review: Vec::new(),
+            next_conflict: 0,
+        }
+    }
+
+    /// Adds a referee who watches one alliance, or the whole field if `watching` is None.
+    /// Referees added partway through a match start from the authoritative match.
+    pub fn add_referee(&mut self, watching: Option<Alliance>) -> RefereeId {
+        let view = self.rebased(&[]);
+        self.referees.push(Referee { watching, view, recorded: Vec::new() });
+        RefereeId(self.referees.len() - 1)
+    }
+
+    /// The phase the referees are recording.
+    #[inline]
+    pub fn phase(&self) -> MatchPhase {
+        self.phase
+    }
+
+    /// The authoritative match. It stays in a phase until every conflict from that phase is resolved.
+    #[inline]
+    pub fn authority(&self) -> &AnyMatch {
+        &self.authority
+    }
+
+    /// The match as one referee has recorded it.
+    pub fn view_of(&self, referee: RefereeId) -> Option<&AnyMatch> {
+        self.referees.get(referee.0).map(|referee| &referee.view)
+    }
+
+    /// Records an action a referee saw. The action has to be possible in the referee's copy of the match.
+    pub fn record(&mut self, referee: RefereeId, action: MatchAction) -> Result<(), ReconcileError> {
+        let referee = self.referees.get_mut(referee.0).ok_or(ReconcileError::NoSuchReferee)?;
+        let alliance = match action {
+            MatchAction::Score { alliance, .. }
+            | MatchAction::AddTerminal { alliance, .. }
+            | MatchAction::Penalize { alliance, .. } => alliance,
+            MatchAction::Cap { robot, .. } | MatchAction::Park { robot, .. } | MatchAction::Card { robot, .. } => {
+                robot.alliance()
+            }
+            // the cone removed is the top cone in the referee's copy
+            MatchAction::Descore { junction } => referee.view.clone().descore(junction)?,
+            MatchAction::AdvancePhase => return Err(ReconcileError::WrongSubject),
+        };
+        if !referee.watches(alliance) {
+            return Err(ReconcileError::NotWatching);
+        }
+        referee.view.apply(action)?;
+        referee.recorded.push((action, alliance));
+        Ok(())
+    }
+
+    /// Ends the current phase for every referee, and applies what they agree on to the authoritative match.
+    /// Returns the phase the referees are now recording.
+    pub fn end_phase(&mut self) -> Result<MatchPhase, ReconcileError> {
+        if self.phase == MatchPhase::Finished {
+            return Err(MatchError::WrongPhase.into());
+        }
+        let mut subjects: Vec<Subject> = Vec::new();
+        for subject in self.referees.iter().flat_map(|referee| &referee.recorded).filter_map(|&(a, _)| Subject::of(a)) {
+            if !subjects.contains(&subject) {
+                subjects.push(subject);
+            }
+        }
+        let subjects = subjects
+            .into_iter()
+            .map(|subject| Reviewed { subject, agreed: self.merge(subject), versions: self.versions(subject) })
+            .collect::<Vec<_>>();
+        for reviewed in subjects.iter().filter(|reviewed| reviewed.agreed.is_none()) {
+            self.flag(self.phase, reviewed);
+        }
+        self.closed.push_back(ClosedPhase { phase: self.phase, subjects });
+        for referee in &mut self.referees {
+            referee.recorded.clear();
+            referee.view.advance_phase()?;
+        }
+        self.phase = match self.phase {
+            MatchPhase::Auto => MatchPhase::TeleOp,
+            MatchPhase::TeleOp => MatchPhase::EndGame,
+            _ => MatchPhase::Finished,
+        };
+        self.catch_up();
+        Ok(self.phase)
+    }
+
+    /// The conflicts waiting for review, oldest first.
+    #[inline]
+    pub fn conflicts(&self) -> &[Conflict] {
+        &self.review
+    }
+
+    /// Settles a conflict with the actions the head referee decided on, which may be one referee's version.
+    pub fn resolve(&mut self, id: ConflictId, actions: &[MatchAction]) -> Result<(), ReconcileError> {
+        let index = self.review.iter().position(|conflict| conflict.id == id).ok_or(ReconcileError::NoSuchConflict)?;
+        let conflict = &self.review[index];
+        if self.closed.front().map(|closed| closed.phase) != Some(conflict.phase) {
+            return Err(ReconcileError::EarlierConflicts);
+        }
+        if actions.iter().any(|&action| Subject::of(action) != Some(conflict.subject)) {
+            return Err(ReconcileError::WrongSubject);
+        }
+        Self::apply_all(&mut self.authority, actions)?;
+        self.review.remove(index);
+        self.catch_up();
+        Ok(())
+    }
+
+    /// The results of the match, once it has ended and every conflict is resolved.
+    #[inline]
+    pub fn results(&self) -> Option<MatchResult> {
+        self.authority.results()
+    }
+
+    // the actions every referee agrees on, or None if they disagree
+    fn merge(&self, subject: Subject) -> Option<Vec<MatchAction>> {
+        let mut sides = [Vec::new(), Vec::new()];
+        for (side, alliance) in sides.iter_mut().zip([Alliance::RED, Alliance::BLUE]) {
+            if subject.alliance().is_some_and(|of| of != alliance) {
+                continue;
+            }
+            let mut watchers = self
+                .referees
+                .iter()
+                .filter(|referee| referee.watches(alliance))
+                .map(|referee| referee.actions_for(subject, Some(alliance)));
+            if let Some(first) = watchers.next() {
+                if watchers.any(|other| other != first) {
+                    return None;
+                }
+                *side = first;
+            }
+        }
+        let [red, blue] = sides;
+        if red.is_empty() || blue.is_empty() {
+            return Some([red, blue].concat());
+        }
+        // both alliances played on the junction, so only referees watching the whole field know which cone is on top
+        let mut orders = self
+            .referees
+            .iter()
+            .filter(|referee| referee.watching.is_none())
+            .map(|referee| referee.actions_for(subject, None));
+        let first = orders.next()?;
+        orders.all(|other| other == first).then_some(first)
+    }
+
+    fn versions(&self, subject: Subject) -> Vec<(RefereeId, Vec<MatchAction>)> {
+        self.referees
+            .iter()
+            .enumerate()
+            .filter(|(_, referee)| subject.alliance().is_none_or(|alliance| referee.watches(alliance)))
+            .map(|(i, referee)| (RefereeId(i), referee.actions_for(subject, None)))
+            .collect()
+    }
+
+    fn flag(&mut self, phase: MatchPhase, reviewed: &Reviewed) {
+        self.review.push(Conflict {
+            id: ConflictId(self.next_conflict),
+            phase,
+            subject: reviewed.subject,
+            versions: reviewed.versions.clone(),
+        });
+        self.next_conflict += 1;
+    }
+
+    // applies every action or none of them
+    fn apply_all(game: &mut AnyMatch, actions: &[MatchAction]) -> Result<(), MatchError> {
+        // tried on a copy first, since a failed action can still change the match
+        let mut trial = game.clone();
+        for &action in actions {
+            trial.apply(action)?;
+        }
+        for &action in actions {
+            game.apply(action)?;
+        }
+        Ok(())
+    }
+
+    // the authoritative match, moved on to the phase the referees are recording, with a referee's actions from it
+    fn rebased(&self, recorded: &[(MatchAction, Alliance)]) -> AnyMatch {
+        let mut view = self.authority.clone();
+        while view.phase() < self.phase {
+            view.advance_phase().expect("the match is not finished");
+        }
+        // these all worked in the referee's old copy, but may not after the head referee overruled it
+        for &(action, _) in recorded {
+            let _ = view.apply(action);
+        }
+        view
+    }
+
+    // applies closed phases to the authoritative match until one has conflicts waiting,
+    // and starts every referee's copy again from it if it finished a phase
+    fn catch_up(&mut self) {
+        let before = self.authority.phase();
+        while let Some(closed) = self.closed.front_mut() {
+            let phase = closed.phase;
+            // agreed actions can still fail together, such as one robot capping two junctions
+            for reviewed in take(&mut closed.subjects) {
+                if let Some(agreed) = &reviewed.agreed {
+                    if Self::apply_all(&mut self.authority, agreed).is_err() {
+                        self.flag(phase, &reviewed);
+                    }
+                }
+            }
+            if self.review.iter().any(|conflict| conflict.phase == phase) {
+                break;
+            }
+            let _ = self.authority.advance_phase();
+            self.closed.pop_front();
+        }
+        if self.authority.phase() != before {
+            for i in 0..self.referees.len() {
+                self.referees[i].view = self.rebased(&self.referees[i].recorded);
+            }
+        }
+    }
+}
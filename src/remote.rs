@@ -4,15 +4,22 @@ use crate::sealed::Sealed;
 use crate::BeaconError::{BeaconPreviouslyScored, JunctionIsCapped};
 use crate::ConeRemovalError::{BeaconOnJunction, JunctionIsEmpty};
 use crate::MaybeInvalid::{Invalid, Valid};
-use crate::{AllianceInfo, InternalAllianceInfo};
+use crate::{AllianceInfo, InternalAllianceInfo, RobotStatus};
 use crate::{Auto, BeaconError, ConeRemovalError, FieldCoordinate, Match, MaybeInvalid};
 use crate::{BeaconScoredOutsideEndgame, EndGame, TeleOp};
-use nohash::IntMap;
-use std::mem::transmute;
-use std::num::NonZeroU8;
-use std::ops::Index;
+use crate::{FieldEvent, MatchEvent, Observable, SubscriptionId};
+use crate::dynamic::MatchPhase;
+use crate::events::Subscribers;
+use crate::junction_map::JunctionMap;
+use alloc::boxed::Box;
+use core::mem::transmute;
+use core::num::NonZeroU8;
+use core::ops::Index;
 use RedRemoteJunction::*;
 
+mod encoding;
+mod render;
+
 #[derive(Ord, PartialOrd, Eq, PartialEq, Copy, Clone, Debug, Hash)]
 #[repr(u8)]
 pub enum RemoteCircuitPattern {
@@ -100,7 +107,11 @@ pub enum RedRemoteJunction {
     Z1 = 0b010_000_00, Z2 = 0b010_001_01, Z3 = 0b010_010_00, Z4 = 0b010_011_01, Z5 = 0b010_100_00
 }
 
-crate::junction_impl!(RedRemoteJunction, 3, 5);
+crate::junction_impl!(RedRemoteJunction, 3, 5, &[
+    X1, X2, X3, X4, X5,
+    Y1, Y2, Y3, Y4, Y5,
+    Z1, Z2, Z3, Z4, Z5,
+]);
 
 #[derive(Ord, PartialOrd, Eq, PartialEq, Copy, Clone, Debug)]
 #[repr(u8)]
@@ -113,14 +124,24 @@ pub enum BlueRemoteJunction {
     X1 = 0b000_000_00, X2 = 0b000_001_01, X3 = 0b000_010_00, X4 = 0b000_011_01, X5 = 0b000_100_00
 }
 
-crate::junction_impl!(BlueRemoteJunction, 3, 5);
+crate::junction_impl!(BlueRemoteJunction, 3, 5, {
+    use BlueRemoteJunction::*;
+    &[
+        V1, V2, V3, V4, V5,
+        W1, W2, W3, W4, W5,
+        X1, X2, X3, X4, X5,
+    ]
+});
 
-// TODO nohash intmap, ahash intmap, or array?
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 struct InternalRemoteMatch {
     data: InternalAllianceInfo<RedRemoteJunction, 1>,
     circuit_pattern: RemoteCircuitPattern,
-    junctions: IntMap<RedRemoteJunction, NonZeroU8>,
+    junctions: JunctionMap<RedRemoteJunction, NonZeroU8>,
+    // whether the alliance scores its circuit, terminals included
+    circuit: bool,
+    // blue matches are stored as red, so blue subscribers translate every event
+    subscribers: Subscribers<RedRemoteJunction>,
 }
 
 impl InternalRemoteMatch {
@@ -128,12 +149,61 @@ impl InternalRemoteMatch {
         Self {
             data: InternalAllianceInfo::new([team]),
             circuit_pattern,
-            junctions: IntMap::default(),
+            junctions: JunctionMap::default(),
+            circuit: false,
+            subscribers: Subscribers::default(),
+        }
+    }
+
+    /// Whether the alliance possesses this junction, by a cone or its beacon.
+    fn possesses(&self, location: RedRemoteJunction) -> bool {
+        self.junctions.contains_key(&location) || self.data.beacon_placements[0] == Valid(location)
+    }
+
+    /// Whether the alliance possesses exactly the junctions in its circuit pattern, and has cones in both terminals.
+    fn scores_circuit(&self) -> bool {
+        let pattern = CIRCUIT_PATTERNS[self.circuit_pattern as usize];
+        let lone_beacon = match self.data.beacon_placements[0] {
+            Valid(junction) => !self.junctions.contains_key(&junction) as usize,
+            _ => 0,
+        };
+        self.data.terminal_amounts[0] != 0
+            && self.data.terminal_amounts[1] != 0
+            && self.junctions.len() + lone_beacon == pattern.len()
+            && pattern.iter().all(|&junction| self.possesses(junction))
+    }
+
+    /// Tells subscribers about an action, followed by what it changed about possession and the circuit.
+    /// `changed` is the junction the action touched, and whether the alliance possessed it beforehand.
+    fn publish(&mut self, action: MatchEvent<RedRemoteJunction>, changed: Option<(RedRemoteJunction, bool)>) {
+        self.subscribers.publish(action);
+        if let Some((junction, previous)) = changed {
+            let current = self.possesses(junction);
+            if previous == current {
+                // nothing else can change if possession did not
+                return;
+            }
+            let owner = |possessed: bool| possessed.then_some(Alliance::RED);
+            self.subscribers.publish(MatchEvent::Field(FieldEvent::OwnershipChanged {
+                junction,
+                previous: owner(previous),
+                current: owner(current),
+            }));
+        }
+        let circuit = self.scores_circuit();
+        if circuit != self.circuit {
+            self.circuit = circuit;
+            self.subscribers.publish(MatchEvent::Field(if circuit {
+                FieldEvent::CircuitFormed(Alliance::RED)
+            } else {
+                FieldEvent::CircuitBroken(Alliance::RED)
+            }));
         }
     }
 
     fn score(&mut self, location: RedRemoteJunction) -> bool {
         if self.data.beacon_placements[0] != Valid(location) {
+            let possessed = self.possesses(location);
             match self.junctions.get_mut(&location) {
                 Some(num) => match (*num).checked_add(1) {
                     Some(value) => {
@@ -145,6 +215,10 @@ impl InternalRemoteMatch {
                     self.junctions.insert(location, unsafe { NonZeroU8::new_unchecked(1) });
                 }
             }
+            self.publish(
+                MatchEvent::ConeScored { alliance: Alliance::RED, junction: location },
+                Some((location, possessed)),
+            );
             true
         } else {
             false
@@ -168,6 +242,7 @@ impl InternalRemoteMatch {
                             *num = nonzero;
                         }
                     }
+                    self.publish(MatchEvent::ConeRemoved { alliance, junction: location }, Some((location, true)));
                     Ok(alliance)
                 }
                 None => Err(JunctionIsEmpty),
@@ -181,6 +256,7 @@ impl InternalRemoteMatch {
         let amounts = &mut self.data.terminal_amounts;
         let near_terminal = terminal == Terminal::Near;
         amounts[near_terminal as usize] += 1;
+        self.publish(MatchEvent::TerminalScored { alliance: Alliance::RED, terminal }, None);
         near_terminal
     }
 
@@ -189,7 +265,12 @@ impl InternalRemoteMatch {
         match self.data.beacon_placements[0] {
             Valid(loc) if loc == location => Err(JunctionIsCapped),
             MaybeInvalid::None => {
+                let possessed = self.possesses(location);
                 self.data.beacon_placements[0] = Valid(location);
+                self.publish(
+                    MatchEvent::BeaconPlaced { robot: MatchIndex::RED_CAPTAIN, junction: location },
+                    Some((location, possessed)),
+                );
                 Ok(())
             }
             _ => Err(BeaconPreviouslyScored),
@@ -203,7 +284,7 @@ impl InternalRemoteMatch {
 }
 
 // TODO seeing if optimizing these two fields into one, along with the circuit pattern field, is worth it
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct RedRemoteAuto {
     data: InternalRemoteMatch,
     has_signal_sleeve: bool,
@@ -211,10 +292,10 @@ pub struct RedRemoteAuto {
 }
 
 #[repr(transparent)]
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct RedRemoteTeleOp(InternalRemoteMatch);
 #[repr(transparent)]
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct RedRemoteEndGame(InternalRemoteMatch);
 
 impl RedRemoteEndGame {
@@ -318,6 +399,18 @@ macro_rules! delegated_impl {
                 self.penalty(points)
             }
 
+            #[inline]
+            fn status_of(&self, robot: MatchIndex) -> RobotStatus {
+                $index_checker!(robot);
+                self.$( $d2. )?$delegate.data.statuses[0]
+            }
+
+            #[inline]
+            fn set_status(&mut self, robot: MatchIndex, status: RobotStatus) {
+                $index_checker!(robot);
+                self.$( $d2. )?$delegate.data.statuses[0] = status;
+            }
+
             fn alliance_of(&self, robot: FtcTeamID) -> Option<Alliance> {
                 if self.$( $d2. )?$delegate.data.teams[0] == robot {
                     Some(Alliance::$alliance)
@@ -415,6 +508,7 @@ impl Auto<RedRemoteJunction, 1, 0> for RedRemoteAuto {
             .sum();
         self.data.data
             .score_auto_parking_terminals([self.has_signal_sleeve], self.signal_zone);
+        self.data.subscribers.publish(MatchEvent::PhaseChanged(MatchPhase::TeleOp));
         unsafe { transmute(self.data) }
     }
 }
@@ -443,7 +537,8 @@ impl RemoteAuto<RedRemoteJunction, 1, 0> for RedRemoteAuto {
 impl TeleOp<RedRemoteJunction, 1, 0> for RedRemoteTeleOp {
     type EndGameType = RedRemoteEndGame;
 
-    fn into_end_game(self) -> Self::EndGameType {
+    fn into_end_game(mut self) -> Self::EndGameType {
+        self.0.subscribers.publish(MatchEvent::PhaseChanged(MatchPhase::EndGame));
         unsafe { transmute(self) }
     }
 }
@@ -467,6 +562,8 @@ impl EndGame<RedRemoteJunction, 1, 0> for RedRemoteEndGame {
                 auto_points: 0,
                 teleop_points: 0,
                 endgame_points: 0,
+                roster: None,
+                statuses: [],
             },
         )
     }
@@ -480,6 +577,7 @@ impl RemoteEndGame<RedRemoteJunction, 1, 0> for RedRemoteEndGame {
     }
 
     fn end_match(mut self) -> AllianceInfo<1> {
+        self.0.subscribers.publish(MatchEvent::PhaseChanged(MatchPhase::Finished));
         AllianceInfo {
             alliance: Alliance::RED,
             teams: self.0.data.teams,
@@ -525,22 +623,24 @@ impl RemoteEndGame<RedRemoteJunction, 1, 0> for RedRemoteEndGame {
                 points += (self.0.junctions.len() as u16 - valid_beacon_count) * 3;
                 points
             },
+            roster: self.0.data.roster,
+            statuses: self.0.data.statuses,
         }
     }
 }
 
 #[repr(transparent)]
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct BlueRemoteAuto {
     inner: RedRemoteAuto
 }
 #[repr(transparent)]
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct BlueRemoteTeleOp {
     inner: RedRemoteTeleOp
 }
 #[repr(transparent)]
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct BlueRemoteEndGame {
     inner: RedRemoteEndGame
 }
@@ -636,7 +736,7 @@ impl TeleOp<BlueRemoteJunction, 0, 1> for BlueRemoteTeleOp {
 
     #[inline(always)]
     fn into_end_game(self) -> Self::EndGameType {
-        unsafe { transmute(self) }
+        unsafe { transmute(self.inner.into_end_game()) }
     }
 }
 
@@ -658,6 +758,8 @@ impl EndGame<BlueRemoteJunction, 0, 1> for BlueRemoteEndGame {
                 auto_points: 0,
                 teleop_points: 0,
                 endgame_points: 0,
+                roster: None,
+                statuses: [],
             },
             <Self as RemoteEndGame<BlueRemoteJunction, 0, 1>>::end_match(self),
         )
@@ -679,3 +781,49 @@ impl RemoteEndGame<BlueRemoteJunction, 0, 1> for BlueRemoteEndGame {
         info
     }
 }
+
+macro_rules! red_observable_impl {
+    ($struc:ty, $delegate:tt) => {
+        impl Observable<RedRemoteJunction> for $struc {
+            fn subscribe_until(
+                &mut self,
+                subscriber: impl FnMut(&MatchEvent<RedRemoteJunction>) -> bool + Send + Sync + 'static,
+            ) -> SubscriptionId {
+                self.$delegate.subscribers.add(Box::new(subscriber))
+            }
+
+            #[inline]
+            fn unsubscribe(&mut self, id: SubscriptionId) -> bool {
+                self.$delegate.subscribers.remove(id)
+            }
+        }
+    };
+}
+
+red_observable_impl!(RedRemoteAuto, data);
+red_observable_impl!(RedRemoteTeleOp, 0);
+red_observable_impl!(RedRemoteEndGame, 0);
+
+macro_rules! blue_observable_impl {
+    ($struc:ty) => {
+        impl Observable<BlueRemoteJunction> for $struc {
+            fn subscribe_until(
+                &mut self,
+                mut subscriber: impl FnMut(&MatchEvent<BlueRemoteJunction>) -> bool + Send + Sync + 'static,
+            ) -> SubscriptionId {
+                self.inner.subscribe_until(move |event| {
+                    subscriber(&event.map(|junction| unsafe { transmute(junction) }, |_| Alliance::BLUE))
+                })
+            }
+
+            #[inline(always)]
+            fn unsubscribe(&mut self, id: SubscriptionId) -> bool {
+                self.inner.unsubscribe(id)
+            }
+        }
+    };
+}
+
+blue_observable_impl!(BlueRemoteAuto);
+blue_observable_impl!(BlueRemoteTeleOp);
+blue_observable_impl!(BlueRemoteEndGame);
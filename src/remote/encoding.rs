@@ -0,0 +1,102 @@
+//! The compact encoding of remote matches, as described in [`crate::encoding`].
+//! Blue matches are encoded exactly like red ones, other than their header.
+use super::*;
+use crate::dynamic::{FieldType, MatchPhase};
+use crate::encoding::{compact_impl, header, read_junctions, write_junctions, Reader};
+use alloc::vec::Vec;
+
+impl InternalRemoteMatch {
+    fn encode_into(&self, out: &mut Vec<u8>) {
+        self.data.encode_into(out);
+        out.push(self.circuit_pattern as u8);
+        write_junctions(out, &self.junctions, |out, cones| out.push(cones.get()));
+    }
+
+    fn decode_from(reader: &mut Reader) -> Option<Self> {
+        use RemoteCircuitPattern::*;
+        let data = InternalAllianceInfo::decode_from(reader)?;
+        let circuit_pattern =
+            reader.variant(&[Pattern1, Pattern2, Pattern3, Pattern4, Pattern5, Pattern6], |p| p as u8)?;
+        let mut remote = Self::new(data.teams[0], circuit_pattern);
+        remote.data = data;
+        remote.junctions = read_junctions(reader, |reader| match NonZeroU8::new(reader.byte()?) {
+            Some(cones) => Some(cones),
+            None => reader.invalid(),
+        })?;
+        remote.circuit = remote.scores_circuit();
+        Some(remote)
+    }
+}
+
+impl RedRemoteAuto {
+    fn encode_as(&self, field: FieldType, out: &mut Vec<u8>) {
+        out.push(header(field, MatchPhase::Auto));
+        self.data.encode_into(out);
+        out.extend([self.has_signal_sleeve as u8, self.signal_zone as u8]);
+    }
+
+    fn decode_as(field: FieldType, reader: &mut Reader) -> Option<Self> {
+        reader.header(field, MatchPhase::Auto)?;
+        Some(Self {
+            data: InternalRemoteMatch::decode_from(reader)?,
+            has_signal_sleeve: reader.flag()?,
+            signal_zone: reader.signal_zone()?,
+        })
+    }
+}
+
+macro_rules! red_encoding_impl {
+    ($struc:ident, $phase:ident) => {
+        impl $struc {
+            fn encode_as(&self, field: FieldType, out: &mut Vec<u8>) {
+                out.push(header(field, MatchPhase::$phase));
+                self.0.encode_into(out);
+            }
+
+            fn decode_as(field: FieldType, reader: &mut Reader) -> Option<Self> {
+                reader.header(field, MatchPhase::$phase)?;
+                InternalRemoteMatch::decode_from(reader).map($struc)
+            }
+        }
+    };
+}
+
+red_encoding_impl!(RedRemoteTeleOp, TeleOp);
+red_encoding_impl!(RedRemoteEndGame, EndGame);
+
+macro_rules! encoding_impl {
+    ($red:ident, $blue:ident) => {
+        impl $red {
+            pub(crate) fn encode_into(&self, out: &mut Vec<u8>) {
+                self.encode_as(FieldType::RedRemote, out);
+            }
+
+            pub(crate) fn decode_from(reader: &mut Reader) -> Option<Self> {
+                Self::decode_as(FieldType::RedRemote, reader)
+            }
+        }
+
+        impl $blue {
+            pub(crate) fn encode_into(&self, out: &mut Vec<u8>) {
+                self.inner.encode_as(FieldType::BlueRemote, out);
+            }
+
+            pub(crate) fn decode_from(reader: &mut Reader) -> Option<Self> {
+                $red::decode_as(FieldType::BlueRemote, reader).map(|inner| Self { inner })
+            }
+        }
+    };
+}
+
+encoding_impl!(RedRemoteAuto, BlueRemoteAuto);
+encoding_impl!(RedRemoteTeleOp, BlueRemoteTeleOp);
+encoding_impl!(RedRemoteEndGame, BlueRemoteEndGame);
+
+compact_impl!(
+    RedRemoteAuto,
+    RedRemoteTeleOp,
+    RedRemoteEndGame,
+    BlueRemoteAuto,
+    BlueRemoteTeleOp,
+    BlueRemoteEndGame
+);
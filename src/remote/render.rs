@@ -0,0 +1,78 @@
+//! Drawing remote matches, as described in [`crate::render`].
+//! Blue matches are stored as red, so they are drawn with blue names and colors.
+use super::*;
+use crate::dynamic::{FieldType, MatchPhase};
+use crate::render::{render_impl, Cell, Picture};
+use alloc::vec::Vec;
+use core::convert::identity;
+use core::mem::transmute;
+
+impl InternalRemoteMatch {
+    // junctions are drawn with the names of T, and found by the red junction `location` stores each one as
+    fn picture<T: FieldCoordinate>(
+        &self,
+        location: fn(T) -> RedRemoteJunction,
+        field: FieldType,
+        alliance: Alliance,
+        phase: MatchPhase,
+    ) -> Picture {
+        let pattern = CIRCUIT_PATTERNS[self.circuit_pattern as usize];
+        let cells = T::ALL
+            .iter()
+            .map(|&junction| {
+                let mut cell = Cell::new(junction);
+                let location = location(junction);
+                let cones = self.junctions.get(&location).map_or(0, |cones| cones.get());
+                cell.cones = alloc::vec![alliance; cones as usize];
+                if self.data.beacon_placements[0] == Valid(location) {
+                    cell.beacon = Some(MatchIndex::new(alliance, 0));
+                }
+                if self.circuit && pattern.contains(&location) {
+                    cell.circuit = Some(alliance);
+                }
+                cell
+            })
+            .collect();
+        let mut picture = Picture {
+            field,
+            phase,
+            columns: T::COLUMNS as usize,
+            cells,
+            terminals: Vec::new(),
+            parked: Vec::new(),
+        };
+        picture.add_alliance(alliance, &self.data);
+        picture
+    }
+}
+
+macro_rules! picture_impl {
+    ($red:ty, $blue:ty, $phase:ident, $($delegate:tt).+) => {
+        impl $red {
+            fn picture(&self) -> Picture {
+                self.$($delegate).+.picture(identity, FieldType::RedRemote, Alliance::RED, MatchPhase::$phase)
+            }
+        }
+
+        impl $blue {
+            fn picture(&self) -> Picture {
+                // blue junctions share their encoding with the red ones they are stored as
+                let location = |junction: BlueRemoteJunction| -> RedRemoteJunction { unsafe { transmute(junction) } };
+                self.inner.$($delegate).+.picture(location, FieldType::BlueRemote, Alliance::BLUE, MatchPhase::$phase)
+            }
+        }
+    };
+}
+
+picture_impl!(RedRemoteAuto, BlueRemoteAuto, Auto, data);
+picture_impl!(RedRemoteTeleOp, BlueRemoteTeleOp, TeleOp, 0);
+picture_impl!(RedRemoteEndGame, BlueRemoteEndGame, EndGame, 0);
+
+render_impl!(
+    RedRemoteAuto,
+    RedRemoteTeleOp,
+    RedRemoteEndGame,
+    BlueRemoteAuto,
+    BlueRemoteTeleOp,
+    BlueRemoteEndGame
+);
@@ -0,0 +1,286 @@
+//! Drawings of the field for match reports, logs, debugging and the stream overlay.
+//!
+//! Every phase type implements [`Render`], which draws the junction grid as plain text or as SVG.
+//! [`AnyMatch`](crate::dynamic::AnyMatch) can be drawn until it finishes, since finished matches only keep their results.
+//!
+//! Rows are drawn top to bottom in the order of their letters, and columns left to right.
+//! Both drawings show every junction's cones from the bottom up with the top cone emphasized, beacons labelled
+//! with the robot that scored them, the junctions on a scoring circuit, the cones in each terminal, and parked robots.
+//! In text, `r` and `b` are red and blue cones with the top cone in capitals, `@R0` is a beacon from red robot 0,
+//! and `*` marks a junction on a circuit.
+use crate::dynamic::{FieldType, MatchPhase};
+use crate::*;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::fmt::Write;
+
+/// Drawing a match's field.
+pub trait Render: sealed::Sealed {
+    /// The field as plain text, for terminals and logs.
+    fn to_ascii(&self) -> String;
+    /// The field as a standalone SVG image.
+    fn to_svg(&self) -> String;
+}
+
+macro_rules! render_impl {
+    ($($struc:ty),+) => {
+        $(
+            impl crate::render::Render for $struc {
+                fn to_ascii(&self) -> alloc::string::String {
+                    crate::render::ascii(&self.picture())
+                }
+
+                fn to_svg(&self) -> alloc::string::String {
+                    crate::render::svg(&self.picture())
+                }
+            }
+        )+
+    };
+}
+pub(crate) use render_impl;
+
+/// One junction, as it is drawn.
+pub(crate) struct Cell {
+    pub(crate) name: String,
+    pub(crate) points: u8,
+    /// From the bottom up.
+    pub(crate) cones: Vec<Alliance>,
+    pub(crate) beacon: Option<MatchIndex>,
+    /// The alliance whose circuit runs through this junction.
+    pub(crate) circuit: Option<Alliance>,
+}
+
+impl Cell {
+    pub(crate) fn new(junction: impl FieldCoordinate) -> Self {
+        Self { name: junction.to_string(), points: junction.points(), cones: Vec::new(), beacon: None, circuit: None }
+    }
+}
+
+/// Everything a drawing shows, gathered from any phase of any field.
+pub(crate) struct Picture {
+    pub(crate) field: FieldType,
+    pub(crate) phase: MatchPhase,
+    pub(crate) columns: usize,
+    /// In row-major order.
+    pub(crate) cells: Vec<Cell>,
+    /// The cones in each alliance's near and far terminals.
+    pub(crate) terminals: Vec<(Alliance, [u8; 2])>,
+    pub(crate) parked: Vec<(MatchIndex, ParkingLocation)>,
+}
+
+impl Picture {
+    /// Terminal amounts and parking locations of an alliance, which are stored the same way on every field.
+    pub(crate) fn add_alliance<T: FieldCoordinate, const N: usize>(
+        &mut self,
+        alliance: Alliance,
+        info: &InternalAllianceInfo<T, N>,
+    ) {
+        // terminal amounts are stored far terminal first
+        let [far, near] = info.terminal_amounts;
+        self.terminals.push((alliance, [near, far]));
+        self.parked.extend(
+            info.parking_locations
+                .iter()
+                .enumerate()
+                .filter_map(|(i, location)| location.map(|location| (MatchIndex::new(alliance, i as u8), location))),
+        );
+    }
+
+    fn rows(&self) -> impl Iterator<Item = &[Cell]> {
+        self.cells.chunks(self.columns)
+    }
+}
+
+// like R0 or B1, as commands name robots
+fn robot(robot: MatchIndex) -> String {
+    let alliance = if robot.alliance().is_blue() { 'B' } else { 'R' };
+    alloc::format!("{alliance}{}", robot.index())
+}
+
+fn alliance_name(alliance: Alliance) -> &'static str {
+    if alliance.is_blue() { "blue" } else { "red" }
+}
+
+// the most cones drawn on one junction, below which the count of hidden cones is given instead
+const SHOWN_CONES: usize = 6;
+
+fn ascii_cell(cell: &Cell) -> String {
+    let mut text = String::new();
+    let hidden = cell.cones.len().saturating_sub(SHOWN_CONES);
+    if hidden > 0 {
+        text += &alloc::format!("{hidden}+");
+    }
+    for (i, &cone) in cell.cones.iter().enumerate().skip(hidden) {
+        let letter = if cone.is_blue() { 'b' } else { 'r' };
+        text.push(if i + 1 == cell.cones.len() { letter.to_ascii_uppercase() } else { letter });
+    }
+    if let Some(beacon) = cell.beacon {
+        text += "@";
+        text += &robot(beacon);
+    }
+    if text.is_empty() {
+        text.push('.');
+    }
+    if cell.circuit.is_some() {
+        text.push('*');
+    }
+    text
+}
+
+fn write_ascii(picture: &Picture, out: &mut String) -> core::fmt::Result {
+    writeln!(out, "{} {}", picture.field, picture.phase)?;
+    let cells: Vec<String> = picture.cells.iter().map(ascii_cell).collect();
+    let width = cells.iter().map(String::len).max().unwrap_or(1).max(2);
+    let mut line = String::from(" ");
+    for column in 1..=picture.columns {
+        write!(line, "  {column:<width$}")?;
+    }
+    writeln!(out, "{}", line.trim_end())?;
+    for (row, cells) in picture.rows().zip(cells.chunks(picture.columns)) {
+        line.clear();
+        line.extend(row[0].name.chars().next());
+        for cell in cells {
+            write!(line, "  {cell:<width$}")?;
+        }
+        writeln!(out, "{}", line.trim_end())?;
+    }
+    for (alliance, [near, far]) in &picture.terminals {
+        writeln!(out, "{} terminals: near {near}, far {far}", alliance_name(*alliance))?;
+    }
+    for (index, location) in &picture.parked {
+        writeln!(out, "{} parked in {location}", robot(*index))?;
+    }
+    for alliance in [Alliance::RED, Alliance::BLUE] {
+        let path: Vec<&str> =
+            picture.cells.iter().filter(|cell| cell.circuit == Some(alliance)).map(|cell| cell.name.as_str()).collect();
+        if !path.is_empty() {
+            writeln!(out, "{} circuit: {}", alliance_name(alliance), path.join(" "))?;
+        }
+    }
+    Ok(())
+}
+
+pub(crate) fn ascii(picture: &Picture) -> String {
+    let mut out = String::new();
+    write_ascii(picture, &mut out).expect("writing to a string cannot fail");
+    out
+}
+
+// the distance between junctions, which is one tile
+const TILE: usize = 80;
+// the space around the field for labels
+const MARGIN: usize = 30;
+const LINE: usize = 20;
+
+fn color(alliance: Alliance) -> &'static str {
+    if alliance.is_blue() { "#1976d2" } else { "#d32f2f" }
+}
+
+fn write_svg(picture: &Picture, out: &mut String) -> core::fmt::Result {
+    let rows = picture.cells.len() / picture.columns;
+    let (field_width, field_height) = ((picture.columns + 1) * TILE, (rows + 1) * TILE);
+    let legend = 1 + picture.terminals.len() + picture.parked.len();
+    let (width, height) = (field_width + 2 * MARGIN, field_height + 2 * MARGIN + legend * LINE);
+    writeln!(
+        out,
+        r#"<svg xmlns="http://www.w3.org/2000/svg" width="{width}" height="{height}" viewBox="0 0 {width} {height}" font-family="sans-serif">"#
+    )?;
+    writeln!(out, r##"<rect x="{MARGIN}" y="{MARGIN}" width="{field_width}" height="{field_height}" fill="#cfcfcf" stroke="#333"/>"##)?;
+    // tile seams meet at the junctions
+    for column in 1..=picture.columns {
+        let x = MARGIN + column * TILE;
+        writeln!(out, r##"<line x1="{x}" y1="{MARGIN}" x2="{x}" y2="{}" stroke="#aaa"/>"##, MARGIN + field_height)?;
+        writeln!(out, r#"<text x="{x}" y="{}" font-size="14" text-anchor="middle">{column}</text>"#, MARGIN - 10)?;
+    }
+    for (row, cells) in picture.rows().enumerate() {
+        let y = MARGIN + (row + 1) * TILE;
+        writeln!(out, r##"<line x1="{MARGIN}" y1="{y}" x2="{}" y2="{y}" stroke="#aaa"/>"##, MARGIN + field_width)?;
+        let letter = cells[0].name.chars().next().unwrap_or(' ');
+        writeln!(out, r#"<text x="{}" y="{}" font-size="14" text-anchor="middle">{letter}</text>"#, MARGIN / 2, y + 5)?;
+    }
+
+    // terminals fill the corners, with red's near terminal at the bottom left like on a full field
+    for (alliance, counts) in &picture.terminals {
+        for (terminal, count) in [Terminal::Near, Terminal::Far].into_iter().zip(counts) {
+            let left = terminal == Terminal::Near;
+            let top = left == alliance.is_blue();
+            let (x, dx) = if left { (MARGIN, 1) } else { (MARGIN + field_width, -1) };
+            let (y, dy) = if top { (MARGIN, 1) } else { (MARGIN + field_height, -1) };
+            let (x, y, size) = (x as isize, y as isize, (TILE * 3 / 4) as isize);
+            writeln!(
+                out,
+                r#"<polygon points="{x},{y} {},{y} {x},{}" fill="{}" fill-opacity="0.4"/>"#,
+                x + dx * size,
+                y + dy * size,
+                color(*alliance),
+            )?;
+            writeln!(
+                out,
+                r#"<text x="{}" y="{}" font-size="14" text-anchor="middle">{count}</text>"#,
+                x + dx * size / 4,
+                y + dy * size / 4 + 5,
+            )?;
+        }
+    }
+
+    for (i, cell) in picture.cells.iter().enumerate() {
+        let (x, y) = (MARGIN + (i % picture.columns + 1) * TILE, MARGIN + (i / picture.columns + 1) * TILE);
+        let radius = 3 * cell.points as usize;
+        if let Some(alliance) = cell.circuit {
+            writeln!(out, r#"<circle cx="{x}" cy="{y}" r="{}" fill="none" stroke="{}" stroke-width="4"/>"#, radius + 6, color(alliance))?;
+        }
+        writeln!(out, r##"<circle cx="{x}" cy="{y}" r="{radius}" fill="#888" stroke="#333"/>"##)?;
+        // the stack stands to the right of the junction, bottom up, with its top cone outlined
+        let hidden = cell.cones.len().saturating_sub(SHOWN_CONES);
+        for (level, (i, &cone)) in cell.cones.iter().enumerate().skip(hidden).enumerate() {
+            let stroke = if i + 1 == cell.cones.len() { r##" stroke="#000" stroke-width="2""## } else { "" };
+            writeln!(
+                out,
+                r#"<rect x="{}" y="{}" width="10" height="5" fill="{}"{stroke}/>"#,
+                x + 20,
+                y + 12 - level * 6,
+                color(cone),
+            )?;
+        }
+        if hidden > 0 {
+            writeln!(out, r#"<text x="{}" y="{}" font-size="10">+{hidden}</text>"#, x + 20, y + 27)?;
+        }
+        if let Some(beacon) = cell.beacon {
+            writeln!(out, r##"<circle cx="{x}" cy="{y}" r="11" fill="{}" stroke="#fff" stroke-width="2"/>"##, color(beacon.alliance()))?;
+            writeln!(
+                out,
+                r##"<text x="{x}" y="{}" font-size="9" fill="#fff" text-anchor="middle">{}</text>"##,
+                y + 3,
+                robot(beacon)
+            )?;
+        }
+    }
+
+    let mut y = 2 * MARGIN + field_height;
+    writeln!(out, r#"<text x="{MARGIN}" y="{y}" font-size="14">{} {}</text>"#, picture.field, picture.phase)?;
+    for (alliance, [near, far]) in &picture.terminals {
+        y += LINE;
+        writeln!(
+            out,
+            r#"<text x="{MARGIN}" y="{y}" font-size="14" fill="{}">{} terminals: near {near}, far {far}</text>"#,
+            color(*alliance),
+            alliance_name(*alliance),
+        )?;
+    }
+    for (index, location) in &picture.parked {
+        y += LINE;
+        writeln!(
+            out,
+            r#"<text x="{MARGIN}" y="{y}" font-size="14" fill="{}">{} parked in {location}</text>"#,
+            color(index.alliance()),
+            robot(*index),
+        )?;
+    }
+    writeln!(out, "</svg>")
+}
+
+pub(crate) fn svg(picture: &Picture) -> String {
+    let mut out = String::new();
+    write_svg(picture, &mut out).expect("writing to a string cannot fail");
+    out
+}
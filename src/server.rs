@@ -0,0 +1,361 @@
+//! A scoring server, so several referee tablets can score the same match at once.
+//!
+//! Matches are created and scored with JSON over HTTP, and every change is pushed to WebSocket clients:
+//!
+//! - `POST /matches` creates a match and returns its state, including its `id`.
+//! - `GET /matches/{id}` returns the state of a match.
+//! - `POST /matches/{id}/actions` applies one action and returns the new state.
+//! - `GET /matches/{id}/live` upgrades to a WebSocket that receives the state now and after every action.
+//!
+//! Names are the ones used in Rust, ignoring case: alliances are `"red"` or `"blue"`, and junctions are names like
+//! `"W3"`. Robots are identified by their team number. A new traditional match looks like
+//! `{"field": "traditional", "red": [{"team": 1, "signal_sleeve": true}, ...], "blue": [...], "signal_zone": "middle"}`,
+//! and a remote match like
+//! `{"field": "red_remote", "team": 1, "signal_sleeve": true, "signal_zone": "left", "circuit_pattern": 1}`.
+//! Actions are tagged by `action`, such as `{"action": "score", "alliance": "red", "junction": "X3"}`.
+//! The other actions are `descore`, `add_terminal`, `cap`, `penalize`, `park`, `card`, and `advance_phase`,
+//! with the same arguments as [`AnyMatch`].
+//!
+//! Actions are applied one at a time, in the order they arrive. Bad input is rejected with `400`,
+//! unknown matches with `404`, and actions the match does not allow with `409` and the [`MatchError`].
+//! An action that the scoring engine cannot handle is rejected with `500`, and leaves the match as it was.
+//! The state's `score` is what each alliance would have if the match ended now, until it actually ends.
+use crate::dynamic::*;
+use crate::remote::*;
+use crate::traditional::*;
+use crate::*;
+use axum::extract::ws::{Message, WebSocket, WebSocketUpgrade};
+use axum::extract::{Path, State};
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Response};
+use axum::routing::{get, post};
+use axum::{Json, Router};
+use serde::Deserialize;
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::panic::{catch_unwind, AssertUnwindSafe};
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+use tokio::net::TcpListener;
+use tokio::sync::broadcast;
+
+// live clients that fall further behind than this skip ahead to the latest state
+const UPDATE_BACKLOG: usize = 64;
+
+struct HostedMatch {
+    game: AnyMatch,
+    updates: broadcast::Sender<Value>,
+}
+
+#[derive(Default)]
+struct Matches {
+    matches: HashMap<u32, HostedMatch>,
+    next_id: u32,
+}
+
+type Shared = Arc<Mutex<Matches>>;
+
+// a panic never leaves a match half changed, so the matches are still usable after one
+fn lock(shared: &Shared) -> MutexGuard<'_, Matches> {
+    shared.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+// a copy of a match, so its state can be found without holding the lock
+fn game(shared: &Shared, id: u32) -> Result<AnyMatch, ServerError> {
+    lock(shared).matches.get(&id).map(|hosted| hosted.game.clone()).ok_or(ServerError::NoSuchMatch(id))
+}
+
+/// The server's routes, with no matches yet.
+pub fn router() -> Router {
+    Router::new()
+        .route("/matches", post(create))
+        .route("/matches/{id}", get(state))
+        .route("/matches/{id}/actions", post(act))
+        .route("/matches/{id}/live", get(live))
+        .with_state(Shared::default())
+}
+
+/// Serves [`router`] on `listener` until the process exits.
+pub async fn serve(listener: TcpListener) -> std::io::Result<()> {
+    axum::serve(listener, router()).await
+}
+
+#[derive(Debug)]
+enum ServerError {
+    BadRequest(String),
+    NoSuchMatch(u32),
+    Match(MatchError),
+    Panic,
+}
+
+impl IntoResponse for ServerError {
+    fn into_response(self) -> Response {
+        let (status, message) = match self {
+            ServerError::BadRequest(message) => (StatusCode::BAD_REQUEST, message),
+            ServerError::NoSuchMatch(id) => (StatusCode::NOT_FOUND, format!("There is no match {id}.")),
+            ServerError::Match(error) => (StatusCode::CONFLICT, error.to_string()),
+            ServerError::Panic => (StatusCode::INTERNAL_SERVER_ERROR, "The action could not be scored.".into()),
+        };
+        (status, Json(json!({ "error": message }))).into_response()
+    }
+}
+
+impl From<MatchError> for ServerError {
+    #[inline(always)]
+    fn from(value: MatchError) -> Self {
+        ServerError::Match(value)
+    }
+}
+
+// finds the value whose name matches, ignoring case
+fn named<T: Display + Copy>(kind: &str, all: &[T], name: &str) -> Result<T, ServerError> {
+    all.iter()
+        .copied()
+        .find(|value| value.to_string().eq_ignore_ascii_case(name))
+        .ok_or_else(|| ServerError::BadRequest(format!("{name} is not a valid {kind}.")))
+}
+
+fn alliance(name: &str) -> Result<Alliance, ServerError> {
+    named("alliance", &[Alliance::RED, Alliance::BLUE], name)
+}
+
+fn signal_zone(name: &str) -> Result<SignalZone, ServerError> {
+    named("signal zone", &[SignalZone::Left, SignalZone::Middle, SignalZone::Right], name)
+}
+
+fn junction(game: &AnyMatch, name: &str) -> Result<AnyJunction, ServerError> {
+    match game.field_type() {
+        FieldType::Traditional => named("junction", TraditionalJunction::ALL, name).map(AnyJunction::from),
+        FieldType::RedRemote => named("junction", RedRemoteJunction::ALL, name).map(AnyJunction::from),
+        FieldType::BlueRemote => named("junction", BlueRemoteJunction::ALL, name).map(AnyJunction::from),
+    }
+}
+
+fn robot(game: &AnyMatch, team: i32) -> Result<MatchIndex, ServerError> {
+    game.index_of(FtcTeamID(team)).ok_or(ServerError::Match(MatchError::NotInMatch))
+}
+
+#[derive(Deserialize)]
+struct Robot {
+    team: i32,
+    signal_sleeve: bool,
+}
+
+#[derive(Deserialize)]
+#[serde(tag = "field", rename_all = "snake_case")]
+enum NewMatch {
+    Traditional {
+        red: [Robot; 2],
+        blue: [Robot; 2],
+        signal_zone: String,
+    },
+    RedRemote {
+        team: i32,
+        signal_sleeve: bool,
+        signal_zone: String,
+        circuit_pattern: u8,
+    },
+    BlueRemote {
+        team: i32,
+        signal_sleeve: bool,
+        signal_zone: String,
+        circuit_pattern: u8,
+    },
+}
+
+#[derive(Deserialize)]
+#[serde(tag = "action", rename_all = "snake_case")]
+enum Action {
+    Score { alliance: String, junction: String },
+    Descore { junction: String },
+    AddTerminal { alliance: String, terminal: String },
+    Cap { team: i32, junction: String },
+    Penalize { alliance: String, points: u8 },
+    Park { team: i32, location: String },
+    Card { team: i32, card: String },
+    AdvancePhase,
+}
+
+impl NewMatch {
+    fn build(self) -> Result<AnyMatch, ServerError> {
+        use RemoteCircuitPattern::*;
+        let pattern = |number: u8| {
+            [Pattern1, Pattern2, Pattern3, Pattern4, Pattern5, Pattern6]
+                .get(number.wrapping_sub(1) as usize)
+                .copied()
+                .ok_or_else(|| ServerError::BadRequest(format!("{number} is not a valid circuit pattern.")))
+        };
+        Ok(match self {
+            NewMatch::Traditional { red, blue, signal_zone: zone } => {
+                let robots = |[first, second]: [Robot; 2]| {
+                    [(FtcTeamID(first.team), first.signal_sleeve), (FtcTeamID(second.team), second.signal_sleeve)]
+                };
+                TraditionalAuto::try_from_teams(robots(red), robots(blue), signal_zone(&zone)?)
+                    .ok_or_else(|| ServerError::BadRequest("A team cannot be in a match more than once.".into()))?
+                    .into()
+            }
+            NewMatch::RedRemote { team, signal_sleeve, signal_zone: zone, circuit_pattern } => {
+                RedRemoteAuto::with_team(FtcTeamID(team), signal_sleeve, signal_zone(&zone)?, pattern(circuit_pattern)?)
+                    .into()
+            }
+            NewMatch::BlueRemote { team, signal_sleeve, signal_zone: zone, circuit_pattern } => {
+                BlueRemoteAuto::with_team(FtcTeamID(team), signal_sleeve, signal_zone(&zone)?, pattern(circuit_pattern)?)
+                    .into()
+            }
+        })
+    }
+}
+
+impl Action {
+    fn parse(self, game: &AnyMatch) -> Result<MatchAction, ServerError> {
+        Ok(match self {
+            Action::Score { alliance: a, junction: j } => {
+                MatchAction::Score { alliance: alliance(&a)?, junction: junction(game, &j)? }
+            }
+            Action::Descore { junction: j } => MatchAction::Descore { junction: junction(game, &j)? },
+            Action::AddTerminal { alliance: a, terminal } => MatchAction::AddTerminal {
+                alliance: alliance(&a)?,
+                terminal: named("terminal", &[Terminal::Near, Terminal::Far], &terminal)?,
+            },
+            Action::Cap { team, junction: j } => {
+                MatchAction::Cap { robot: robot(game, team)?, junction: junction(game, &j)? }
+            }
+            Action::Penalize { alliance: a, points } => MatchAction::Penalize { alliance: alliance(&a)?, points },
+            Action::Park { team, location } => {
+                use ParkingLocation::*;
+                let location = named(
+                    "parking location",
+                    &[LeftSignalZone, MiddleSignalZone, RightSignalZone, NearTerminal, FarTerminal, Substation],
+                    &location,
+                )?;
+                MatchAction::Park { robot: robot(game, team)?, location }
+            }
+            Action::Card { team, card } => MatchAction::Card {
+                robot: robot(game, team)?,
+                card: named("card", &[Card::Yellow, Card::Red], &card)?,
+            },
+            Action::AdvancePhase => MatchAction::AdvancePhase,
+        })
+    }
+}
+
+// an alliance's score, including penalty points committed by the opponent
+fn score_json<const N: usize>(info: &AllianceInfo<N>, opponent_penalty_points: u16) -> Value {
+    json!({
+        "auto_points": info.auto_points,
+        "teleop_points": info.teleop_points,
+        "endgame_points": info.endgame_points,
+        "penalty_points": info.penalty_points,
+        "total": info.auto_points + info.teleop_points + info.endgame_points + opponent_penalty_points,
+    })
+}
+
+fn state_json(id: u32, game: &AnyMatch) -> Value {
+    // the score if the match ended now, found by ending a copy of it
+    let mut ended = game.clone();
+    while ended.advance_phase().is_ok() {}
+    let score = match ended.results() {
+        Some(MatchResult::Traditional(red, blue)) => json!({
+            "red": score_json(&red, blue.penalty_points),
+            "blue": score_json(&blue, red.penalty_points),
+        }),
+        Some(MatchResult::Remote(info)) => json!({ info.alliance.to_string().to_lowercase(): score_json(&info, 0) }),
+        None => unreachable!("A match always finishes after enough phases."),
+    };
+    let teams = |alliance| game.teams(alliance).iter().map(|team| team.0).collect::<Vec<_>>();
+    json!({
+        "id": id,
+        "field_type": game.field_type().to_string(),
+        "phase": game.phase().to_string(),
+        "teams": { "red": teams(Alliance::RED), "blue": teams(Alliance::BLUE) },
+        "score": score,
+    })
+}
+
+fn parse<T: serde::de::DeserializeOwned>(body: &str) -> Result<T, ServerError> {
+    serde_json::from_str(body).map_err(|error| ServerError::BadRequest(error.to_string()))
+}
+
+async fn create(State(shared): State<Shared>, body: String) -> Result<Json<Value>, ServerError> {
+    let game = parse::<NewMatch>(&body)?.build()?;
+    let id = {
+        let mut matches = lock(&shared);
+        let id = matches.next_id;
+        matches.next_id += 1;
+        matches.matches.insert(id, HostedMatch { game: game.clone(), updates: broadcast::channel(UPDATE_BACKLOG).0 });
+        id
+    };
+    Ok(Json(state_json(id, &game)))
+}
+
+async fn state(State(shared): State<Shared>, Path(id): Path<u32>) -> Result<Json<Value>, ServerError> {
+    Ok(Json(state_json(id, &game(&shared, id)?)))
+}
+
+async fn act(State(shared): State<Shared>, Path(id): Path<u32>, body: String) -> Result<Json<Value>, ServerError> {
+    let action = parse::<Action>(&body)?;
+    // the lock is what puts concurrent actions in order
+    let mut matches = lock(&shared);
+    let hosted = matches.matches.get_mut(&id).ok_or(ServerError::NoSuchMatch(id))?;
+    let action = action.parse(&hosted.game)?;
+    // the action is applied to a copy, which only replaces the match if neither it nor its state panics.
+    // the state is found under the lock too, so updates go out in the order the actions were applied.
+    let mut game = hosted.game.clone();
+    let (result, state) = catch_unwind(AssertUnwindSafe(|| {
+        let result = game.apply(action);
+        (result, state_json(id, &game))
+    }))
+    .map_err(|_| ServerError::Panic)?;
+    hosted.game = game;
+    // some failed actions still change the match, like a beacon scored outside end game
+    let result = result.map_err(ServerError::from);
+    // nobody listening is not an error
+    let _ = hosted.updates.send(state.clone());
+    result.map(|()| Json(state))
+}
+
+async fn live(
+    State(shared): State<Shared>,
+    Path(id): Path<u32>,
+    upgrade: WebSocketUpgrade,
+) -> Result<Response, ServerError> {
+    // subscribe before reading the state, so no update can be missed in between
+    let (updates, game) = {
+        let matches = lock(&shared);
+        let hosted = matches.matches.get(&id).ok_or(ServerError::NoSuchMatch(id))?;
+        (hosted.updates.subscribe(), hosted.game.clone())
+    };
+    let current = state_json(id, &game);
+    Ok(upgrade.on_upgrade(move |socket| push_updates(socket, shared, id, updates, current)))
+}
+
+async fn push_updates(
+    mut socket: WebSocket,
+    shared: Shared,
+    id: u32,
+    mut updates: broadcast::Receiver<Value>,
+    mut state: Value,
+) {
+    loop {
+        if socket.send(Message::text(state.to_string())).await.is_err() {
+            return;
+        }
+        state = loop {
+            tokio::select! {
+                update = updates.recv() => match update {
+                    Ok(update) => break update,
+                    Err(broadcast::error::RecvError::Lagged(_)) => match game(&shared, id) {
+                        Ok(game) => break state_json(id, &game),
+                        Err(_) => return,
+                    },
+                    Err(broadcast::error::RecvError::Closed) => return,
+                },
+                // clients only listen, so the socket only ends or fails
+                message = socket.recv() => match message {
+                    Some(Ok(Message::Close(_))) | Some(Err(_)) | None => return,
+                    // anything else, like a ping, is ignored until the next update
+                    Some(Ok(_)) => {}
+                },
+            }
+        };
+    }
+}
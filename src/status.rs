@@ -0,0 +1,145 @@
+//! The module for the status of robots in a match, and its consequences at an event.
+//! [`RobotStatus`] is recorded per robot in a single match, while [`CardTracker`] carries cards between matches
+//! and turns match results into ranking results.
+use crate::{Alliance, AllianceInfo, FtcTeamID};
+use alloc::collections::BTreeSet;
+use alloc::vec::Vec;
+use core::cmp::Ordering;
+
+#[derive(Ord, PartialOrd, Eq, PartialEq, Copy, Clone, Debug, Hash)]
+#[repr(u8)]
+pub enum Card {
+    Yellow,
+    Red,
+}
+crate::display_impl_as_debug!(Card);
+
+/// The status of a single robot in a single match.
+#[derive(Eq, PartialEq, Copy, Clone, Debug, Hash, Default)]
+pub struct RobotStatus {
+    /// The robot did not report to the field for the match.
+    pub no_show: bool,
+    /// The robot was disabled during the match. This has no effect on scoring.
+    pub disabled: bool,
+    /// The most serious card the team received in this match.
+    pub card: Option<Card>,
+    /// The team was disqualified without receiving a red card.
+    pub disqualified: bool,
+}
+
+impl RobotStatus {
+    #[inline]
+    pub fn is_disqualified(&self) -> bool {
+        self.disqualified || self.card == Some(Card::Red)
+    }
+
+    /// Gives the robot a card. A second yellow card in the same match becomes a red card.
+    pub fn give_card(&mut self, card: Card) {
+        self.card = match (self.card, card) {
+            (Some(Card::Yellow), Card::Yellow) => Some(Card::Red),
+            (previous, card) => previous.max(Some(card)),
+        };
+    }
+}
+
+/// One team's result from one match, after cards and no-shows are applied.
+#[derive(Eq, PartialEq, Copy, Clone, Debug, Hash)]
+pub struct TeamResult {
+    pub team: FtcTeamID,
+    pub alliance: Alliance,
+    /// The card the team ends the match with, after any yellow card from an earlier match escalates.
+    pub card: Option<Card>,
+    pub disqualified: bool,
+    /// 2 for a win and 1 for a tie. Disqualified and no-show teams receive none.
+    pub ranking_points: u8,
+    /// The alliance's score as counted towards this team's ranking. Disqualified teams receive none.
+    pub ranking_score: u16,
+}
+
+/// Tracks cards across the matches of an event.
+/// A team with a yellow card that receives another yellow card in a later match receives a red card instead.
+#[derive(Debug, Clone, Default)]
+pub struct CardTracker {
+    yellow_carded: BTreeSet<FtcTeamID>,
+}
+
+// an alliance's score, including penalty points committed by the opponent
+fn score<const N: usize, const M: usize>(info: &AllianceInfo<N>, opponent: &AllianceInfo<M>) -> u16 {
+    info.auto_points + info.teleop_points + info.endgame_points + opponent.penalty_points
+}
+
+impl CardTracker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Whether a team carries a yellow card into its next match.
+    #[inline]
+    pub fn has_yellow_card(&self, team: FtcTeamID) -> bool {
+        self.yellow_carded.contains(&team)
+    }
+
+    /// Records a finished match, returning each team's result in the order red then blue.
+    /// In a playoff match, a disqualification applies to the whole alliance.
+    pub fn record<const R: usize, const B: usize>(
+        &mut self,
+        red: &AllianceInfo<R>,
+        blue: &AllianceInfo<B>,
+        playoff: bool,
+    ) -> Vec<TeamResult> {
+        let scores = [score(red, blue), score(blue, red)];
+        let mut results = Vec::new();
+        for (teams, statuses, alliance) in [
+            (&red.teams[..], &red.statuses[..], Alliance::RED),
+            (&blue.teams[..], &blue.statuses[..], Alliance::BLUE),
+        ] {
+            for (&team, status) in teams.iter().zip(statuses) {
+                let card = match status.card {
+                    Some(Card::Yellow) if self.has_yellow_card(team) => Some(Card::Red),
+                    card => card,
+                };
+                match card {
+                    Some(Card::Yellow) => {
+                        self.yellow_carded.insert(team);
+                    }
+                    // a red card replaces the yellow card it escalated from
+                    Some(Card::Red) => {
+                        self.yellow_carded.remove(&team);
+                    }
+                    None => {}
+                }
+                results.push(TeamResult {
+                    team,
+                    alliance,
+                    card,
+                    disqualified: status.disqualified || card == Some(Card::Red),
+                    ranking_points: 0,
+                    ranking_score: 0,
+                });
+            }
+        }
+        if playoff {
+            for alliance in [Alliance::RED, Alliance::BLUE] {
+                if results.iter().any(|r| r.alliance == alliance && r.disqualified) {
+                    results.iter_mut().filter(|r| r.alliance == alliance).for_each(|r| r.disqualified = true);
+                }
+            }
+        }
+        let statuses = red.statuses.iter().chain(&blue.statuses);
+        for (result, status) in results.iter_mut().zip(statuses) {
+            if result.disqualified {
+                continue;
+            }
+            let (ours, theirs) = (scores[result.alliance as usize], scores[result.alliance.opponent() as usize]);
+            result.ranking_score = ours;
+            if !status.no_show {
+                result.ranking_points = match ours.cmp(&theirs) {
+                    Ordering::Greater => 2,
+                    Ordering::Equal => 1,
+                    Ordering::Less => 0,
+                };
+            }
+        }
+        results
+    }
+}
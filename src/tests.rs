@@ -1,9 +1,10 @@
 use crate::traditional::TraditionalAuto;
-use crate::traditional::TraditionalJunction::{V1, V4, W2, W3, X2, Y1};
-use crate::{Alliance, Auto, EndGame, FtcTeamID, Match, SignalZone, TeleOp};
+use crate::traditional::TraditionalJunction::{V1, V4, W2, W3, X2, Y1, Y2};
+use crate::{Alliance, Auto, EndGame, FtcTeamID, Match, MatchIndex, SignalZone, TeleOp};
 use crate::locations::Terminal;
 use crate::remote::{RedRemoteAuto, RemoteAuto, RemoteCircuitPattern, RemoteEndGame, RemoteMatch};
 use crate::remote::RedRemoteJunction::{Y5, Z1, Z2, Z3, Z4, Z5};
+use crate::traditional::TraditionalJunction;
 
 #[test]
 fn test() {
@@ -68,4 +69,1289 @@ fn circuit_test() {
     endgame.score_for(Alliance::RED, W3);
     endgame.score_for(Alliance::RED, V4);
     dbg!(endgame.end_match());
-}
\ No newline at end of file
+}
+#[test]
+fn possession_tracking_test() {
+    use crate::FieldEvent::*;
+    let mut endgame = TraditionalAuto::new(
+        [true, true],
+        [true, true],
+        SignalZone::Middle
+    ).into_teleop().into_end_game();
+    endgame.add_terminal_for(Alliance::RED, Terminal::Near);
+    endgame.add_terminal_for(Alliance::RED, Terminal::Far);
+    for junction in [Y1, X2, W3] {
+        endgame.score_for(Alliance::RED, junction);
+    }
+    assert!(!endgame.has_circuit(Alliance::RED));
+    endgame.score_for(Alliance::RED, V4);
+    assert!(endgame.has_circuit(Alliance::RED));
+    assert_eq!(endgame.events().collect::<Vec<_>>(), [
+        OwnershipChanged { junction: V4, previous: None, current: Some(Alliance::RED) },
+        CircuitFormed(Alliance::RED),
+    ]);
+
+    // topping a junction with the same alliance changes nothing
+    endgame.score_for(Alliance::RED, X2);
+    assert_eq!(endgame.events().count(), 0);
+
+    endgame.score_for(Alliance::BLUE, X2);
+    assert_eq!(endgame.owner_of(X2), Some(Alliance::BLUE));
+    assert_eq!(endgame.events().collect::<Vec<_>>(), [
+        OwnershipChanged { junction: X2, previous: Some(Alliance::RED), current: Some(Alliance::BLUE) },
+        CircuitBroken(Alliance::RED),
+    ]);
+
+    // a beacon takes the junction back, and the circuit with it
+    endgame.cap_for(MatchIndex::RED_CAPTAIN, X2).unwrap();
+    assert!(endgame.has_circuit(Alliance::RED));
+    assert_eq!(endgame.descore(X2), Err(crate::ConeRemovalError::BeaconOnJunction));
+    assert_eq!(endgame.events().count(), 0);
+    assert_eq!(endgame.possessions(Alliance::RED).collect::<Vec<_>>(), [V4, W3, X2, Y1]);
+    assert_eq!(endgame.possessions(Alliance::BLUE).count(), 0);
+}
+
+#[test]
+fn observer_test() {
+    use crate::dynamic::MatchPhase;
+    use crate::remote::{BlueRemoteAuto, BlueRemoteJunction as Blue};
+    use crate::{FieldEvent::*, MatchEvent::*, Observable};
+    use std::sync::{Arc, Mutex};
+
+    let mut auto = TraditionalAuto::new([true, true], [true, true], SignalZone::Middle);
+    let seen = Arc::new(Mutex::new(vec![]));
+    let log = seen.clone();
+    let id = auto.subscribe(move |event| log.lock().unwrap().push(*event));
+    let events = auto.event_channel();
+    auto.score_for(Alliance::RED, Y2);
+    // forks never notify
+    auto.fork().score_for(Alliance::BLUE, Y2);
+    let mut teleop = auto.into_teleop();
+    assert!(teleop.unsubscribe(id));
+    assert!(!teleop.unsubscribe(id));
+    teleop.score_for(Alliance::BLUE, Y2);
+    assert_eq!(*seen.lock().unwrap(), [
+        ConeScored { alliance: Alliance::RED, junction: Y2 },
+        Field(OwnershipChanged { junction: Y2, previous: None, current: Some(Alliance::RED) }),
+        PhaseChanged(MatchPhase::TeleOp),
+    ]);
+    assert_eq!(events.try_iter().last(), Some(Field(OwnershipChanged {
+        junction: Y2,
+        previous: Some(Alliance::RED),
+        current: Some(Alliance::BLUE),
+    })));
+    // a dropped receiver unsubscribes itself
+    drop(events);
+    EndGame::end_match(teleop.into_end_game());
+
+    // blue remote matches report blue junctions
+    let mut blue = BlueRemoteAuto::new(true, SignalZone::Middle, RemoteCircuitPattern::Pattern1);
+    let events = blue.event_channel();
+    blue.add_terminal(Terminal::Near);
+    blue.add_terminal(Terminal::Far);
+    for junction in [Blue::V2, Blue::V3, Blue::V4, Blue::V5] {
+        blue.score(junction);
+    }
+    blue.into_teleop().score(Blue::W5);
+    assert_eq!(events.try_iter().skip(10).collect::<Vec<_>>(), [
+        PhaseChanged(MatchPhase::TeleOp),
+        ConeScored { alliance: Alliance::BLUE, junction: Blue::W5 },
+        Field(OwnershipChanged { junction: Blue::W5, previous: None, current: Some(Alliance::BLUE) }),
+        Field(CircuitFormed(Alliance::BLUE)),
+    ]);
+}
+
+#[test]
+fn circuit_completion_test() {
+    let mut endgame = TraditionalAuto::new(
+        [true, true],
+        [true, true],
+        SignalZone::Middle
+    ).into_teleop().into_end_game();
+    endgame.add_terminal_for(Alliance::RED, Terminal::Near);
+    endgame.score_for(Alliance::RED, Y1);
+    endgame.score_for(Alliance::RED, X2);
+    endgame.score_for(Alliance::RED, W3);
+    let completion = endgame.circuit_completion(Alliance::RED).unwrap();
+    assert_eq!(completion.junctions.len(), 1);
+    assert_eq!(completion.terminals, vec![Terminal::Far]);
+    // an opponent beacon forces a detour
+    endgame.cap_for(MatchIndex::BLUE_CAPTAIN, V4).unwrap();
+    let completion = endgame.circuit_completion(Alliance::RED).unwrap();
+    assert_eq!(completion.junctions.len(), 2);
+    assert!(!completion.junctions.contains(&V4));
+}
+
+#[test]
+fn circuit_cut_test() {
+    let mut endgame = TraditionalAuto::new(
+        [true, true],
+        [true, true],
+        SignalZone::Middle
+    ).into_teleop().into_end_game();
+    assert_eq!(endgame.circuit_cut(Alliance::RED), Some(vec![]));
+    endgame.score_for(Alliance::RED, Y1);
+    endgame.score_for(Alliance::RED, X2);
+    endgame.score_for(Alliance::RED, W3);
+    endgame.score_for(Alliance::RED, V4);
+    endgame.score_for(Alliance::RED, TraditionalJunction::Z2);
+    endgame.score_for(Alliance::RED, Y2);
+    endgame.cap_for(MatchIndex::RED_CAPTAIN, X2).unwrap();
+    endgame.cap_for(MatchIndex::RED_FIRST_PICK, W3).unwrap();
+    assert_eq!(endgame.circuit_cut(Alliance::RED), Some(vec![V4]));
+}
+
+#[test]
+fn single_cone_possession_test() {
+    // the first cone of a stack is its top cone until another is added
+    let mut endgame = TraditionalAuto::new(
+        [true, true],
+        [true, true],
+        SignalZone::Middle
+    ).into_teleop().into_end_game();
+    endgame.score_for(Alliance::BLUE, W3);
+    endgame.score_for(Alliance::RED, W2);
+    endgame.score_for(Alliance::BLUE, W2);
+    endgame.descore(W2).unwrap();
+    let (red, blue) = endgame.end_match();
+    assert_eq!((red.endgame_points, blue.endgame_points), (3, 3));
+}
+
+#[test]
+fn beacon_possession_test() {
+    // a beacon takes its junction from whoever owns the top cone, whichever alliance is scored first
+    let mut endgame = TraditionalAuto::new(
+        [true, true],
+        [true, true],
+        SignalZone::Middle
+    ).into_teleop().into_end_game();
+    endgame.score_for(Alliance::RED, W3);
+    endgame.cap_for(MatchIndex::BLUE_CAPTAIN, W3).unwrap();
+    endgame.score_for(Alliance::BLUE, X2);
+    endgame.cap_for(MatchIndex::RED_CAPTAIN, X2).unwrap();
+    let (red, blue) = endgame.end_match();
+    assert_eq!((red.endgame_points, blue.endgame_points), (10, 10));
+}
+
+#[test]
+fn beacon_recommendation_test() {
+    let mut endgame = TraditionalAuto::new(
+        [true, true],
+        [true, true],
+        SignalZone::Middle
+    ).into_teleop().into_end_game();
+    endgame.add_terminal_for(Alliance::RED, Terminal::Near);
+    endgame.add_terminal_for(Alliance::RED, Terminal::Far);
+    endgame.score_for(Alliance::RED, Y1);
+    endgame.score_for(Alliance::RED, X2);
+    endgame.score_for(Alliance::RED, W3);
+    endgame.score_for(Alliance::BLUE, V4);
+    let recommendations = endgame.beacon_recommendations(Alliance::RED);
+    let single = recommendations.iter()
+        .find(|r| r.placements == vec![(MatchIndex::RED_CAPTAIN, V4)])
+        .unwrap();
+    assert_eq!((single.gain, single.margin), (30, 33));
+    assert!(single.creates_circuit);
+    let best = &recommendations[0];
+    assert_eq!(best.placements.len(), 2);
+    assert!(best.placements.iter().any(|&(_, junction)| junction == V4));
+    assert_eq!((best.margin, best.protected_points), (43, 0));
+
+    for (robot, junction) in best.placements.clone() {
+        endgame.cap_for(robot, junction).unwrap();
+    }
+    let (red, _) = EndGame::end_match(endgame);
+    // two beacons, three possessions, and a circuit
+    assert_eq!(red.endgame_points, 49);
+}
+
+#[cfg(feature = "simulation")]
+#[test]
+fn simulation_test() {
+    use crate::traditional::simulation::{RobotProfile, Simulator};
+    let strong = RobotProfile {
+        cycle_time: (6.0, 9.0),
+        level_weights: [0, 1, 2, 6],
+        has_signal_sleeve: true,
+        auto_cones: 3,
+        auto_success_rate: 0.9,
+        beacon_reliability: 0.9,
+        defense_tendency: 0.1,
+    };
+    let weak = RobotProfile {
+        cycle_time: (15.0, 25.0),
+        level_weights: [3, 1, 0, 0],
+        has_signal_sleeve: false,
+        auto_cones: 0,
+        auto_success_rate: 0.5,
+        beacon_reliability: 0.3,
+        defense_tendency: 0.0,
+    };
+    let simulate = || {
+        Simulator::new([strong.clone(), strong.clone()], [weak.clone(), weak.clone()], 4017).simulate(20)
+    };
+    let results = simulate();
+    assert_eq!(results, simulate());
+    assert!(results.win_probability(Alliance::RED) > results.win_probability(Alliance::BLUE));
+    assert!(results.mean_score(Alliance::RED) > results.mean_score(Alliance::BLUE));
+    let nothing = Simulator::new([strong.clone(), strong.clone()], [weak.clone(), weak.clone()], 0).simulate(0);
+    assert_eq!((nothing.win_probability(Alliance::RED), nothing.mean_score(Alliance::BLUE)), (0.0, 0.0));
+    let slow = RobotProfile { cycle_time: (25.0, 15.0), ..weak.clone() };
+    let unlikely = RobotProfile { defense_tendency: 1.5, ..weak.clone() };
+    for invalid in [slow, unlikely] {
+        let profiles = [invalid, weak.clone()];
+        assert!(std::panic::catch_unwind(|| Simulator::new(profiles.clone(), profiles.clone(), 0)).is_err());
+    }
+}
+
+#[test]
+fn fork_test() {
+    let mut teleop = TraditionalAuto::new(
+        [true, true],
+        [true, true],
+        SignalZone::Middle
+    ).into_teleop();
+    teleop.score_for(Alliance::RED, W3);
+    let mut hypothetical = teleop.fork();
+    hypothetical.score_for(Alliance::BLUE, W3);
+    let (red, blue) = EndGame::end_match(teleop.into_end_game());
+    let (hypothetical_red, hypothetical_blue) = EndGame::end_match(hypothetical.into_end_game());
+    assert_eq!(red.endgame_points, 3);
+    assert_eq!(blue.endgame_points, 0);
+    assert_eq!(hypothetical_red.endgame_points, 0);
+    assert_eq!(hypothetical_blue.endgame_points, 3);
+
+    let auto = RedRemoteAuto::new(true, SignalZone::Middle, RemoteCircuitPattern::Pattern1);
+    let mut hypothetical = auto.fork();
+    hypothetical.score(Z2);
+    assert_eq!(RemoteEndGame::end_match(auto.into_teleop().into_end_game()).auto_points, 0);
+    assert_eq!(RemoteEndGame::end_match(hypothetical.into_teleop().into_end_game()).auto_points, 3);
+}
+
+#[test]
+fn any_match_test() {
+    use crate::dynamic::{AnyMatch, MatchError, MatchPhase, MatchResult};
+    let mut matches: Vec<AnyMatch> = vec![
+        TraditionalAuto::new([true, true], [true, true], SignalZone::Middle).into(),
+        RedRemoteAuto::new(true, SignalZone::Middle, RemoteCircuitPattern::Pattern1).into(),
+    ];
+    assert!(matches.iter().all(|any| any.phase() == MatchPhase::Auto));
+    let [traditional, remote] = &mut matches[..] else { unreachable!() };
+
+    traditional.score_for(Alliance::RED, W3).unwrap();
+    assert_eq!(traditional.cap_for(MatchIndex::RED_CAPTAIN, W3), Err(MatchError::BeaconScoredOutsideEndgame));
+    assert_eq!(traditional.score_for(Alliance::RED, Z2), Err(MatchError::WrongField));
+    assert_eq!(traditional.advance_phase(), Ok(MatchPhase::TeleOp));
+    assert_eq!(traditional.park_for(MatchIndex::RED_CAPTAIN, Terminal::Near), Err(MatchError::WrongPhase));
+    assert_eq!(traditional.advance_phase(), Ok(MatchPhase::EndGame));
+    assert_eq!(traditional.advance_phase(), Ok(MatchPhase::Finished));
+    assert_eq!(traditional.advance_phase(), Err(MatchError::WrongPhase));
+    match traditional.results() {
+        Some(MatchResult::Traditional(red, _)) => assert_eq!(red.auto_points, 5),
+        results => panic!("unexpected results {results:?}"),
+    }
+
+    assert_eq!(remote.score_for(Alliance::BLUE, Z2), Err(MatchError::NotInMatch));
+    remote.score_for(Alliance::RED, Z2).unwrap();
+    assert_eq!(remote.descore(Z3), Err(MatchError::JunctionIsEmpty));
+    while !remote.is_finished() {
+        remote.advance_phase().unwrap();
+    }
+    assert!(matches!(remote.results(), Some(MatchResult::Remote(info)) if info.auto_points == 3));
+}
+
+#[test]
+fn playoff_roster_test() {
+    use crate::{AllianceRoster, RosterError};
+    let mut red = AllianceRoster::new(FtcTeamID(4017), FtcTeamID(16145), Some(FtcTeamID(5)))
+        .unwrap();
+    let blue = AllianceRoster::new(FtcTeamID(8109), FtcTeamID(8110), None).unwrap();
+    assert_eq!(
+        AllianceRoster::new(FtcTeamID(1), FtcTeamID(2), Some(FtcTeamID(1))),
+        Err(RosterError::DuplicateTeam)
+    );
+    assert_eq!(
+        TraditionalAuto::from_rosters(
+            (red, [(FtcTeamID(4017), true), (FtcTeamID(8109), true)]),
+            (blue, [(FtcTeamID(8109), true), (FtcTeamID(8110), true)]),
+            SignalZone::Left,
+        ).err(),
+        Some(RosterError::NotOnRoster)
+    );
+
+    assert_eq!(red.substitute(FtcTeamID(16145), FtcTeamID(5)), Err(RosterError::DuplicateTeam));
+    red.substitute(FtcTeamID(16145), FtcTeamID(7)).unwrap();
+    assert_eq!(red.substitute(FtcTeamID(5), FtcTeamID(8)), Err(RosterError::BackupAlreadyUsed));
+    assert_eq!(red.select([FtcTeamID(4017), FtcTeamID(16145)]), Err(RosterError::NotOnRoster));
+
+    let auto = TraditionalAuto::from_rosters(
+        (red, [(FtcTeamID(5), true), (FtcTeamID(7), false)]),
+        (blue, [(FtcTeamID(8109), true), (FtcTeamID(8110), true)]),
+        SignalZone::Left,
+    ).unwrap();
+    let (red_info, blue_info) = EndGame::end_match(auto.into_teleop().into_end_game());
+    assert_eq!(red_info.teams, [FtcTeamID(5), FtcTeamID(7)]);
+    assert_eq!(
+        red_info.roster.unwrap().teams(),
+        &[FtcTeamID(4017), FtcTeamID(16145), FtcTeamID(5), FtcTeamID(7)]
+    );
+    assert_eq!(blue_info.roster, Some(blue));
+}
+
+#[test]
+fn card_tracker_test() {
+    use crate::{Card, CardTracker};
+    let play = |yellow: bool, no_show: bool| {
+        let mut auto = TraditionalAuto::from_teams(
+            [(FtcTeamID(4017), true), (FtcTeamID(16145), false)],
+            [(FtcTeamID(8109), true), (FtcTeamID(8110), true)],
+            SignalZone::Middle,
+        );
+        auto.score_for(Alliance::RED, W3);
+        if yellow {
+            auto.card_for(MatchIndex::RED_CAPTAIN, Card::Yellow);
+        }
+        let mut status = auto.status_of(MatchIndex::BLUE_FIRST_PICK);
+        status.no_show = no_show;
+        auto.set_status(MatchIndex::BLUE_FIRST_PICK, status);
+        if no_show {
+            // the rest of the alliance wins without the missing robot
+            auto.score_for(Alliance::BLUE, W2);
+            auto.score_for(Alliance::BLUE, X2);
+        }
+        EndGame::end_match(auto.into_teleop().into_end_game())
+    };
+    let mut tracker = CardTracker::new();
+
+    let (red, blue) = play(true, true);
+    let results = tracker.record(&red, &blue, false);
+    assert_eq!(results[0].card, Some(Card::Yellow));
+    assert_eq!((results[0].ranking_points, results[0].ranking_score), (0, 13));
+    // a no-show earns no ranking points, but its alliance's score still counts
+    assert_eq!((results[2].ranking_points, results[2].ranking_score), (2, 24));
+    assert_eq!((results[3].ranking_points, results[3].ranking_score), (0, 24));
+    assert!(tracker.has_yellow_card(FtcTeamID(4017)));
+
+    // the second yellow card escalates
+    let (red, blue) = play(true, false);
+    let results = tracker.record(&red, &blue, false);
+    assert_eq!(results[0].card, Some(Card::Red));
+    assert!(results[0].disqualified);
+    assert_eq!((results[0].ranking_points, results[0].ranking_score), (0, 0));
+    assert_eq!((results[1].ranking_points, results[1].ranking_score), (2, 13));
+
+    // in playoffs, the whole alliance is disqualified
+    let (red, blue) = play(true, false);
+    let results = tracker.record(&red, &blue, true);
+    assert_eq!(results[0].card, Some(Card::Yellow));
+    let (red, blue) = play(true, false);
+    let results = tracker.record(&red, &blue, true);
+    assert!(results[0].disqualified && results[1].disqualified && !results[2].disqualified);
+
+    let mut teleop = TraditionalAuto::new([true, true], [true, true], SignalZone::Left).into_teleop();
+    teleop.card_for(MatchIndex::BLUE_CAPTAIN, Card::Yellow);
+    teleop.card_for(MatchIndex::BLUE_CAPTAIN, Card::Yellow);
+    assert!(teleop.status_of(MatchIndex::BLUE_CAPTAIN).is_disqualified());
+}
+
+#[test]
+fn reconcile_test() {
+    use crate::dynamic::{AnyMatch, MatchAction, MatchError, MatchPhase, MatchState};
+    use crate::reconcile::{ReconcileError, Reconciler, Subject};
+    let score = |alliance, junction: TraditionalJunction| MatchAction::Score { alliance, junction: junction.into() };
+    let mut reconciler = Reconciler::new(TraditionalAuto::new([true, true], [true, true], SignalZone::Middle));
+    let red = reconciler.add_referee(Some(Alliance::RED));
+    let blue = reconciler.add_referee(Some(Alliance::BLUE));
+    let head = reconciler.add_referee(None);
+
+    // everyone agrees on auto
+    for referee in [red, head] {
+        reconciler.record(referee, score(Alliance::RED, X2)).unwrap();
+    }
+    assert_eq!(reconciler.record(blue, score(Alliance::RED, X2)), Err(ReconcileError::NotWatching));
+    assert_eq!(reconciler.end_phase(), Ok(MatchPhase::TeleOp));
+    assert!(reconciler.conflicts().is_empty());
+    assert_eq!(reconciler.authority().phase(), MatchPhase::TeleOp);
+    // every referee's copy starts teleop from the authoritative match, even the one that did not see auto
+    for referee in [red, blue, head] {
+        match reconciler.view_of(referee) {
+            Some(AnyMatch::Traditional(MatchState::TeleOp(view))) => assert_eq!(view.owner_of(X2), Some(Alliance::RED)),
+            view => panic!("{view:?}"),
+        }
+    }
+
+    // both alliances claim the top cone on W3, and the head referee never saw the descore of X2
+    reconciler.record(red, score(Alliance::RED, W3)).unwrap();
+    reconciler.record(blue, score(Alliance::BLUE, W3)).unwrap();
+    reconciler.record(head, score(Alliance::RED, W3)).unwrap();
+    reconciler.record(head, score(Alliance::BLUE, W3)).unwrap();
+    let descore = MatchAction::Descore { junction: X2.into() };
+    reconciler.record(red, descore).unwrap();
+    // the blue referee's copy has the red cone from auto, which only the red referee records
+    assert_eq!(reconciler.record(blue, descore), Err(ReconcileError::NotWatching));
+    reconciler.record(head, score(Alliance::BLUE, Y2)).unwrap();
+    reconciler.end_phase().unwrap();
+    // the head referee saw the order on W3, but missed the descore and disagrees about Y2 with the blue referee
+    let subjects = reconciler.conflicts().iter().map(|conflict| conflict.subject).collect::<Vec<_>>();
+    assert_eq!(subjects, [Subject::Junction(X2.into()), Subject::Junction(Y2.into())]);
+    assert_eq!(reconciler.authority().phase(), MatchPhase::TeleOp);
+
+    // conflicts in end game wait behind the ones from teleop
+    reconciler.record(red, score(Alliance::RED, V1)).unwrap();
+    assert_eq!(reconciler.end_phase(), Ok(MatchPhase::Finished));
+    assert_eq!(reconciler.end_phase(), Err(ReconcileError::Match(MatchError::WrongPhase)));
+    let [x2, y2, v1] = [0, 1, 2].map(|i| reconciler.conflicts()[i].clone());
+    assert_eq!(reconciler.resolve(v1.id, &[]), Err(ReconcileError::EarlierConflicts));
+    assert_eq!(reconciler.resolve(x2.id, &[score(Alliance::RED, V1)]), Err(ReconcileError::WrongSubject));
+
+    // the head referee sides with the red referee on X2 and the blue referee on Y2
+    let version = |conflict: &crate::reconcile::Conflict, referee| {
+        conflict.versions.iter().find(|(id, _)| *id == referee).unwrap().1.clone()
+    };
+    reconciler.resolve(x2.id, &version(&x2, red)).unwrap();
+    reconciler.resolve(y2.id, &version(&y2, blue)).unwrap();
+    assert_eq!(reconciler.authority().phase(), MatchPhase::EndGame);
+    assert!(reconciler.results().is_none());
+    reconciler.resolve(v1.id, &version(&v1, red)).unwrap();
+    // the same as if one referee had recorded everything the head referee decided on
+    let mut expected: AnyMatch = TraditionalAuto::new([true, true], [true, true], SignalZone::Middle).into();
+    for action in [
+        score(Alliance::RED, X2),
+        MatchAction::AdvancePhase,
+        score(Alliance::RED, W3),
+        score(Alliance::BLUE, W3),
+        descore,
+        MatchAction::AdvancePhase,
+        score(Alliance::RED, V1),
+        MatchAction::AdvancePhase,
+    ] {
+        expected.apply(action).unwrap();
+    }
+    assert_eq!(reconciler.results(), expected.results());
+    // every referee's copy is the authoritative match once it has caught up
+    for referee in [red, blue, head] {
+        let view = reconciler.view_of(referee).unwrap();
+        assert_eq!((view.phase(), view.results()), (MatchPhase::Finished, expected.results()));
+    }
+}
+
+#[test]
+fn encoding_test() {
+    use crate::dynamic::{AnyMatch, MatchAction};
+    use crate::encoding::{Compact, DecodeError};
+    use crate::remote::{BlueRemoteAuto, BlueRemoteJunction, BlueRemoteTeleOp, RedRemoteEndGame};
+    use crate::traditional::{TraditionalEndGame, TraditionalTeleOp};
+    use crate::{AllianceRoster, Card, ParkingLocation};
+    let red = AllianceRoster::new(FtcTeamID(4017), FtcTeamID(16145), Some(FtcTeamID(8110))).unwrap();
+    let blue = AllianceRoster::new(FtcTeamID(-3), FtcTeamID(-4), None).unwrap();
+    let mut auto = TraditionalAuto::from_rosters(
+        (red, [(FtcTeamID(4017), true), (FtcTeamID(8110), false)]),
+        (blue, [(FtcTeamID(-3), false), (FtcTeamID(-4), true)]),
+        SignalZone::Right,
+    )
+    .unwrap();
+    auto.score_for(Alliance::RED, W3);
+    auto.score_for(Alliance::BLUE, W3);
+    auto.park_for(MatchIndex::BLUE_FIRST_PICK, SignalZone::Right);
+    auto.card_for(MatchIndex::RED_FIRST_PICK, Card::Yellow);
+    let decoded = TraditionalAuto::from_bytes(&auto.to_bytes()).unwrap();
+    assert_eq!(decoded.to_bytes(), auto.to_bytes());
+    assert_eq!(decoded.into_teleop().to_bytes(), auto.fork().into_teleop().to_bytes());
+
+    let mut teleop = auto.into_teleop();
+    for junction in [V1, W2, X2, Y2, Y1, W3, W3] {
+        teleop.score_for(Alliance::RED, junction);
+    }
+    teleop.add_terminal_for(Alliance::RED, Terminal::Near);
+    teleop.add_terminal_for(Alliance::RED, Terminal::Far);
+    teleop.penalize(Alliance::BLUE, 10);
+    let bytes = teleop.to_bytes();
+    assert!(bytes.len() < 64, "{} bytes", bytes.len());
+    let decoded = TraditionalTeleOp::from_bytes(&bytes).unwrap();
+    // possession and circuits are rebuilt
+    assert_eq!(decoded.has_circuit(Alliance::RED), teleop.has_circuit(Alliance::RED));
+    assert_eq!(decoded.owner_of(W3), Some(Alliance::RED));
+
+    let mut endgame = teleop.into_end_game();
+    let _ = endgame.cap_for(MatchIndex::RED_CAPTAIN, X2);
+    endgame.park_in_terminal_for(MatchIndex::BLUE_CAPTAIN);
+    let mut decoded = TraditionalEndGame::from_bytes(&endgame.to_bytes()).unwrap();
+    assert_eq!(decoded.cap_for(MatchIndex::RED_CAPTAIN, W2), endgame.cap_for(MatchIndex::RED_CAPTAIN, W2));
+    assert_eq!(decoded.end_match(), endgame.fork().end_match());
+
+    // every phase of both remote fields, and finished matches
+    let mut blue: AnyMatch = BlueRemoteAuto::with_team(FtcTeamID(19000), true, SignalZone::Left, RemoteCircuitPattern::Pattern3).into();
+    let mut red: AnyMatch = RedRemoteAuto::new(false, SignalZone::Middle, RemoteCircuitPattern::Pattern5).into();
+    let mut traditional: AnyMatch = endgame.into();
+    loop {
+        for game in [&blue, &red, &traditional] {
+            let bytes = game.to_bytes();
+            let decoded = AnyMatch::from_bytes(&bytes).unwrap();
+            assert_eq!((decoded.field_type(), decoded.phase()), (game.field_type(), game.phase()));
+            assert_eq!(decoded.to_bytes(), bytes);
+            assert_eq!(decoded.results(), game.results());
+        }
+        if red.is_finished() {
+            break;
+        }
+        blue.apply(MatchAction::Score { alliance: Alliance::BLUE, junction: BlueRemoteJunction::W3.into() }).unwrap();
+        red.apply(MatchAction::Score { alliance: Alliance::RED, junction: Z2.into() }).unwrap();
+        for game in [&mut blue, &mut red, &mut traditional] {
+            let _ = game.advance_phase();
+        }
+    }
+    let blue_teleop = BlueRemoteTeleOp::from_bytes(&BlueRemoteAuto::new(true, SignalZone::Left, RemoteCircuitPattern::Pattern1).into_teleop().to_bytes());
+    assert!(blue_teleop.is_ok());
+
+    // bad input
+    let bytes = RedRemoteAuto::new(true, SignalZone::Left, RemoteCircuitPattern::Pattern1).to_bytes();
+    assert!(RedRemoteAuto::from_bytes(&bytes).is_ok());
+    assert_eq!(RedRemoteAuto::from_bytes(&bytes[..bytes.len() - 1]).err(), Some(DecodeError::UnexpectedEnd));
+    assert_eq!(RedRemoteAuto::from_bytes(&[&bytes[..], &[0]].concat()).err(), Some(DecodeError::TrailingBytes));
+    assert_eq!(RedRemoteEndGame::from_bytes(&bytes).err(), Some(DecodeError::WrongType));
+    assert_eq!(AnyMatch::from_bytes(&[0xff]).err(), Some(DecodeError::WrongType));
+    let mut bad_zone = bytes.clone();
+    *bad_zone.last_mut().unwrap() = ParkingLocation::Substation as u8;
+    assert_eq!(RedRemoteAuto::from_bytes(&bad_zone).err(), Some(DecodeError::InvalidByte(bytes.len() - 1)));
+    // teams are the first thing after the header, as zigzag varints
+    let robots = |first, second| [(FtcTeamID(first), true), (FtcTeamID(second), true)];
+    let bytes = TraditionalAuto::from_teams(robots(i32::MIN, 2), robots(3, 4), SignalZone::Left).to_bytes();
+    assert_eq!(bytes[1..6], [0xff, 0xff, 0xff, 0xff, 0x0f]);
+    assert!(TraditionalAuto::from_bytes(&bytes).is_ok());
+    let mut overlong = bytes.clone();
+    overlong[5] = 0x1f;
+    assert_eq!(TraditionalAuto::from_bytes(&overlong).err(), Some(DecodeError::InvalidByte(5)));
+    let mut duplicate = TraditionalAuto::from_teams(robots(1, 2), robots(3, 4), SignalZone::Left).to_bytes();
+    duplicate[2] = duplicate[1];
+    assert!(matches!(TraditionalAuto::from_bytes(&duplicate), Err(DecodeError::InvalidByte(_))));
+}
+
+#[test]
+fn command_test() {
+    use crate::command::{parse, Expected, ParseError};
+    use crate::dynamic::{FieldType, MatchAction};
+    use crate::remote::{BlueRemoteJunction, RedRemoteJunction};
+    use crate::{Card, ParkingLocation};
+    let traditional = |text| parse(FieldType::Traditional, text);
+    fn error<T>(position: usize, len: usize, expected: Expected) -> Result<T, ParseError> {
+        Err(ParseError { position, len, expected })
+    }
+    assert_eq!(traditional("R W3"), Ok(MatchAction::Score { alliance: Alliance::RED, junction: W3.into() }));
+    assert_eq!(traditional("  b  -x2 "), Ok(MatchAction::Descore { junction: X2.into() }));
+    assert_eq!(traditional("R1 cap V1"), Ok(MatchAction::Cap { robot: MatchIndex::RED_FIRST_PICK, junction: V1.into() }));
+    assert_eq!(traditional("B term near"), Ok(MatchAction::AddTerminal { alliance: Alliance::BLUE, terminal: Terminal::Near }));
+    assert_eq!(traditional("R0 park left"), Ok(MatchAction::Park { robot: MatchIndex::RED_CAPTAIN, location: ParkingLocation::LeftSignalZone }));
+    assert_eq!(traditional("B0 card yellow"), Ok(MatchAction::Card { robot: MatchIndex::BLUE_CAPTAIN, card: Card::Yellow }));
+    assert_eq!(traditional("foul B major G402"), Ok(MatchAction::Penalize { alliance: Alliance::BLUE, points: 30 }));
+    assert_eq!(traditional("foul red minor"), Ok(MatchAction::Penalize { alliance: Alliance::RED, points: 10 }));
+    assert_eq!(traditional("next"), Ok(MatchAction::AdvancePhase));
+    // junctions depend on the field
+    assert_eq!(parse(FieldType::RedRemote, "R Z1"), Ok(MatchAction::Score { alliance: Alliance::RED, junction: RedRemoteJunction::Z1.into() }));
+    assert_eq!(parse(FieldType::BlueRemote, "B V1"), Ok(MatchAction::Score { alliance: Alliance::BLUE, junction: BlueRemoteJunction::V1.into() }));
+    assert_eq!(parse(FieldType::RedRemote, "R V1"), error(2, 2, Expected::AllianceAction));
+
+    // errors point at the problem
+    assert_eq!(traditional(""), error(0, 0, Expected::Command));
+    assert_eq!(traditional("R W9"), error(3, 1, Expected::Column));
+    assert_eq!(traditional("B -Q2"), error(3, 2, Expected::Junction));
+    assert_eq!(traditional("R2 park left"), error(1, 1, Expected::RobotNumber));
+    assert_eq!(traditional("R0 park"), error(7, 0, Expected::ParkingLocation));
+    assert_eq!(traditional("R0 jump"), error(3, 4, Expected::RobotAction));
+    assert_eq!(traditional("B term middle"), error(7, 6, Expected::Terminal));
+    assert_eq!(traditional("foul B huge"), error(7, 4, Expected::Severity));
+    assert_eq!(traditional("foul B major 402"), error(13, 3, Expected::Rule));
+    assert_eq!(traditional("R W3 W4"), error(5, 2, Expected::End));
+    assert_eq!(traditional("score"), error(0, 5, Expected::Command));
+    assert_eq!(traditional("R W9").unwrap_err().to_string(), "expected a junction column, from 1 to 5 at position 3");
+
+    assert_eq!("w3".parse(), Ok(W3));
+    assert_eq!("Y5".parse::<RedRemoteJunction>(), Ok(RedRemoteJunction::Y5));
+    assert_eq!("Y5".parse::<BlueRemoteJunction>(), error(0, 2, Expected::Junction));
+    assert_eq!("V".parse::<TraditionalJunction>(), error(1, 0, Expected::Column));
+    assert_eq!("B1".parse(), Ok(MatchIndex::BLUE_FIRST_PICK));
+    assert_eq!("substation".parse(), Ok(ParkingLocation::Substation));
+    assert_eq!("Far".parse(), Ok(Terminal::Far));
+    assert_eq!("right".parse(), Ok(SignalZone::Right));
+    assert_eq!("near".parse::<SignalZone>(), error(0, 4, Expected::SignalZone));
+}
+
+#[test]
+fn geometry_test() {
+    use crate::geometry::*;
+    use crate::remote::{BlueRemoteJunction, RedRemoteJunction};
+    use crate::traditional::TraditionalJunction::{V2, X3, Y4, Z1, Z5};
+    let red = Frame::AllianceStation(Alliance::RED);
+    let blue = Frame::AllianceStation(Alliance::BLUE);
+    let close = |a: Point, b: Point| a.distance_squared(b) < 0.01;
+    assert!(close(X3.position(Frame::Audience), Point::new(0.0, 0.0)));
+    assert!(close(Z1.position(Frame::Audience), Point::from_inches(48.0, -48.0)));
+    // red drivers look down the rows from Z, with the audience on their left
+    assert!(close(Z1.position(red), Point::from_inches(-48.0, -48.0)));
+    assert!(close(V1.position(blue), Point::from_inches(48.0, -48.0)));
+    assert_eq!(W3.level(), JunctionLevel::High);
+    assert_eq!(V2.level(), JunctionLevel::Low);
+    assert!((W3.height() - 850.9).abs() < 0.01);
+    assert_eq!(X3.level().height_inches(), 0.56);
+    // remote junctions stand on their alliance's half
+    assert!(close(RedRemoteJunction::Z5.position(Frame::Audience), Z5.position(Frame::Audience)));
+    assert!(close(BlueRemoteJunction::V2.position(Frame::Audience), V2.position(Frame::Audience)));
+
+    let point = Point::from_inches(20.0, 30.0);
+    for frame in [Frame::Audience, red, blue] {
+        assert!(close(point.convert(Frame::Audience, frame).convert(frame, Frame::Audience), point));
+        assert_eq!(TraditionalJunction::nearest(point.convert(Frame::Audience, frame), frame), Y4);
+    }
+    assert_eq!(RedRemoteJunction::nearest(Point::from_inches(-60.0, 0.0), Frame::Audience), RedRemoteJunction::X3);
+    let pose = Pose::new(point, 0.0).convert(Frame::Audience, blue);
+    assert!((pose.heading - core::f32::consts::FRAC_PI_2).abs() < 1e-6);
+    assert!(Pose::new(point, 3.0).convert(Frame::Audience, blue).heading < 0.0);
+
+    let tile = Tile::containing(Z1.position(red), red);
+    assert!(tile.is_some_and(|tile| Z1.tiles().contains(&tile)));
+    assert_eq!(Tile::containing(Point::new(FIELD_SIZE, 0.0), Frame::Audience), None);
+    let a1 = Tile::new(0, 0).unwrap();
+    assert_eq!(a1.to_string(), "A1");
+    assert_eq!(Tile::containing(a1.center(red), red), Some(a1));
+    assert_eq!(a1.junctions::<TraditionalJunction>().collect::<Vec<_>>(), [V1]);
+    assert_eq!(Tile::new(2, 2).unwrap().junctions::<TraditionalJunction>().collect::<Vec<_>>(), [W2, W3, X2, X3]);
+    assert_eq!(Tile::new(1, 1).unwrap().junctions::<RedRemoteJunction>().count(), 0);
+    assert_eq!(Tile::new(6, 0), None);
+
+    assert!(close(terminal(Alliance::RED, Terminal::Near, Frame::Audience), Point::new(FIELD_SIZE / 2.0, -FIELD_SIZE / 2.0)));
+    // each alliance sees the other's side of the field mirrored
+    let mirrored = |point: Point| Point::new(-point.x, point.y);
+    assert!(close(terminal(Alliance::BLUE, Terminal::Far, blue), mirrored(terminal(Alliance::RED, Terminal::Far, red))));
+    assert!(close(substation(Alliance::BLUE, blue), Point::new(0.0, -FIELD_SIZE / 2.0)));
+    assert!(close(cone_stack(Alliance::RED, Side::Rear, red), mirrored(cone_stack(Alliance::BLUE, Side::Rear, blue))));
+    assert_eq!(signal_zone(Alliance::RED, Side::Audience, SignalZone::Left).to_string(), "D1");
+    assert_eq!(signal_zone(Alliance::BLUE, Side::Audience, SignalZone::Left).to_string(), "C3");
+    assert_eq!(signal_zone(Alliance::BLUE, Side::Rear, SignalZone::Right).to_string(), "C4");
+}
+
+#[test]
+fn parking_test() {
+    use crate::geometry::parking::{classify, rectangle, substation_area, terminal_area};
+    use crate::geometry::*;
+    use crate::ParkingLocation;
+    let red = Frame::AllianceStation(Alliance::RED);
+    let robot = rectangle(inches_to_mm(18.0), inches_to_mm(18.0));
+    let place = Pose::new(Point::new(0.0, 0.0), core::f32::consts::FRAC_PI_2).place(Point::new(1.0, 0.0));
+    assert!(place.distance_squared(Point::new(0.0, 1.0)) < 1e-6);
+
+    // completely in a signal zone, even turned a little
+    let zone = signal_zone(Alliance::RED, Side::Audience, SignalZone::Left);
+    let pose = Pose::new(zone.center(red), 0.3);
+    assert_eq!(classify(&robot, pose, red, Alliance::RED, Side::Audience), Some(ParkingLocation::LeftSignalZone));
+    assert_eq!(classify(&robot, pose.convert(red, Frame::Audience), Frame::Audience, Alliance::RED, Side::Audience), Some(ParkingLocation::LeftSignalZone));
+    // the zone is for the other side, or the robot is only partly in it
+    assert_eq!(classify(&robot, pose, red, Alliance::RED, Side::Rear), None);
+    let across = Pose::new(Point::new(pose.position.x + TILE_SIZE / 2.0, pose.position.y), 0.0);
+    assert_eq!(classify(&robot, across, red, Alliance::RED, Side::Audience), None);
+
+    // partly in a terminal still counts
+    let corner = Tile::containing(terminal(Alliance::RED, Terminal::Near, Frame::Audience), Frame::Audience);
+    assert_eq!(corner, None);
+    let corner_tile = Tile::new(5, 0).unwrap();
+    let pose = Pose::new(corner_tile.center(Frame::Audience), 0.0);
+    assert_eq!(classify(&robot, pose, Frame::Audience, Alliance::RED, Side::Audience), Some(ParkingLocation::NearTerminal));
+    assert_eq!(classify(&robot, pose, Frame::Audience, Alliance::BLUE, Side::Audience), None);
+    let next_tile = Pose::new(Tile::new(4, 0).unwrap().center(Frame::Audience), 0.0);
+    assert_eq!(classify(&robot, next_tile, Frame::Audience, Alliance::RED, Side::Audience), None);
+    let far = Pose::new(Tile::new(0, 5).unwrap().center(red), 1.0);
+    assert_eq!(classify(&robot, far, red, Alliance::RED, Side::Audience), Some(ParkingLocation::FarTerminal));
+    assert_eq!(terminal_area(Alliance::BLUE, Terminal::Far, Frame::Audience)[0], terminal(Alliance::BLUE, Terminal::Far, Frame::Audience));
+
+    // against the middle of the wall
+    let wall = substation(Alliance::BLUE, Frame::AllianceStation(Alliance::BLUE));
+    let pose = Pose::new(Point::new(wall.x, wall.y + inches_to_mm(9.0)), core::f32::consts::FRAC_PI_2);
+    assert_eq!(classify(&robot, pose, Frame::AllianceStation(Alliance::BLUE), Alliance::BLUE, Side::Rear), Some(ParkingLocation::Substation));
+    assert_eq!(classify(&robot, pose, Frame::AllianceStation(Alliance::BLUE), Alliance::RED, Side::Rear), None);
+    assert!(substation_area(Alliance::RED, red).iter().all(|point| point.y <= -FIELD_SIZE / 2.0 + TILE_SIZE + 0.01));
+    assert_eq!(classify(&robot[..2], pose, red, Alliance::RED, Side::Rear), None);
+
+    // the result feeds straight into the match
+    let mut auto = TraditionalAuto::new([false; 2], [false; 2], SignalZone::Left);
+    auto.park_for(MatchIndex::RED_CAPTAIN, classify(&robot, Pose::new(zone.center(red), 0.0), red, Alliance::RED, Side::Audience).unwrap());
+    assert_eq!(auto.into_teleop().into_end_game().end_match().0.auto_points, 10);
+}
+
+#[test]
+fn perspective_test() {
+    use crate::geometry::{signal_zone, Frame, JunctionGeometry, Side};
+    use crate::traditional::perspective::{FieldSnapshot, JunctionSnapshot};
+    use crate::traditional::TraditionalJunction::{V5, Z1, Z5};
+    use crate::FieldCoordinate;
+    let red = Frame::AllianceStation(Alliance::RED);
+    let blue = Frame::AllianceStation(Alliance::BLUE);
+    // red's near terminal is on its drivers' near left, and blue's on its drivers' near right
+    assert_eq!(Z1.convert(Frame::Audience, red), V1);
+    assert_eq!(V1.convert(Frame::Audience, blue), Z1);
+    assert_eq!(V1.convert(red, blue), Z5);
+    for &junction in TraditionalJunction::ALL {
+        for (from, to) in [(Frame::Audience, red), (red, blue), (blue, Frame::Audience)] {
+            assert_eq!(junction.convert(from, to).convert(to, from), junction);
+            assert_eq!(junction.convert(from, to).points(), junction.points());
+        }
+        // a name means the same place in every frame
+        let place = junction.convert(red, Frame::Audience).position(Frame::Audience);
+        assert!(place.distance_squared(junction.convert(red, blue).convert(blue, Frame::Audience).position(Frame::Audience)) < 0.01);
+        assert_eq!(junction.mirrored().mirrored(), junction);
+        assert_eq!(junction.rotated().points(), junction.points());
+    }
+    assert_eq!(Z1.mirrored(), V1);
+    assert_eq!(V5.mirrored(), Z5);
+    assert_eq!(Z1.rotated(), V5);
+    // mirroring reflects the field across the line between the alliances, and a signal zone along with it
+    let reflected = |(x, y): (f32, f32), (mirrored_x, mirrored_y): (f32, f32)| {
+        (x + mirrored_x).abs() < 0.01 && (y - mirrored_y).abs() < 0.01
+    };
+    assert!(reflected(W2.position(Frame::Audience).inches(), W2.mirrored().position(Frame::Audience).inches()));
+    for side in [Side::Audience, Side::Rear] {
+        for zone in [SignalZone::Left, SignalZone::Middle, SignalZone::Right] {
+            let red_zone = signal_zone(Alliance::RED, side, zone).center(Frame::Audience).inches();
+            let blue_zone = signal_zone(Alliance::BLUE, side, zone.mirrored()).center(Frame::Audience).inches();
+            assert!(reflected(red_zone, blue_zone), "{side:?} {zone}");
+        }
+    }
+
+    let mut auto = TraditionalAuto::new([true; 2], [false; 2], SignalZone::Left);
+    auto.park_for(MatchIndex::RED_CAPTAIN, SignalZone::Left);
+    auto.park_for(MatchIndex::BLUE_CAPTAIN, SignalZone::Right);
+    auto.score_for(Alliance::RED, Z1);
+    auto.score_for(Alliance::BLUE, Z1);
+    auto.score_for(Alliance::RED, Y2);
+    auto.add_terminal_for(Alliance::RED, Terminal::Near);
+    let mirrored_auto = auto.mirrored();
+    let mut endgame = auto.into_teleop().into_end_game();
+    endgame.cap_for(MatchIndex::BLUE_CAPTAIN, Y2).unwrap();
+    let snapshot = endgame.snapshot();
+    assert_eq!(snapshot[Z1], JunctionSnapshot { top_cone: Some(Alliance::BLUE), cones: [1, 1], beacon: None });
+    assert_eq!(snapshot[Y2].owner(), Some(Alliance::BLUE));
+    assert_eq!(snapshot.terminals, [[1, 0], [0, 0]]);
+    assert_eq!(snapshot.iter().filter(|(_, junction)| junction.owner().is_some()).count(), 2);
+
+    // blue's view of the mirrored field is red's view of the real one, with its left and right swapped
+    let mirrored = snapshot.mirrored();
+    assert_eq!(mirrored[V1], JunctionSnapshot { top_cone: Some(Alliance::RED), cones: [1, 1], beacon: None });
+    assert_eq!(mirrored[W2].beacon, Some(Alliance::RED));
+    assert_eq!(mirrored.terminals, [[0, 0], [1, 0]]);
+    assert_eq!(mirrored.mirrored(), snapshot);
+    assert_eq!(snapshot.convert(Frame::Audience, red)[V1], snapshot[Z1]);
+    assert_eq!(snapshot.convert(Frame::Audience, red).convert(red, Frame::Audience), snapshot);
+    assert_eq!(snapshot.rotated()[V5], snapshot[Z1]);
+    assert_eq!(snapshot.rotated().terminals, [[0, 1], [0, 0]]);
+
+    // a mirrored match scores, and answers strategy queries, like the real one with the alliances swapped
+    let mirrored_match = endgame.mirrored();
+    assert_eq!(mirrored_match.snapshot(), mirrored);
+    assert_eq!(mirrored_match.owner_of(W2), Some(Alliance::RED));
+    let completion = endgame.circuit_completion(Alliance::RED).unwrap();
+    let mirrored_completion = mirrored_match.circuit_completion(Alliance::BLUE).unwrap();
+    assert_eq!(mirrored_completion.junctions.len(), completion.junctions.len());
+    assert_eq!(mirrored_completion.terminals, completion.terminals);
+    let scores = |(red, blue): (crate::AllianceInfo<2>, crate::AllianceInfo<2>)| {
+        [red, blue].map(|info| (info.teams, info.auto_points, info.teleop_points, info.endgame_points))
+    };
+    let [red_scores, blue_scores] = scores(EndGame::end_match(endgame.fork()));
+    assert_eq!(scores(EndGame::end_match(mirrored_match.mirrored())), [red_scores, blue_scores]);
+    assert_eq!(scores(EndGame::end_match(mirrored_match)), [blue_scores, red_scores]);
+    // including the robots parked in signal zones during auto
+    let [red_auto, blue_auto] = scores(EndGame::end_match(mirrored_auto.into_teleop().into_end_game()))
+        .map(|(teams, auto_points, ..)| (teams, auto_points));
+    assert_eq!([red_auto, blue_auto], [(blue_scores.0, blue_scores.1), (red_scores.0, red_scores.1)]);
+
+    // scouting data can be recorded by hand
+    let mut scouted = FieldSnapshot::default();
+    scouted[W3].top_cone = Some(Alliance::RED);
+    assert_eq!(scouted.convert(red, Frame::Audience)[W3.convert(red, Frame::Audience)].top_cone, Some(Alliance::RED));
+}
+
+#[test]
+fn render_test() {
+    use crate::dynamic::AnyMatch;
+    use crate::remote::{BlueRemoteAuto, BlueRemoteJunction};
+    use crate::render::Render;
+    use crate::traditional::TraditionalJunction::{V2, V3, X1, Y1, Z1};
+    let mut auto = TraditionalAuto::new([false; 2], [false; 2], SignalZone::Left);
+    auto.park_for(MatchIndex::BLUE_FIRST_PICK, SignalZone::Middle);
+    let mut teleop = auto.into_teleop();
+    for junction in [Z1, Y1, X1, W2, V3, V4, V2] {
+        teleop.score_for(Alliance::RED, junction);
+    }
+    teleop.score_for(Alliance::BLUE, V2);
+    for _ in 0..8 {
+        teleop.score_for(Alliance::BLUE, W3);
+    }
+    teleop.score_for(Alliance::RED, W3);
+    teleop.add_terminal_for(Alliance::RED, Terminal::Near);
+    teleop.add_terminal_for(Alliance::RED, Terminal::Far);
+    teleop.add_terminal_for(Alliance::RED, Terminal::Far);
+    let mut endgame = teleop.into_end_game();
+    endgame.cap_for(MatchIndex::BLUE_CAPTAIN, X2).unwrap();
+    endgame.park_in_terminal_for(MatchIndex::RED_CAPTAIN);
+    assert!(endgame.has_circuit(Alliance::RED));
+
+    let text = endgame.to_ascii();
+    let lines: Vec<&str> = text.lines().collect();
+    assert_eq!(lines[0], "Traditional EndGame");
+    // the top cone is in capitals, and tall stacks only show their top
+    assert!(lines[3].contains("3+bbbbbR"), "{}", lines[3]);
+    assert!(lines[4].contains("@B0"), "{}", lines[4]);
+    assert!(lines[2].starts_with("V  .   "), "{}", lines[2]);
+    assert!(lines[2].contains(" rB ") && lines[2].contains(" R* "), "{}", lines[2]);
+    assert!(text.contains("red terminals: near 1, far 2"));
+    assert!(text.contains("R0 parked in NearTerminal"));
+    assert!(text.contains("red circuit: V3 V4 W2 X1 Y1"));
+
+    let svg = endgame.to_svg();
+    assert!(svg.starts_with("<svg") && svg.trim_end().ends_with("</svg>"));
+    assert_eq!(svg.matches(">B0</text>").count(), 1);
+    // five circuit rings, and cones outlined at the top of each stack
+    assert_eq!(svg.matches(r#"stroke-width="4""#).count(), 5);
+    assert_eq!(svg.matches(r##"stroke="#000""##).count(), 8);
+
+    let mut remote: AnyMatch = BlueRemoteAuto::new(true, SignalZone::Left, RemoteCircuitPattern::Pattern1).into();
+    remote.score_for(Alliance::BLUE, BlueRemoteJunction::V1).unwrap();
+    remote.score_for(Alliance::BLUE, BlueRemoteJunction::V1).unwrap();
+    let text = remote.to_ascii().unwrap();
+    let lines: Vec<&str> = text.lines().collect();
+    assert_eq!(lines[0], "BlueRemote Auto");
+    assert_eq!(lines.len(), 6);
+    assert!(lines[2].starts_with("V  bB "), "{}", lines[2]);
+    assert!(lines[4].starts_with('X'));
+    assert!(remote.to_svg().unwrap().contains(r#"viewBox="0 0 540 420""#));
+    while !remote.is_finished() {
+        remote.advance_phase().unwrap();
+    }
+    assert_eq!(remote.to_ascii(), None);
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn journal_test() {
+    use crate::dynamic::{MatchAction, MatchError, MatchPhase};
+    use crate::journal::{JournalError, JournaledMatch, MatchSetup, Recovery};
+    use std::io::Write;
+    let path = std::env::temp_dir().join(format!("journal_test_{}", std::process::id()));
+    let _ = std::fs::remove_file(&path);
+    let setup = MatchSetup::Traditional {
+        red: [(FtcTeamID(4017), true), (FtcTeamID(16145), false)],
+        blue: [(FtcTeamID(8109), true), (FtcTeamID(8110), true)],
+        signal_zone: SignalZone::Left,
+    };
+    let mut journaled = JournaledMatch::create(&path, setup).unwrap();
+    assert!(matches!(JournaledMatch::create(&path, setup), Err(JournalError::Io(_))));
+    let actions = [
+        MatchAction::Score { alliance: Alliance::RED, junction: W3.into() },
+        MatchAction::Park { robot: MatchIndex::BLUE_CAPTAIN, location: SignalZone::Left.into() },
+        MatchAction::AdvancePhase,
+        MatchAction::Score { alliance: Alliance::BLUE, junction: W3.into() },
+        // fails, but still takes away the robot's beacon
+        MatchAction::Cap { robot: MatchIndex::RED_CAPTAIN, junction: Y1.into() },
+        MatchAction::AddTerminal { alliance: Alliance::BLUE, terminal: Terminal::Far },
+    ];
+    for action in actions {
+        let _ = journaled.apply(action);
+    }
+    let wrong_field = MatchAction::Descore { junction: Z2.into() };
+    assert!(matches!(journaled.apply(wrong_field), Err(JournalError::Match(MatchError::WrongField))));
+    drop(journaled);
+
+    // a crash partway through writing the next record
+    let mut file = std::fs::OpenOptions::new().append(true).open(&path).unwrap();
+    file.write_all(&[3, 0, 1]).unwrap();
+    drop(file);
+    let (mut journaled, recovery) = JournaledMatch::open(&path).unwrap();
+    assert_eq!(recovery, Recovery { actions: actions.len(), torn: Some(vec![3, 0, 1]) });
+    assert_eq!(journaled.game().phase(), MatchPhase::TeleOp);
+    // the torn record stays until it is truncated
+    assert!(matches!(journaled.apply(MatchAction::AdvancePhase), Err(JournalError::Torn)));
+    assert!(std::fs::read(&path).unwrap().ends_with(&[3, 0, 1]));
+    journaled.truncate().unwrap();
+    journaled.apply(MatchAction::AdvancePhase).unwrap();
+    let cap = MatchAction::Cap { robot: MatchIndex::RED_CAPTAIN, junction: W3.into() };
+    assert!(matches!(journaled.apply(cap), Err(JournalError::Match(MatchError::BeaconPreviouslyScored))));
+    journaled.apply(MatchAction::AdvancePhase).unwrap();
+    let expected = journaled.into_game().results();
+
+    let (journaled, recovery) = JournaledMatch::open(&path).unwrap();
+    assert_eq!(recovery, Recovery { actions: actions.len() + 3, torn: None });
+    assert!(expected.is_some());
+    assert_eq!(journaled.game().results(), expected);
+    drop(journaled);
+
+    // a damaged length that reaches past the end of the file does not hide the last record
+    let bytes = std::fs::read(&path).unwrap();
+    // the last two records are the failed cap and an advance phase, which are six and four bytes long
+    let second_to_last = bytes.len() - 10;
+    let mut damaged = bytes.clone();
+    damaged[second_to_last] = 0xff;
+    std::fs::write(&path, &damaged).unwrap();
+    assert!(matches!(JournaledMatch::open(&path), Err(JournalError::Corrupt(offset)) if offset == second_to_last as u64));
+    assert_eq!(std::fs::read(&path).unwrap(), damaged);
+
+    // damage a record before the last
+    let mut bytes = bytes;
+    bytes[30] ^= 0xff;
+    std::fs::write(&path, bytes).unwrap();
+    assert!(matches!(JournaledMatch::open(&path), Err(JournalError::Corrupt(_))));
+    std::fs::remove_file(&path).unwrap();
+
+    // an action the match panics on is never journaled, so the journal still opens
+    let mut journaled = JournaledMatch::create(&path, setup).unwrap();
+    let score = MatchAction::Score { alliance: Alliance::RED, junction: W3.into() };
+    let mut scored = 0;
+    while journaled.apply(score).is_ok() {
+        scored += 1;
+    }
+    assert!(matches!(journaled.apply(score), Err(JournalError::Panic)));
+    drop(journaled);
+    let (_, recovery) = JournaledMatch::open(&path).unwrap();
+    assert_eq!(recovery.actions, scored);
+    // a journal that holds one anyway reports it instead of panicking
+    let bytes = std::fs::read(&path).unwrap();
+    // a score record is its length, a tag, an alliance, a junction and a two byte checksum
+    let record = bytes[bytes.len() - 6..].to_vec();
+    std::fs::write(&path, [&bytes[..], &record].concat()).unwrap();
+    assert!(matches!(JournaledMatch::open(&path), Err(JournalError::Corrupt(offset)) if offset == bytes.len() as u64));
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[cfg(feature = "server")]
+#[test]
+fn server_test() {
+    use crate::FieldCoordinate;
+    use serde_json::{json, Value};
+    use std::io::{BufRead, BufReader, Read, Write};
+    use std::net::{SocketAddr, TcpStream};
+
+    fn request(address: SocketAddr, method: &str, path: &str, body: Value) -> (u16, Value) {
+        let body = body.to_string();
+        let mut stream = TcpStream::connect(address).unwrap();
+        write!(
+            stream,
+            "{method} {path} HTTP/1.1\r\nHost: {address}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+            body.len(),
+        ).unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).unwrap();
+        let (head, body) = response.split_once("\r\n\r\n").unwrap();
+        (head["HTTP/1.1 ".len()..][..3].parse().unwrap(), serde_json::from_str(body).unwrap())
+    }
+
+    // a WebSocket client that only reads, which is all the server expects of one
+    fn connect(address: SocketAddr, path: &str) -> BufReader<TcpStream> {
+        let mut stream = TcpStream::connect(address).unwrap();
+        write!(
+            stream,
+            "GET {path} HTTP/1.1\r\nHost: {address}\r\nConnection: Upgrade\r\nUpgrade: websocket\r\n\
+            Sec-WebSocket-Version: 13\r\nSec-WebSocket-Key: dGhlIHNhbXBsZSBub25jZQ==\r\n\r\n",
+        ).unwrap();
+        let mut reader = BufReader::new(stream);
+        let mut line = String::new();
+        reader.read_line(&mut line).unwrap();
+        assert!(line.starts_with("HTTP/1.1 101"), "{line}");
+        while line != "\r\n" {
+            line.clear();
+            reader.read_line(&mut line).unwrap();
+        }
+        reader
+    }
+
+    // reads one unfragmented text frame, which is how the server sends every state, skipping pongs
+    fn receive(reader: &mut BufReader<TcpStream>) -> Value {
+        let mut head = [0; 2];
+        reader.read_exact(&mut head).unwrap();
+        if head == [0x8a, 0] {
+            return receive(reader);
+        }
+        assert_eq!(head[0], 0x81, "expected a final text frame");
+        let len = match head[1] {
+            126 => {
+                let mut len = [0; 2];
+                reader.read_exact(&mut len).unwrap();
+                u16::from_be_bytes(len) as usize
+            }
+            127 => {
+                let mut len = [0; 8];
+                reader.read_exact(&mut len).unwrap();
+                u64::from_be_bytes(len) as usize
+            }
+            len => len as usize,
+        };
+        let mut payload = vec![0; len];
+        reader.read_exact(&mut payload).unwrap();
+        serde_json::from_slice(&payload).unwrap()
+    }
+
+    let runtime = tokio::runtime::Runtime::new().unwrap();
+    let listener = runtime.block_on(tokio::net::TcpListener::bind("127.0.0.1:0")).unwrap();
+    let address = listener.local_addr().unwrap();
+    runtime.spawn(crate::server::serve(listener));
+
+    let robot = |team| json!({ "team": team, "signal_sleeve": false });
+    let (status, created) = request(address, "POST", "/matches", json!({
+        "field": "traditional",
+        "red": [robot(1), robot(2)],
+        "blue": [robot(3), robot(4)],
+        "signal_zone": "middle",
+    }));
+    assert_eq!(status, 200);
+    let id = created["id"].as_u64().unwrap();
+    let actions = format!("/matches/{id}/actions");
+    let mut live = connect(address, &format!("/matches/{id}/live"));
+    // an empty, masked ping, which must not make the server send the state again
+    live.get_mut().write_all(&[0x89, 0x80, 0, 0, 0, 0]).unwrap();
+    let mut next = || receive(&mut live);
+    assert_eq!(next(), created);
+
+    // every tablet scores at once, and every cone counts
+    let junctions = ["V1", "W1", "W2", "W3", "X2", "X3", "Y1", "Z5"];
+    let tablets: Vec<_> = junctions.iter()
+        .map(|&junction| {
+            let actions = actions.clone();
+            std::thread::spawn(move || {
+                request(address, "POST", &actions, json!({ "action": "score", "alliance": "red", "junction": junction }))
+            })
+        })
+        .collect();
+    for tablet in tablets {
+        assert_eq!(tablet.join().unwrap().0, 200);
+    }
+    let (_, state) = request(address, "GET", &format!("/matches/{id}"), json!(null));
+    let points: u64 = junctions.iter()
+        .map(|name| TraditionalJunction::ALL.iter().find(|j| j.to_string() == *name).unwrap().points() as u64)
+        .sum();
+    assert_eq!(state["score"]["red"]["auto_points"], points);
+    let updates: Vec<Value> = (0..junctions.len()).map(|_| next()).collect();
+    assert_eq!(updates.last(), Some(&state));
+
+    let (status, error) = request(address, "POST", &actions, json!({ "action": "descore", "junction": "V2" }));
+    assert_eq!((status, error), (409, json!({ "error": "JunctionIsEmpty" })));
+    let (status, _) = request(address, "POST", &actions, json!({ "action": "descore", "junction": "A1" }));
+    assert_eq!(status, 400);
+    let (status, _) = request(address, "GET", "/matches/99", json!(null));
+    assert_eq!(status, 404);
+
+    // an action the engine panics on is refused without affecting this or any other match
+    let (_, overflowing) = request(address, "POST", "/matches", json!({
+        "field": "traditional",
+        "red": [robot(5), robot(6)],
+        "blue": [robot(7), robot(8)],
+        "signal_zone": "left",
+    }));
+    let overflowing = format!("/matches/{}", overflowing["id"]);
+    let score = json!({ "action": "score", "alliance": "red", "junction": "W3" });
+    let mut last = None;
+    let refused = (0..100).find_map(|_| match request(address, "POST", &format!("{overflowing}/actions"), score.clone()) {
+        (200, state) => {
+            last = Some(state);
+            None
+        }
+        (status, _) => Some(status),
+    });
+    assert_eq!(refused, Some(500));
+    assert_eq!(request(address, "GET", &overflowing, json!(null)), (200, last.unwrap()));
+    assert_eq!(request(address, "GET", &format!("/matches/{id}"), json!(null)), (200, state));
+}
+
+#[cfg(feature = "ffi")]
+#[test]
+fn ffi_test() {
+    use crate::ffi::*;
+    use crate::FieldCoordinate;
+    use std::ptr::{null, null_mut};
+    unsafe {
+        let mut m = null_mut();
+        let teams = [4017, 4018];
+        let sleeves = [true, false];
+        assert_eq!(
+            ftc_traditional_new(teams.as_ptr(), sleeves.as_ptr(), teams.as_ptr(), sleeves.as_ptr(), FTC_SIGNAL_ZONE_LEFT, &mut m),
+            FtcError::DuplicateTeam
+        );
+        let blue_teams = [1, 2];
+        assert_eq!(
+            ftc_traditional_new(teams.as_ptr(), sleeves.as_ptr(), blue_teams.as_ptr(), sleeves.as_ptr(), FTC_SIGNAL_ZONE_LEFT, &mut m),
+            FtcError::Ok
+        );
+        let (row, column) = W3.coordinate();
+        assert_eq!(ftc_score(m, FTC_ALLIANCE_RED, row, column), FtcError::Ok);
+        assert_eq!(ftc_score(m, 7, row, column), FtcError::InvalidArgument);
+        assert_eq!(ftc_score(m, FTC_ALLIANCE_RED, 5, 0), FtcError::WrongField);
+        assert_eq!(ftc_park(m, FTC_ALLIANCE_RED, 0, FTC_PARK_LEFT_SIGNAL_ZONE), FtcError::Ok);
+        assert_eq!(ftc_park(m, FTC_ALLIANCE_RED, 2, FTC_PARK_LEFT_SIGNAL_ZONE), FtcError::NotInMatch);
+        assert_eq!(ftc_cap(m, FTC_ALLIANCE_RED, 0, row, column), FtcError::BeaconScoredOutsideEndgame);
+        assert_eq!(ftc_card(m, FTC_ALLIANCE_BLUE, 1, FTC_CARD_YELLOW), FtcError::Ok);
+        let mut status = FtcRobotStatus { no_show: false, disabled: false, card: FTC_CARD_NONE, disqualified: false };
+        assert_eq!(ftc_get_status(m, FTC_ALLIANCE_BLUE, 1, &mut status), FtcError::Ok);
+        assert_eq!(status.card, FTC_CARD_YELLOW);
+        assert_eq!(ftc_set_status(m, FTC_ALLIANCE_BLUE, 1, null()), FtcError::NullPointer);
+
+        let mut info = std::mem::zeroed::<FtcAllianceInfo>();
+        assert_eq!(ftc_alliance_info(m, FTC_ALLIANCE_RED, &mut info), FtcError::WrongPhase);
+        let mut phase = 0;
+        for expected in [FTC_PHASE_TELEOP, FTC_PHASE_END_GAME, FTC_PHASE_FINISHED] {
+            assert_eq!(ftc_advance_phase(m, &mut phase), FtcError::Ok);
+            assert_eq!(phase, expected);
+        }
+        assert_eq!(ftc_advance_phase(m, null_mut()), FtcError::WrongPhase);
+        assert_eq!(ftc_alliance_info(m, FTC_ALLIANCE_RED, &mut info), FtcError::Ok);
+        assert_eq!((info.team_count, info.teams, info.auto_points, info.teleop_points), (2, [4017, 4018], 25, 5));
+        ftc_match_free(m);
+
+        assert_eq!(ftc_remote_new(FTC_ALLIANCE_BLUE, 4017, true, FTC_SIGNAL_ZONE_RIGHT, 0, &mut m), FtcError::Ok);
+        let mut field = 0;
+        assert_eq!(ftc_match_field_type(m, &mut field), FtcError::Ok);
+        assert_eq!(field, FTC_FIELD_BLUE_REMOTE);
+        assert_eq!(ftc_park(m, FTC_ALLIANCE_RED, 0, FTC_PARK_SUBSTATION), FtcError::NotInMatch);
+        assert_eq!(ftc_score(null_mut(), FTC_ALLIANCE_BLUE, 0, 0), FtcError::NullPointer);
+        ftc_match_free(m);
+        ftc_match_free(null_mut());
+    }
+}
+
+#[cfg(feature = "ffi")]
+#[test]
+fn ffi_header_test() {
+    let generated = concat!(env!("OUT_DIR"), "/ftc_powerplay_scoring.h");
+    assert!(
+        include_str!(concat!(env!("OUT_DIR"), "/ftc_powerplay_scoring.h"))
+            == include_str!("../include/ftc_powerplay_scoring.h"),
+        "include/ftc_powerplay_scoring.h is out of date, copy it from {generated}"
+    );
+}
+
+// only the conversions that do not call into JavaScript can run natively
+#[cfg(feature = "wasm")]
+#[test]
+fn wasm_test() {
+    use crate::dynamic::MatchError;
+    use crate::wasm::{WasmError, WasmMatch};
+    let mut m = WasmMatch::remote("Blue", 4017, true, "right", 2).unwrap();
+    assert_eq!((m.field_type(), m.phase()), ("BlueRemote".to_string(), "Auto".to_string()));
+    assert_eq!(m.score("blue", "w3"), Ok(()));
+    assert_eq!(m.descore("W3"), Ok("BLUE".to_string()));
+    let rejected = m.cap(4017, "W3").unwrap_err();
+    assert_eq!(rejected, WasmError::Match(MatchError::BeaconScoredOutsideEndgame));
+    assert_eq!((rejected.name(), rejected.to_string()), ("BeaconScoredOutsideEndgame".to_string(), "BeaconScoredOutsideEndgame".to_string()));
+    assert_eq!(m.park(8109, "Substation"), Err(WasmError::Match(MatchError::NotInMatch)));
+
+    let invalid = m.score("blue", "A7").unwrap_err();
+    assert_eq!(invalid, WasmError::Invalid { kind: "junction", name: "A7".to_string() });
+    assert_eq!((invalid.name(), invalid.to_string()), ("TypeError".to_string(), "A7 is not a valid junction.".to_string()));
+    assert!(matches!(WasmMatch::remote("green", 4017, true, "left", 1), Err(WasmError::Invalid { kind: "alliance", .. })));
+    assert!(matches!(WasmMatch::remote("red", 4017, true, "left", 7), Err(WasmError::Invalid { kind: "circuit pattern", .. })));
+
+    for phase in ["TeleOp", "EndGame", "Finished"] {
+        assert_eq!(m.advance_phase(), Ok(phase.to_string()));
+    }
+    assert_eq!(m.advance_phase(), Err(WasmError::Match(MatchError::WrongPhase)));
+}
+
+#[cfg(feature = "python")]
+#[test]
+fn python_test() {
+    use pyo3::ffi::c_str;
+    use pyo3::prelude::*;
+    use pyo3::types::PyModule;
+    pyo3::prepare_freethreaded_python();
+    Python::with_gil(|py| -> PyResult<()> {
+        let module = PyModule::new(py, "ftc_powerplay_scoring")?;
+        crate::python::python_module(&module)?;
+        py.import("sys")?.getattr("modules")?.set_item("ftc_powerplay_scoring", module)?;
+        py.run(c_str!(r#"
+from ftc_powerplay_scoring import *
+
+m = Match.traditional([(4017, True), (4018, False)], [(1, True), (2, True)], SignalZone.Left)
+m.score(Alliance.RED, TraditionalJunction.W3)
+m.score("blue", "w3")
+m.park(4017, ParkingLocation.LeftSignalZone)
+assert m.descore(TraditionalJunction.W3) == Alliance.BLUE
+try:
+    m.cap(4017, TraditionalJunction.W3)
+    assert False
+except BeaconScoredOutsideEndgame:
+    pass
+try:
+    m.score(Alliance.RED, RedRemoteJunction.Z5)
+    assert False
+except ValueError:
+    pass
+
+what_if = m.copy()
+assert what_if.advance_phase() == MatchPhase.TeleOp
+assert m.phase == MatchPhase.Auto
+try:
+    m.end_match()
+    assert False
+except WrongPhaseError:
+    pass
+
+m.advance_phase()
+m.advance_phase()
+m.cap(1, TraditionalJunction.W3)
+try:
+    m.cap(2, TraditionalJunction.W3)
+    assert False
+except JunctionIsCappedError as error:
+    assert isinstance(error, BeaconError) and isinstance(error, ScoringError)
+try:
+    m.descore(TraditionalJunction.W3)
+    assert False
+except BeaconOnJunctionError:
+    pass
+results = m.end_match()
+assert results[Alliance.RED].auto_points == 25
+assert results[Alliance.RED].teams == [4017, 4018]
+assert results[Alliance.BLUE].endgame_points == 10
+assert m.results()[Alliance.RED].auto_points == 25
+assert len(list(TraditionalJunction)) == 25
+
+remote = Match.remote(Alliance.RED, 4017, True, SignalZone.Right, RemoteCircuitPattern.Pattern1)
+assert remote.field_type == FieldType.RedRemote
+assert remote.teams(Alliance.BLUE) == []
+"#), None, None)
+    }).unwrap();
+}
+
+#[cfg(feature = "jni")]
+#[test]
+fn jni_test() {
+    use std::env::consts::{DLL_PREFIX, DLL_SUFFIX};
+    use std::process::Command;
+    let found = |tool: &str| Command::new(tool).arg("-version").output().is_ok();
+    if !found("javac") || !found("java") {
+        eprintln!("skipping jni_test: no JDK found");
+        return;
+    }
+    // cargo test does not build the cdylib with the features under test, so it is built here.
+    // cargo keeps the target directory locked while tests run, so the build gets a directory of its own.
+    let exe = std::env::current_exe().unwrap();
+    let target = exe.ancestors().nth(3).unwrap().join("jni-test");
+    let built = Command::new(env!("CARGO"))
+        .args(["build", "--lib", "--features", "jni", "--manifest-path"])
+        .arg(concat!(env!("CARGO_MANIFEST_DIR"), "/Cargo.toml"))
+        .arg("--target-dir")
+        .arg(&target)
+        .status()
+        .unwrap();
+    assert!(built.success());
+    let library = target.join("debug").join(format!("{DLL_PREFIX}ftc_powerplay_scoring{DLL_SUFFIX}"));
+    let java = concat!(env!("CARGO_MANIFEST_DIR"), "/java");
+    let classes = std::env::temp_dir().join("ftc_powerplay_scoring_jni_test");
+    let sources: Vec<_> = ["ftc/powerplay/scoring", "test/ftc/powerplay/scoring"].iter()
+        .flat_map(|dir| std::fs::read_dir(format!("{java}/{dir}")).unwrap())
+        .map(|entry| entry.unwrap().path())
+        .collect();
+    let compiled = Command::new("javac").arg("-d").arg(&classes).args(&sources).status().unwrap();
+    assert!(compiled.success());
+    let output = Command::new("java")
+        .arg("-ea")
+        .arg(format!("-Dftc.powerplay.scoring.library={}", library.display()))
+        .arg("-cp")
+        .arg(&classes)
+        .arg("ftc.powerplay.scoring.ScoringTest")
+        .output()
+        .unwrap();
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+}
@@ -6,11 +6,21 @@ use crate::sealed::Sealed;
 use crate::BeaconError::*;
 use crate::ConeRemovalError::{BeaconOnJunction, JunctionIsEmpty};
 use crate::MaybeInvalid::{Invalid, Valid};
-use nohash::IntMap;
-use std::hint::unreachable_unchecked;
-use std::mem::transmute;
-use std::ops::Index;
-use crate::traditional::TraditionalJunctionOrStart::*;
+use crate::dynamic::MatchPhase;
+use crate::events::Subscribers;
+use crate::junction_map::JunctionMap;
+use alloc::boxed::Box;
+use core::hint::unreachable_unchecked;
+use core::mem::transmute;
+use core::ops::Index;
+
+mod encoding;
+pub mod perspective;
+mod render;
+#[cfg(feature = "simulation")]
+pub mod simulation;
+#[cfg(feature = "std")]
+pub mod strategy;
 
 #[derive(Ord, PartialOrd, Eq, PartialEq, Copy, Clone, Debug)]
 #[repr(u8)]
@@ -24,14 +34,53 @@ pub enum TraditionalJunction {
     Z1 = 0b100_000_00, Z2 = 0b100_001_01, Z3 = 0b100_010_00, Z4 = 0b100_011_01, Z5 = 0b100_100_00,
 }
 
-junction_impl!(TraditionalJunction, 5, 5);
-
-#[derive(Debug)]
+junction_impl!(TraditionalJunction, 5, 5, &JUNCTIONS);
+
+// indexed by row * 5 + column
+const JUNCTIONS: [TraditionalJunction; 25] = {
+    use TraditionalJunction::*;
+    [
+        V1, V2, V3, V4, V5,
+        W1, W2, W3, W4, W5,
+        X1, X2, X3, X4, X5,
+        Y1, Y2, Y3, Y4, Y5,
+        Z1, Z2, Z3, Z4, Z5,
+    ]
+};
+
+#[derive(Debug, Clone)]
 struct InternalTraditionalMatch {
     red: InternalAllianceInfo<TraditionalJunction, 2>,
     blue: InternalAllianceInfo<TraditionalJunction, 2>,
     // beacons are not stored here!
-    junctions: IntMap<TraditionalJunction, ConeStack>,
+    junctions: JunctionMap<TraditionalJunction, ConeStack>,
+    // who possesses each junction, beacons included. indexed by alliance
+    possessions: [JunctionSet; 2],
+    // whether each alliance scores a circuit, terminals included. indexed by alliance
+    circuits: [bool; 2],
+    // what the last scoring call changed
+    events: RecentEvents,
+    subscribers: Subscribers<TraditionalJunction>,
+}
+
+// the most events a single scoring call can cause: one junction changing hands, and both circuits changing
+const MAX_EVENTS: usize = 3;
+
+#[derive(Debug, Clone, Default)]
+struct RecentEvents {
+    events: [Option<FieldEvent<TraditionalJunction>>; MAX_EVENTS],
+    len: u8,
+}
+
+impl RecentEvents {
+    fn push(&mut self, event: FieldEvent<TraditionalJunction>) {
+        self.events[self.len as usize] = Some(event);
+        self.len += 1;
+    }
+
+    fn iter(&self) -> impl Iterator<Item = FieldEvent<TraditionalJunction>> + '_ {
+        self.events.iter().flatten().copied()
+    }
 }
 
 // like has_beacon_on, but inlined to appease the borrow checker
@@ -64,12 +113,69 @@ impl InternalTraditionalMatch {
         has_beacon_on!(self, location)
     }
 
+    /// The alliance that would possess this junction if the match ended now.
+    #[inline]
+    fn owner_of(&self, location: TraditionalJunction) -> Option<Alliance> {
+        if self.possessions[0] & bit(location) != 0 {
+            Some(Alliance::RED)
+        } else if self.possessions[1] & bit(location) != 0 {
+            Some(Alliance::BLUE)
+        } else {
+            None
+        }
+    }
+
+    /// Tells subscribers about an action, followed by what it changed about possession and circuits.
+    fn publish(&mut self, action: MatchEvent<TraditionalJunction>) {
+        self.subscribers.publish(action);
+        for event in self.events.iter() {
+            self.subscribers.publish(MatchEvent::Field(event));
+        }
+    }
+
+    /// Brings possession and circuits up to date after a scoring call, recording what changed.
+    /// `changed` is the junction the call touched, if any.
+    fn track(&mut self, changed: Option<TraditionalJunction>) {
+        self.events = RecentEvents::default();
+        if let Some(junction) = changed {
+            let previous = self.owner_of(junction);
+            let current = self.owner_with(junction, &self.beacons());
+            if previous == current {
+                // nothing else can change if possession did not
+                return;
+            }
+            self.possessions = self.possessions.map(|set| set & !bit(junction));
+            if let Some(alliance) = current {
+                self.possessions[alliance as usize] |= bit(junction);
+            }
+            self.events.push(FieldEvent::OwnershipChanged { junction, previous, current });
+        }
+        for alliance in [Alliance::RED, Alliance::BLUE] {
+            let terminals = self.data_of(alliance).terminal_amounts;
+            let circuit = terminals[0] != 0
+                && terminals[1] != 0
+                && connects(alliance, self.possessions[alliance as usize]);
+            if circuit != self.circuits[alliance as usize] {
+                self.circuits[alliance as usize] = circuit;
+                self.events.push(if circuit {
+                    FieldEvent::CircuitFormed(alliance)
+                } else {
+                    FieldEvent::CircuitBroken(alliance)
+                });
+            }
+        }
+    }
+
     fn new(red: [FtcTeamID; 2], blue: [FtcTeamID; 2], unchecked: bool) -> Self {
         if unchecked || Self::verify_teams(red, blue) {
             Self {
                 red: InternalAllianceInfo::new(red),
                 blue: InternalAllianceInfo::new(blue),
-                junctions: IntMap::default(),
+                junctions: JunctionMap::default(),
+                possessions: [0; 2],
+                circuits: [false; 2],
+                events: RecentEvents::default(),
+                subscribers: Subscribers::default(),
             }
         } else {
             panic!("The same team cannot compete in two slots in the same match.")
@@ -88,7 +194,7 @@ impl InternalTraditionalMatch {
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct TraditionalAuto {
     data: InternalTraditionalMatch,
     red_signal_sleeves: [bool; 2],
@@ -150,14 +256,34 @@ impl TraditionalAuto {
             None
         }
     }
+    /// Creates a new playoff match between two alliances, given the two robots playing for each alliance.
+    /// Returns an error if a robot cannot play for its alliance, or if the alliances share a team.
+    pub fn from_rosters(
+        red: (AllianceRoster, [(FtcTeamID, bool); 2]),
+        blue: (AllianceRoster, [(FtcTeamID, bool); 2]),
+        signal_zone: SignalZone,
+    ) -> Result<Self, RosterError> {
+        let (red_roster, red) = red;
+        let (blue_roster, blue) = blue;
+        if !red_roster.is_disjoint(&blue_roster) {
+            return Err(RosterError::DuplicateTeam);
+        }
+        red_roster.select(red.map(|(team, _)| team))?;
+        blue_roster.select(blue.map(|(team, _)| team))?;
+        // the rosters already guarantee every team is unique
+        let mut auto = Self::from_teams(red, blue, signal_zone);
+        auto.data.red.roster = Some(red_roster);
+        auto.data.blue.roster = Some(blue_roster);
+        Ok(auto)
+    }
 }
 
 #[repr(transparent)]
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct TraditionalTeleOp(InternalTraditionalMatch);
 
 #[repr(transparent)]
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct TraditionalEndGame(InternalTraditionalMatch);
 
 impl Index<MatchIndex> for InternalTraditionalMatch {
@@ -182,6 +308,7 @@ impl Match<TraditionalJunction> for InternalTraditionalMatch {
     // returns true if modification was successful
     fn score_for(&mut self, alliance: Alliance, location: TraditionalJunction) -> bool {
         if self.has_beacon_on(location) {
+            self.events = RecentEvents::default();
             return false;
         }
         match self.junctions.get_mut(&location) {
@@ -190,6 +317,8 @@ impl Match<TraditionalJunction> for InternalTraditionalMatch {
                 self.junctions.insert(location, ConeStack::new(alliance));
             }
         }
+        self.track(Some(location));
+        self.publish(MatchEvent::ConeScored { alliance, junction: location });
         true
     }
 
@@ -198,7 +327,7 @@ impl Match<TraditionalJunction> for InternalTraditionalMatch {
         &mut self,
         location: TraditionalJunction,
     ) -> Result<Alliance, Self::ConeRemovalErrorType> {
-        match self.junctions.get_mut(&location) {
+        let result = match self.junctions.get_mut(&location) {
             Some(cone_stack) => {
                 if has_beacon_on!(self, location) {
                     Err(BeaconOnJunction)
@@ -211,7 +340,12 @@ impl Match<TraditionalJunction> for InternalTraditionalMatch {
                 }
             }
             None => Err(JunctionIsEmpty),
+        };
+        self.track(result.ok().map(|_| location));
+        if let Ok(alliance) = result {
+            self.publish(MatchEvent::ConeRemoved { alliance, junction: location });
         }
+        result
     }
 
     fn add_terminal_for(&mut self, alliance: Alliance, terminal: Terminal) -> bool {
@@ -223,6 +357,8 @@ impl Match<TraditionalJunction> for InternalTraditionalMatch {
         //     amounts.1 += 1;
         // }
         amounts[near_terminal as usize] += 1;
+        self.track(None);
+        self.publish(MatchEvent::TerminalScored { alliance, terminal });
         near_terminal
     }
 
@@ -254,6 +390,7 @@ impl Match<TraditionalJunction> for InternalTraditionalMatch {
         // };
 
         if alliance_info.beacon_placements[team_index] != MaybeInvalid::None {
+            self.events = RecentEvents::default();
             return Err(BeaconPreviouslyScored);
         }
         alliance_info.beacon_placements[team_index] = match output {
@@ -261,6 +398,10 @@ impl Match<TraditionalJunction> for InternalTraditionalMatch {
             Err(JunctionIsCapped) => Invalid,
             _ => unsafe { unreachable_unchecked() },
         };
+        self.track(output.ok().map(|_| location));
+        if output.is_ok() {
+            self.publish(MatchEvent::BeaconPlaced { robot, junction: location });
+        }
         output
     }
 
@@ -281,6 +422,16 @@ impl Match<TraditionalJunction> for InternalTraditionalMatch {
         self.data_of_mut(alliance).penalty_points += points as u16;
     }
 
+    #[inline]
+    fn status_of(&self, robot: MatchIndex) -> RobotStatus {
+        self.data_of(robot.alliance()).statuses[robot.index()]
+    }
+
+    #[inline]
+    fn set_status(&mut self, robot: MatchIndex, status: RobotStatus) {
+        self.data_of_mut(robot.alliance()).statuses[robot.index()] = status;
+    }
+
     fn alliance_of(&self, robot: FtcTeamID) -> Option<Alliance> {
         for alliance in [Alliance::RED, Alliance::BLUE] {
             if self[alliance].contains(&robot) {
@@ -321,6 +472,7 @@ impl Auto<TraditionalJunction, 2, 2> for TraditionalAuto {
         ] {
             alliance_info.score_auto_parking_terminals(signal_sleeves, self.signal_zone);
         }
+        self.data.subscribers.publish(MatchEvent::PhaseChanged(MatchPhase::TeleOp));
         unsafe { transmute(self.data) }
     }
 }
@@ -331,6 +483,7 @@ macro_rules! delegated_impl {
             type BeaconErrorType = BeaconScoredOutsideEndgame;
             fn cap_for(&mut self, robot: MatchIndex, _: TraditionalJunction) -> Result<(), Self::BeaconErrorType> {
                 self.$delegate.data_of_mut(robot.alliance()).beacon_placements[robot.index()] = Invalid;
+                self.$delegate.events = RecentEvents::default();
                 Err(BeaconScoredOutsideEndgame)
             }
         ));
@@ -385,6 +538,16 @@ macro_rules! delegated_impl {
                 self.$delegate.penalize(alliance, points)
             }
 
+            #[inline(always)]
+            fn status_of(&self, robot: MatchIndex) -> RobotStatus {
+                self.$delegate.status_of(robot)
+            }
+
+            #[inline(always)]
+            fn set_status(&mut self, robot: MatchIndex, status: RobotStatus) {
+                self.$delegate.set_status(robot, status)
+            }
+
             #[inline(always)]
             fn alliance_of(&self, robot: FtcTeamID) -> Option<Alliance> {
                 self.$delegate.alliance_of(robot)
@@ -408,11 +571,68 @@ delegated_impl!(TraditionalEndGame, 0, true, (
     }
 ));
 
+macro_rules! possession_impl {
+    ($struc:ty, $delegate:tt) => {
+        impl $struc {
+            /// The alliance possessing `junction`, by its top cone or a beacon, or None if it is empty.
+            #[inline]
+            pub fn owner_of(&self, junction: TraditionalJunction) -> Option<Alliance> {
+                self.$delegate.owner_of(junction)
+            }
+
+            /// Whether `alliance` would score a circuit if the match ended now.
+            #[inline]
+            pub fn has_circuit(&self, alliance: Alliance) -> bool {
+                self.$delegate.circuits[alliance as usize]
+            }
+
+            /// Every junction `alliance` possesses, in row-major order.
+            pub fn possessions(&self, alliance: Alliance) -> impl Iterator<Item = TraditionalJunction> + '_ {
+                let set = self.$delegate.possessions[alliance as usize];
+                JUNCTIONS.into_iter().filter(move |&junction| set & bit(junction) != 0)
+            }
+
+            /// What the most recent call to `score_for`, `descore`, `add_terminal_for`, or `cap_for`
+            /// changed about possession and circuits. Ownership changes come first.
+            pub fn events(&self) -> impl Iterator<Item = FieldEvent<TraditionalJunction>> + '_ {
+                self.$delegate.events.iter()
+            }
+        }
+    };
+}
+
+possession_impl!(TraditionalAuto, data);
+possession_impl!(TraditionalTeleOp, 0);
+possession_impl!(TraditionalEndGame, 0);
+
+macro_rules! observable_impl {
+    ($struc:ty, $delegate:tt) => {
+        impl Observable<TraditionalJunction> for $struc {
+            fn subscribe_until(
+                &mut self,
+                subscriber: impl FnMut(&MatchEvent<TraditionalJunction>) -> bool + Send + Sync + 'static,
+            ) -> SubscriptionId {
+                self.$delegate.subscribers.add(Box::new(subscriber))
+            }
+
+            #[inline]
+            fn unsubscribe(&mut self, id: SubscriptionId) -> bool {
+                self.$delegate.subscribers.remove(id)
+            }
+        }
+    };
+}
+
+observable_impl!(TraditionalAuto, data);
+observable_impl!(TraditionalTeleOp, 0);
+observable_impl!(TraditionalEndGame, 0);
+
 impl TeleOp<TraditionalJunction, 2, 2> for TraditionalTeleOp {
     type EndGameType = TraditionalEndGame;
 
     #[inline(always)]
-    fn into_end_game(self) -> Self::EndGameType {
+    fn into_end_game(mut self) -> Self::EndGameType {
+        self.0.subscribers.publish(MatchEvent::PhaseChanged(MatchPhase::EndGame));
         unsafe { transmute(self) }
     }
 }
@@ -425,177 +645,157 @@ macro_rules! copy_previous_calculations {
             penalty_points: $info.penalty_points,
             auto_points: $info.auto_points,
             teleop_points: 0,
-            endgame_points: 0
+            endgame_points: 0,
+            roster: $info.roster,
+            statuses: $info.statuses
         }
     };
 }
 
-#[repr(u8)]
-#[derive(Eq, PartialEq, Copy, Clone, Debug, Hash)]
-enum TraditionalJunctionOrStart {
-    // copied from TraditionalJunction and must be synced with it
-    V1 = 0b000_000, V2 = 0b000_001, V3 = 0b000_010, V4 = 0b000_011, V5 = 0b000_100,
-    W1 = 0b001_000, W2 = 0b001_001, W3 = 0b001_010, W4 = 0b001_011, W5 = 0b001_100,
-    X1 = 0b010_000, X2 = 0b010_001, X3 = 0b010_010, X4 = 0b010_011, X5 = 0b010_100,
-    Y1 = 0b011_000, Y2 = 0b011_001, Y3 = 0b011_010, Y4 = 0b011_011, Y5 = 0b011_100,
-    Z1 = 0b100_000, Z2 = 0b100_001, Z3 = 0b100_010, Z4 = 0b100_011, Z5 = 0b100_100,
-
-    RedStart = 0b11_0000,
-    BlueStart = 0b11_0001
+impl TraditionalEndGame {
+    fn end_match(mut self) -> [AllianceInfo<2>; 2] {
+        self.0.subscribers.publish(MatchEvent::PhaseChanged(MatchPhase::Finished));
+        let red = &self.0.red;
+        let blue = &self.0.blue;
+        let mut out = [
+            copy_previous_calculations!(RED, red),
+            copy_previous_calculations!(BLUE, blue)
+        ];
+        let beacons = self.0.beacons();
+        for (i, info) in out.iter_mut().enumerate() {
+            let alliance = Alliance::from(i != 0);
+            info.teleop_points = self.0.junctions.iter()
+                .map(|(junction, cone_stack)| (cone_stack.count(alliance) * junction.points()) as u16)
+                .sum::<u16>() + self.0.data_of(alliance).terminal_amounts.iter().sum::<u8>() as u16;
+            info.endgame_points = self.0.endgame_points(alliance, &beacons);
+        }
+        out
+    }
 }
 
-impl TraditionalJunctionOrStart {
-    fn red_success(&self) -> bool {
-        match self {
-            V5 | V4 | W5 => true,
-            _ => false
-        }
-    }
-    fn blue_success(&self) -> bool {
-        match self {
-            Z5 | Z4 | Y5 => true,
-            _ => false
-        }
-    }
-
-    fn coordinate(self) -> (u8, u8) {
-        (self as u8 >> 3, self as u8 & 0b111)
-    }
-    fn successors(self) -> Vec<TraditionalJunctionOrStart> {
-        match self {
-            RedStart => vec![Z1, Y1, Z2],
-            BlueStart => vec![V1, W1, V2],
-            // corner cases (literally)
-            V1 => vec![V2, W1, W2],
-            Z1 => vec![Y1, Y2, Z2],
-            V5 => vec![V4, W4, W5],
-            Z5 => vec![Y4, Y5, Z4],
-            _ => {
-                let (row, col) = self.coordinate();
-                // TODO make better.
-                unsafe {
-                    if row == 0 {
-                        vec![
-                            Self::from_coordinate((0, col - 1)),
-                            Self::from_coordinate((0, col + 1)),
-                            Self::from_coordinate((1, col - 1)),
-                            Self::from_coordinate((1, col)),
-                            Self::from_coordinate((1, col + 1))
-                        ]
-                    } else if row == 4 {
-                        vec![
-                            Self::from_coordinate((4, col - 1)),
-                            Self::from_coordinate((4, col + 1)),
-                            Self::from_coordinate((3, col - 1)),
-                            Self::from_coordinate((3, col)),
-                            Self::from_coordinate((3, col + 1))
-                        ]
-                    } else if col == 0 {
-                        vec![
-                            Self::from_coordinate((row - 1, 0)),
-                            Self::from_coordinate((row + 1, 0)),
-                            Self::from_coordinate((row - 1, 1)),
-                            Self::from_coordinate((row, 1)),
-                            Self::from_coordinate((row + 1, 1))
-                        ]
-                    } else if col == 4 {
-                        vec![
-                            Self::from_coordinate((row - 1, 4)),
-                            Self::from_coordinate((row + 1, 4)),
-                            Self::from_coordinate((row - 1, 3)),
-                            Self::from_coordinate((row, 3)),
-                            Self::from_coordinate((row + 1, 3))
-                        ]
-                    } else {
-                        vec![
-                            Self::from_coordinate((row - 1, col - 1)),
-                            Self::from_coordinate((row - 1, col)),
-                            Self::from_coordinate((row - 1, col + 1)),
-                            Self::from_coordinate((row, col - 1)),
-                            Self::from_coordinate((row, col + 1)),
-                            Self::from_coordinate((row + 1, col - 1)),
-                            Self::from_coordinate((row + 1, col)),
-                            Self::from_coordinate((row + 1, col + 1))
-                        ]
-                    }
-                }
+/// A set of traditional junctions, with a bit for each junction at its index in [`JUNCTIONS`].
+type JunctionSet = u32;
+
+#[inline(always)]
+const fn bit(junction: TraditionalJunction) -> JunctionSet {
+    let encoding = junction as u8;
+    1 << ((encoding >> 5) * 5 + ((encoding >> 2) & 0b111))
+}
+
+// the junctions touching each junction, diagonals included, indexed like JUNCTIONS
+const ADJACENT: [JunctionSet; 25] = {
+    let mut adjacent = [0; 25];
+    let mut i = 0usize;
+    while i < 25 {
+        let mut j = 0usize;
+        while j < 25 {
+            if i != j && (i / 5).abs_diff(j / 5) <= 1 && (i % 5).abs_diff(j % 5) <= 1 {
+                adjacent[i] |= 1 << j;
             }
+            j += 1;
         }
+        i += 1;
     }
-    unsafe fn from_coordinate((row, col): (u8, u8)) -> TraditionalJunctionOrStart {
-        transmute((row << 3) + col)
+    adjacent
+};
+
+// indexed by alliance: the junctions next to its starting terminal, then the junctions next to its other terminal
+const CIRCUIT_ENDS: [(JunctionSet, JunctionSet); 2] = {
+    use TraditionalJunction::*;
+    [
+        (bit(Z1) | bit(Y1) | bit(Z2), bit(V5) | bit(V4) | bit(W5)),
+        (bit(V1) | bit(W1) | bit(V2), bit(Z5) | bit(Z4) | bit(Y5)),
+    ]
+};
+
+/// Whether the `owned` junctions link both of `alliance`'s terminals.
+fn connects(alliance: Alliance, owned: JunctionSet) -> bool {
+    let (start, end) = CIRCUIT_ENDS[alliance as usize];
+    // flood fill outwards from the start, one ring of neighbours at a time
+    let mut reached = start & owned;
+    loop {
+        if reached & end != 0 {
+            return true;
+        }
+        let mut next = reached;
+        let mut remaining = reached;
+        while remaining != 0 {
+            next |= ADJACENT[remaining.trailing_zeros() as usize];
+            remaining &= remaining - 1;
+        }
+        next &= owned;
+        if next == reached {
+            return false;
+        }
+        reached = next;
     }
 }
 
-impl Into<TraditionalJunctionOrStart> for TraditionalJunction {
-    #[inline(always)]
-    fn into(self) -> TraditionalJunctionOrStart {
-        unsafe { transmute(self as u8 >> 2) }
+// indexed by alliance
+type Beacons = [[MaybeInvalid<TraditionalJunction>; 2]; 2];
+
+impl InternalTraditionalMatch {
+    #[inline]
+    fn beacons(&self) -> Beacons {
+        [self.red.beacon_placements, self.blue.beacon_placements]
     }
-}
 
-impl TraditionalEndGame {
-    const RED_SUCCESS: fn(&TraditionalJunctionOrStart) -> bool = TraditionalJunctionOrStart::red_success;
-    const BLUE_SUCCESS: fn(&TraditionalJunctionOrStart) -> bool = TraditionalJunctionOrStart::blue_success;
+    /// Like owner_of, but with the given beacons on the field instead of the ones actually scored.
+    fn owner_with(&self, location: TraditionalJunction, beacons: &Beacons) -> Option<Alliance> {
+        if beacons[0].contains(&Valid(location)) {
+            Some(Alliance::RED)
+        } else if beacons[1].contains(&Valid(location)) {
+            Some(Alliance::BLUE)
+        } else {
+            self.junctions.get(&location).and_then(ConeStack::top_cone)
+        }
+    }
 
-    fn end_match(mut self) -> [AllianceInfo<2>; 2] {
-        let red = self.0.red;
-        let blue = self.0.blue;
-        let mut out = [
-            copy_previous_calculations!(RED, red),
-            copy_previous_calculations!(BLUE, blue)
-        ];
-        let internal_info = [red, blue];
-        for i in 0..2 {
-            out[i].teleop_points = self.0.junctions.iter()
-                .map(|(junction, cone_stack)| {
-                    (cone_stack.count(unsafe { transmute(i as u8) }) * junction.points()) as u16
-                })
-                .sum::<u16>() + internal_info[i].terminal_amounts.iter().sum::<u8>() as u16;
-        }
-        for (i, start, success) in [(0, RedStart, Self::RED_SUCCESS), (1, BlueStart, Self::BLUE_SUCCESS)] {
-            let data = &internal_info[i];
-            out[i].endgame_points = {
-                // parking
-                let mut points = match data.parking_locations {
-                    [Some(ParkingLocation::NearTerminal), Some(ParkingLocation::NearTerminal)] => 4,
-                    [Some(ParkingLocation::NearTerminal), _] | [_, Some(ParkingLocation::NearTerminal)] => 2,
-                    _ => 0
-                };
-                let mut possessions: Vec<TraditionalJunctionOrStart> = vec![];
-                let beacons = data.beacon_placements;
-                for beacon in beacons {
-                    if let Valid(junction) = beacon {
-                        self.0.junctions.remove(&junction);
-                        possessions.push(junction.into());
-                        points += 10;
-                    }
-                }
-                points += self.0.junctions.iter_mut()
-                    .map(|(junction, cone_stack)| {
-                        if unsafe { transmute::<_, Alliance>(i as u8) }
-                            == cone_stack.top_cone().expect("Empty cone stacks should not exist.") {
-                            // self.0.junctions.remove(junction); // better efficiency MAYBE but borrowck is angy.
-                            possessions.push((*junction).into());
-                            3
-                        } else { 0 }
-                    })
-                    .sum::<u16>();
-                if data.terminal_amounts[0] != 0
-                    && data.terminal_amounts[1] != 0
-                    && pathfinding::prelude::bfs(
-                    &start,
-                    // TODO remove collect
-                    |&loc: &TraditionalJunctionOrStart| loc.successors().iter().copied()
-                        .filter(|l| possessions.contains(l)).collect::<Vec<_>>(),
-                    success
-                ).is_some() {
-                    points += 20;
-                }
-                points
-            };
+    /// Whether `alliance` possesses a chain of junctions between its terminals, given who owns each junction.
+    /// Terminal cones are not checked.
+    #[cfg(feature = "std")]
+    fn has_circuit(alliance: Alliance, owner: impl Fn(TraditionalJunction) -> Option<Alliance>) -> bool {
+        let owned = JUNCTIONS.into_iter()
+            .filter(|&junction| owner(junction) == Some(alliance))
+            .fold(0, |set, junction| set | bit(junction));
+        connects(alliance, owned)
+    }
+
+    /// The junctions topped by `alliance`, ignoring beacons.
+    fn topped_by(&self, alliance: Alliance) -> JunctionSet {
+        self.junctions.iter()
+            .filter(|(_, cone_stack)| cone_stack.top_cone() == Some(alliance))
+            .fold(0, |set, (&junction, _)| set | bit(junction))
+    }
+
+    /// The endgame points `alliance` would earn if the match ended now, with the given beacons on the field.
+    fn endgame_points(&self, alliance: Alliance, beacons: &Beacons) -> u16 {
+        let data = self.data_of(alliance);
+        // parking
+        let mut points = match data.parking_locations {
+            [Some(ParkingLocation::NearTerminal), Some(ParkingLocation::NearTerminal)] => 4,
+            [Some(ParkingLocation::NearTerminal), _] | [_, Some(ParkingLocation::NearTerminal)] => 2,
+            _ => 0
+        };
+        // beacons replace the possession points of the junction they are on
+        let [red_beacons, blue_beacons] = beacons.map(|placements| placements.iter()
+            .fold(0, |set, beacon| match beacon {
+                Valid(junction) => set | bit(*junction),
+                _ => set,
+            }));
+        let (own_beacons, beaconed) = match alliance {
+            Alliance::RED => (red_beacons, red_beacons | blue_beacons),
+            Alliance::BLUE => (blue_beacons, red_beacons | blue_beacons),
+        };
+        let topped = self.topped_by(alliance) & !beaconed;
+        points += own_beacons.count_ones() as u16 * 10;
+        points += topped.count_ones() as u16 * 3;
+        if data.terminal_amounts[0] != 0
+            && data.terminal_amounts[1] != 0
+            && connects(alliance, topped | own_beacons) {
+            points += 20;
         }
-        out
+        points
     }
 }
 
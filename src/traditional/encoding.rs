@@ -0,0 +1,79 @@
+//! The compact encoding of traditional matches, as described in [`crate::encoding`].
+use super::*;
+use crate::dynamic::{FieldType, MatchPhase};
+use crate::encoding::{compact_impl, header, read_junctions, write_junctions, Reader};
+use alloc::vec::Vec;
+
+impl InternalTraditionalMatch {
+    fn encode_into(&self, out: &mut Vec<u8>) {
+        self.red.encode_into(out);
+        self.blue.encode_into(out);
+        write_junctions(out, &self.junctions, |out, stack| stack.encode_into(out));
+    }
+
+    fn decode_from(reader: &mut Reader) -> Option<Self> {
+        let red = InternalAllianceInfo::decode_from(reader)?;
+        let blue = InternalAllianceInfo::decode_from(reader)?;
+        // like try_from_teams, since a team cannot play itself
+        if !Self::verify_teams(red.teams, blue.teams) {
+            return reader.invalid();
+        }
+        let mut data = Self::new(red.teams, blue.teams, true);
+        data.red = red;
+        data.blue = blue;
+        data.junctions = read_junctions(reader, ConeStack::decode_from)?;
+        // possession and circuits are rebuilt as if every junction had just been scored
+        for junction in JUNCTIONS {
+            data.track(Some(junction));
+        }
+        data.events = RecentEvents::default();
+        Some(data)
+    }
+}
+
+impl TraditionalAuto {
+    pub(crate) fn encode_into(&self, out: &mut Vec<u8>) {
+        out.push(header(FieldType::Traditional, MatchPhase::Auto));
+        self.data.encode_into(out);
+        let sleeves = self.red_signal_sleeves.into_iter().chain(self.blue_signal_sleeves);
+        out.push(sleeves.enumerate().fold(0, |byte, (i, sleeve)| byte | (sleeve as u8) << i));
+        out.push(self.signal_zone as u8);
+    }
+
+    pub(crate) fn decode_from(reader: &mut Reader) -> Option<Self> {
+        reader.header(FieldType::Traditional, MatchPhase::Auto)?;
+        let data = InternalTraditionalMatch::decode_from(reader)?;
+        let sleeves = reader.byte()?;
+        if sleeves >> 4 != 0 {
+            return reader.invalid();
+        }
+        let sleeve = |i: u8| sleeves & 1 << i != 0;
+        Some(Self {
+            data,
+            red_signal_sleeves: [sleeve(0), sleeve(1)],
+            blue_signal_sleeves: [sleeve(2), sleeve(3)],
+            signal_zone: reader.signal_zone()?,
+        })
+    }
+}
+
+macro_rules! encoding_impl {
+    ($struc:ident, $phase:ident) => {
+        impl $struc {
+            pub(crate) fn encode_into(&self, out: &mut Vec<u8>) {
+                out.push(header(FieldType::Traditional, MatchPhase::$phase));
+                self.0.encode_into(out);
+            }
+
+            pub(crate) fn decode_from(reader: &mut Reader) -> Option<Self> {
+                reader.header(FieldType::Traditional, MatchPhase::$phase)?;
+                InternalTraditionalMatch::decode_from(reader).map($struc)
+            }
+        }
+    };
+}
+
+encoding_impl!(TraditionalTeleOp, TeleOp);
+encoding_impl!(TraditionalEndGame, EndGame);
+
+compact_impl!(TraditionalAuto, TraditionalTeleOp, TraditionalEndGame);
@@ -0,0 +1,274 @@
+//! Naming junctions from either alliance's side of the field, and mirroring the field between alliances.
+//!
+//! Junction names normally belong to the audience's view. From any [`Frame`], rows are lettered from V on the
+//! viewer's left to Z on their right, and columns are numbered from 1 nearest the viewer to 5, so that
+//! scouts can record junctions "from my alliance's side".
+//!
+//! The field is a mirror image of itself with the colors swapped, so [`TraditionalJunction::mirrored`],
+//! [`FieldSnapshot::mirrored`] and the `mirrored` method of each phase turn any position for red into the same
+//! position for blue, and back. A mirrored phase is a whole match, so strategy queries work on it too.
+//! Signal zones are named as the drivers see them, so mirroring swaps left and right.
+use super::*;
+use crate::geometry::Frame;
+use core::ops::IndexMut;
+
+// the audience's row and column of a junction named by someone in the frame
+#[inline]
+fn to_audience(frame: Frame, (row, column): (u8, u8)) -> (u8, u8) {
+    match frame {
+        Frame::Audience => (row, column),
+        Frame::AllianceStation(Alliance::RED) => (4 - column, row),
+        Frame::AllianceStation(_) => (column, 4 - row),
+    }
+}
+
+#[inline]
+fn from_audience(frame: Frame, (row, column): (u8, u8)) -> (u8, u8) {
+    match frame {
+        Frame::Audience => (row, column),
+        Frame::AllianceStation(Alliance::RED) => (column, 4 - row),
+        Frame::AllianceStation(_) => (4 - column, row),
+    }
+}
+
+#[inline(always)]
+fn at((row, column): (u8, u8)) -> TraditionalJunction {
+    JUNCTIONS[(row * 5 + column) as usize]
+}
+
+impl TraditionalJunction {
+    /// Renames a junction named by someone in one frame to what someone in another frame calls it.
+    /// The physical junction does not change.
+    pub fn convert(self, from: Frame, to: Frame) -> Self {
+        at(from_audience(to, to_audience(from, self.coordinate())))
+    }
+
+    /// The junction in the same place on the other alliance's side, mirrored across the middle of the field.
+    /// Each alliance's terminals mirror onto the other alliance's terminals of the same kind.
+    #[inline]
+    pub fn mirrored(self) -> Self {
+        at((4 - self.row(), self.column()))
+    }
+
+    /// The junction opposite this one, after a half turn of the field.
+    /// Each alliance's near terminal turns onto its far terminal.
+    #[inline]
+    pub fn rotated(self) -> Self {
+        at((4 - self.row(), 4 - self.column()))
+    }
+}
+
+impl SignalZone {
+    /// The zone in the same place on the other alliance's side. The other alliance's drivers face the field from
+    /// the opposite wall, so left and right swap, like in [`geometry::signal_zone`](crate::geometry::signal_zone).
+    #[inline]
+    pub fn mirrored(self) -> Self {
+        match self {
+            SignalZone::Left => SignalZone::Right,
+            SignalZone::Middle => SignalZone::Middle,
+            SignalZone::Right => SignalZone::Left,
+        }
+    }
+}
+
+/// What is on a single junction.
+#[derive(Eq, PartialEq, Copy, Clone, Debug, Default, Hash)]
+pub struct JunctionSnapshot {
+    /// The alliance of the top cone, or None if there are no cones.
+    pub top_cone: Option<Alliance>,
+    /// How many cones each alliance has on the junction, indexed by alliance.
+    pub cones: [u8; 2],
+    /// The alliance of the beacon capping the junction, if any.
+    pub beacon: Option<Alliance>,
+}
+crate::display_impl_as_debug!(JunctionSnapshot);
+
+impl JunctionSnapshot {
+    /// The alliance possessing the junction, by its beacon or its top cone.
+    #[inline]
+    pub fn owner(&self) -> Option<Alliance> {
+        self.beacon.or(self.top_cone)
+    }
+
+    fn swap_colors(self) -> Self {
+        let swap = |alliance: Option<Alliance>| alliance.map(Alliance::opponent);
+        Self {
+            top_cone: swap(self.top_cone),
+            cones: [self.cones[1], self.cones[0]],
+            beacon: swap(self.beacon),
+        }
+    }
+}
+
+/// Everything on a traditional field at one moment, from a match or from scouting.
+/// Snapshots are indexed by junction, named from whichever frame the snapshot was recorded in.
+#[derive(Eq, PartialEq, Copy, Clone, Debug, Default, Hash)]
+pub struct FieldSnapshot {
+    // indexed like JUNCTIONS
+    junctions: [JunctionSnapshot; 25],
+    /// The cones in each alliance's terminals, indexed by alliance, near terminal first.
+    pub terminals: [[u8; 2]; 2],
+}
+crate::display_impl_as_debug!(FieldSnapshot);
+
+impl Index<TraditionalJunction> for FieldSnapshot {
+    type Output = JunctionSnapshot;
+
+    #[inline]
+    fn index(&self, junction: TraditionalJunction) -> &Self::Output {
+        &self.junctions[bit(junction).trailing_zeros() as usize]
+    }
+}
+
+impl IndexMut<TraditionalJunction> for FieldSnapshot {
+    #[inline]
+    fn index_mut(&mut self, junction: TraditionalJunction) -> &mut Self::Output {
+        &mut self.junctions[bit(junction).trailing_zeros() as usize]
+    }
+}
+
+impl FieldSnapshot {
+    /// Renames every junction of a snapshot recorded in one frame to what someone in another frame calls it.
+    pub fn convert(&self, from: Frame, to: Frame) -> Self {
+        Self {
+            junctions: JUNCTIONS.map(|junction| self[junction.convert(to, from)]),
+            terminals: self.terminals,
+        }
+    }
+
+    /// The same field with the alliances swapped, so that a strategy written for red can be applied for blue.
+    /// Junctions in the result are the [mirrored](TraditionalJunction::mirrored) junctions of this snapshot.
+    pub fn mirrored(&self) -> Self {
+        Self {
+            junctions: JUNCTIONS.map(|junction| self[junction.mirrored()].swap_colors()),
+            terminals: [self.terminals[1], self.terminals[0]],
+        }
+    }
+
+    /// The same field after a half turn, which swaps each alliance's near and far terminals.
+    pub fn rotated(&self) -> Self {
+        Self {
+            junctions: JUNCTIONS.map(|junction| self[junction.rotated()]),
+            terminals: self.terminals.map(|[near, far]| [far, near]),
+        }
+    }
+
+    /// Every junction with its contents, in row-major order.
+    pub fn iter(&self) -> impl Iterator<Item = (TraditionalJunction, &JunctionSnapshot)> + '_ {
+        JUNCTIONS.into_iter().zip(self.junctions.iter())
+    }
+}
+
+impl ConeStack {
+    // the same stack with every cone's color swapped
+    fn swap_colors(&self) -> Self {
+        let mut cones = self.cones().map(Alliance::opponent);
+        let mut stack = ConeStack::new(cones.next().expect("Empty cone stacks should not exist."));
+        cones.for_each(|cone| stack.push(cone));
+        stack
+    }
+}
+
+impl InternalAllianceInfo<TraditionalJunction, 2> {
+    fn mirrored(&self) -> Self {
+        let mut info = self.clone();
+        info.beacon_placements = self.beacon_placements.map(|placement| match placement {
+            Valid(junction) => Valid(junction.mirrored()),
+            placement => placement,
+        });
+        info.parking_locations = self.parking_locations.map(|location| {
+            location.map(|location| SignalZone::try_from(location).map_or(location, |zone| zone.mirrored().into()))
+        });
+        info
+    }
+}
+
+impl InternalTraditionalMatch {
+    fn mirrored(&self) -> Self {
+        let mut mirrored = self.clone();
+        mirrored.red = self.blue.mirrored();
+        mirrored.blue = self.red.mirrored();
+        mirrored.junctions = JunctionMap::default();
+        for (&junction, stack) in self.junctions.iter() {
+            mirrored.junctions.insert(junction.mirrored(), stack.swap_colors());
+        }
+        // possession and circuits are rebuilt as if every junction had just been scored
+        mirrored.possessions = [0; 2];
+        mirrored.circuits = [false; 2];
+        for junction in JUNCTIONS {
+            mirrored.track(Some(junction));
+        }
+        mirrored.events = RecentEvents::default();
+        mirrored
+    }
+
+    fn snapshot(&self) -> FieldSnapshot {
+        // terminal amounts are stored far terminal first
+        let terminals = |[far, near]: [u8; 2]| [near, far];
+        let mut snapshot = FieldSnapshot {
+            junctions: Default::default(),
+            terminals: [terminals(self.red.terminal_amounts), terminals(self.blue.terminal_amounts)],
+        };
+        for (&junction, stack) in self.junctions.iter() {
+            snapshot[junction] = JunctionSnapshot {
+                top_cone: stack.top_cone(),
+                cones: [stack.count(Alliance::RED), stack.count(Alliance::BLUE)],
+                beacon: None,
+            };
+        }
+        for (alliance, placements) in [(Alliance::RED, self.red.beacon_placements), (Alliance::BLUE, self.blue.beacon_placements)] {
+            for placement in placements {
+                if let Valid(junction) = placement {
+                    snapshot[junction].beacon = Some(alliance);
+                }
+            }
+        }
+        snapshot
+    }
+}
+
+macro_rules! snapshot_impl {
+    ($struc:ty, $delegate:tt) => {
+        impl $struc {
+            /// Everything on the field right now, named from the audience's view.
+            #[inline]
+            pub fn snapshot(&self) -> FieldSnapshot {
+                self.$delegate.snapshot()
+            }
+        }
+    };
+}
+
+snapshot_impl!(TraditionalAuto, data);
+snapshot_impl!(TraditionalTeleOp, 0);
+snapshot_impl!(TraditionalEndGame, 0);
+
+impl TraditionalAuto {
+    /// The same match with the alliances swapped, so that a strategy written for red can be applied for blue.
+    /// Teams, cones, beacons and scores all move to the other alliance, on the [mirrored](TraditionalJunction::mirrored)
+    /// junctions. The signal zone, and robots parked in signal zones, move to the [mirrored](SignalZone::mirrored)
+    /// zones, which scores them the same. Subscribers stay with this match.
+    pub fn mirrored(&self) -> Self {
+        Self {
+            data: self.data.mirrored(),
+            red_signal_sleeves: self.blue_signal_sleeves,
+            blue_signal_sleeves: self.red_signal_sleeves,
+            signal_zone: self.signal_zone.mirrored(),
+        }
+    }
+}
+
+macro_rules! mirrored_impl {
+    ($struc:ident) => {
+        impl $struc {
+            /// The same match with the alliances swapped, as [`TraditionalAuto::mirrored`] describes.
+            #[inline]
+            pub fn mirrored(&self) -> Self {
+                $struc(self.0.mirrored())
+            }
+        }
+    };
+}
+
+mirrored_impl!(TraditionalTeleOp);
+mirrored_impl!(TraditionalEndGame);
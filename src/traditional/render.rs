@@ -0,0 +1,90 @@
+//! Drawing traditional matches, as described in [`crate::render`].
+use super::*;
+use crate::dynamic::{FieldType, MatchPhase};
+use crate::render::{render_impl, Cell, Picture};
+use alloc::vec::Vec;
+
+impl InternalTraditionalMatch {
+    /// One shortest chain of junctions linking `alliance`'s terminals, or nothing if it has no circuit.
+    fn circuit_path(&self, alliance: Alliance) -> JunctionSet {
+        if !self.circuits[alliance as usize] {
+            return 0;
+        }
+        let owned = self.possessions[alliance as usize];
+        let (start, end) = CIRCUIT_ENDS[alliance as usize];
+        // breadth first, one ring of neighbours at a time
+        let mut rings = Vec::from([start & owned]);
+        let mut reached = start & owned;
+        while rings.last().is_some_and(|&ring| ring & end == 0) {
+            let mut next = 0;
+            let mut remaining = rings[rings.len() - 1];
+            while remaining != 0 {
+                next |= ADJACENT[remaining.trailing_zeros() as usize];
+                remaining &= remaining - 1;
+            }
+            next &= owned & !reached;
+            if next == 0 {
+                return 0;
+            }
+            reached |= next;
+            rings.push(next);
+        }
+        // walk back from the end through each earlier ring
+        let mut current = 1 << (rings[rings.len() - 1] & end).trailing_zeros();
+        let mut path = current;
+        for &ring in rings.iter().rev().skip(1) {
+            current = 1 << (ring & ADJACENT[(current as JunctionSet).trailing_zeros() as usize]).trailing_zeros();
+            path |= current;
+        }
+        path
+    }
+
+    fn picture(&self, phase: MatchPhase) -> Picture {
+        let paths = [Alliance::RED, Alliance::BLUE].map(|alliance| self.circuit_path(alliance));
+        let mut cells: Vec<Cell> = JUNCTIONS
+            .iter()
+            .map(|&junction| {
+                let mut cell = Cell::new(junction);
+                cell.cones = self.junctions.get(&junction).map(|stack| stack.cones().collect()).unwrap_or_default();
+                cell.circuit = [Alliance::RED, Alliance::BLUE]
+                    .into_iter()
+                    .find(|&alliance| paths[alliance as usize] & bit(junction) != 0);
+                cell
+            })
+            .collect();
+        for (alliance, info) in [(Alliance::RED, &self.red), (Alliance::BLUE, &self.blue)] {
+            for (i, placement) in info.beacon_placements.iter().enumerate() {
+                if let Valid(junction) = placement {
+                    cells[bit(*junction).trailing_zeros() as usize].beacon = Some(MatchIndex::new(alliance, i as u8));
+                }
+            }
+        }
+        let mut picture = Picture {
+            field: FieldType::Traditional,
+            phase,
+            columns: 5,
+            cells,
+            terminals: Vec::new(),
+            parked: Vec::new(),
+        };
+        picture.add_alliance(Alliance::RED, &self.red);
+        picture.add_alliance(Alliance::BLUE, &self.blue);
+        picture
+    }
+}
+
+macro_rules! picture_impl {
+    ($struc:ty, $delegate:tt, $phase:ident) => {
+        impl $struc {
+            fn picture(&self) -> Picture {
+                self.$delegate.picture(MatchPhase::$phase)
+            }
+        }
+    };
+}
+
+picture_impl!(TraditionalAuto, data, Auto);
+picture_impl!(TraditionalTeleOp, 0, TeleOp);
+picture_impl!(TraditionalEndGame, 0, EndGame);
+
+render_impl!(TraditionalAuto, TraditionalTeleOp, TraditionalEndGame);
@@ -0,0 +1,289 @@
+//! Monte Carlo simulation of traditional matches.
+//!
+//! Robots are described by a [`RobotProfile`]. Every simulated match is played through the same scoring API
+//! a referee would use, so simulated matches score exactly like real ones.
+use super::*;
+use crate::traditional::strategy::CircuitCompletion;
+use rand::distributions::WeightedIndex;
+use rand::prelude::*;
+use std::collections::BTreeMap;
+
+const AUTO_END: f32 = 30.0;
+const TELEOP_END: f32 = 120.0;
+const MATCH_END: f32 = 150.0;
+
+/// How a single robot plays a match.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RobotProfile {
+    /// The shortest and longest time, in seconds, the robot takes to score one cone.
+    /// Cycle times are uniformly distributed between the two.
+    pub cycle_time: (f32, f32),
+    /// Relative weights for scoring on ground, low, medium, and high junctions, in that order.
+    pub level_weights: [u32; 4],
+    /// Whether the robot uses a custom signal sleeve.
+    pub has_signal_sleeve: bool,
+    /// How many cones the robot scores in autonomous when its routine works.
+    pub auto_cones: u8,
+    /// The chance, from 0 to 1, that the robot's autonomous routine works.
+    /// A working routine scores its cones and parks in the correct signal zone.
+    pub auto_success_rate: f64,
+    /// The chance, from 0 to 1, that the robot places its beacon at the start of end game.
+    pub beacon_reliability: f64,
+    /// The chance, from 0 to 1, that the robot spends a cycle taking a junction from the opponent.
+    pub defense_tendency: f64,
+}
+
+impl RobotProfile {
+    // the distributions that rand would otherwise panic on while a match is being played
+    fn validate(&self) {
+        let (min, max) = self.cycle_time;
+        assert!(0.0 < min && min <= max, "A robot profile's cycle times must be positive, shortest first.");
+        for chance in [self.auto_success_rate, self.beacon_reliability, self.defense_tendency] {
+            assert!((0.0..=1.0).contains(&chance), "A robot profile's chances must be between 0 and 1.");
+        }
+    }
+}
+
+/// The scores of many simulated matches between the same two alliances.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Default)]
+pub struct SimulationResults {
+    /// The red alliance's score in each match, in the order the matches were played.
+    pub red_scores: Vec<u16>,
+    /// The blue alliance's score in each match, in the order the matches were played.
+    pub blue_scores: Vec<u16>,
+}
+
+impl SimulationResults {
+    fn scores(&self, alliance: Alliance) -> &[u16] {
+        match alliance {
+            Alliance::RED => &self.red_scores,
+            Alliance::BLUE => &self.blue_scores,
+        }
+    }
+
+    // 0 rather than NaN when no matches were simulated
+    fn fraction(&self, total: f64) -> f64 {
+        if self.red_scores.is_empty() {
+            0.0
+        } else {
+            total / self.red_scores.len() as f64
+        }
+    }
+
+    /// The fraction of matches won by `alliance`, or 0 if no matches were simulated.
+    pub fn win_probability(&self, alliance: Alliance) -> f64 {
+        let wins = self.scores(alliance).iter()
+            .zip(self.scores(alliance.opponent()))
+            .filter(|(ours, theirs)| ours > theirs)
+            .count();
+        self.fraction(wins as f64)
+    }
+
+    /// The fraction of matches that ended in a tie, or 0 if no matches were simulated.
+    pub fn tie_probability(&self) -> f64 {
+        let ties = self.red_scores.iter().zip(&self.blue_scores).filter(|(red, blue)| red == blue).count();
+        self.fraction(ties as f64)
+    }
+
+    /// The average score of `alliance`, or 0 if no matches were simulated.
+    pub fn mean_score(&self, alliance: Alliance) -> f64 {
+        self.fraction(self.scores(alliance).iter().map(|&score| score as f64).sum())
+    }
+
+    /// How many matches `alliance` finished with each score.
+    pub fn score_distribution(&self, alliance: Alliance) -> BTreeMap<u16, usize> {
+        let mut distribution = BTreeMap::new();
+        for &score in self.scores(alliance) {
+            *distribution.entry(score).or_insert(0) += 1;
+        }
+        distribution
+    }
+}
+
+// lets one period's logic drive any phase, while still reading the field for strategy
+trait Phase: Match<TraditionalJunction> {
+    fn field(&self) -> &InternalTraditionalMatch;
+}
+
+impl Phase for TraditionalAuto {
+    #[inline(always)]
+    fn field(&self) -> &InternalTraditionalMatch {
+        &self.data
+    }
+}
+
+impl Phase for TraditionalTeleOp {
+    #[inline(always)]
+    fn field(&self) -> &InternalTraditionalMatch {
+        &self.0
+    }
+}
+
+impl Phase for TraditionalEndGame {
+    #[inline(always)]
+    fn field(&self) -> &InternalTraditionalMatch {
+        &self.0
+    }
+}
+
+/// Plays simulated matches between two hypothetical alliances.
+/// The same seed always produces the same matches.
+#[derive(Debug)]
+pub struct Simulator {
+    // indexed by MatchIndex
+    profiles: [RobotProfile; 4],
+    levels: [WeightedIndex<u32>; 4],
+    rng: StdRng,
+}
+
+impl Simulator {
+    /// Creates a simulator, panicking if a profile has no preferred junction level, a chance outside 0 to 1,
+    /// or a cycle time that is not positive or whose shortest is longer than its longest.
+    pub fn new(red: [RobotProfile; 2], blue: [RobotProfile; 2], seed: u64) -> Self {
+        let [red1, red2] = red;
+        let [blue1, blue2] = blue;
+        let profiles = [red1, blue1, red2, blue2];
+        profiles.iter().for_each(RobotProfile::validate);
+        let levels = profiles.each_ref().map(|profile| {
+            WeightedIndex::new(profile.level_weights)
+                .expect("A robot profile must prefer at least one junction level.")
+        });
+        Self {
+            profiles,
+            levels,
+            rng: StdRng::seed_from_u64(seed),
+        }
+    }
+
+    /// Simulates `matches` matches.
+    pub fn simulate(&mut self, matches: usize) -> SimulationResults {
+        let mut results = SimulationResults::default();
+        for _ in 0..matches {
+            let (red, blue) = self.simulate_match();
+            results.red_scores.push(red.auto_points + red.teleop_points + red.endgame_points);
+            results.blue_scores.push(blue.auto_points + blue.teleop_points + blue.endgame_points);
+        }
+        results
+    }
+
+    /// Simulates a single match.
+    pub fn simulate_match(&mut self) -> (AllianceInfo<2>, AllianceInfo<2>) {
+        let signal_zone = *[SignalZone::Left, SignalZone::Middle, SignalZone::Right]
+            .choose(&mut self.rng)
+            .unwrap();
+        let sleeves = |i: usize| [self.profiles[i].has_signal_sleeve, self.profiles[i + 2].has_signal_sleeve];
+        let mut auto = TraditionalAuto::new(sleeves(0), sleeves(1), signal_zone);
+
+        let mut clocks = [0.0; 4];
+        let mut auto_cones = [0; 4];
+        for (i, profile) in self.profiles.iter().enumerate() {
+            if self.rng.gen_bool(profile.auto_success_rate) {
+                auto_cones[i] = profile.auto_cones;
+                auto.park_for(MatchIndex(i as u8), signal_zone);
+            }
+        }
+        self.play_period(&mut auto, &mut clocks, AUTO_END, |this, auto, robot| {
+            let remaining = &mut auto_cones[robot.0 as usize];
+            if *remaining == 0 {
+                return false;
+            }
+            *remaining -= 1;
+            this.score_preferred(auto, robot, None);
+            true
+        });
+
+        let mut teleop = auto.into_teleop();
+        self.play_period(&mut teleop, &mut clocks, TELEOP_END, |this, teleop, robot| {
+            this.cycle(teleop, robot);
+            true
+        });
+
+        let mut endgame = teleop.into_end_game();
+        let mut beacon_pending = [true; 4];
+        self.play_period(&mut endgame, &mut clocks, MATCH_END, |this, endgame, robot| {
+            let i = robot.0 as usize;
+            if beacon_pending[i] {
+                beacon_pending[i] = false;
+                if this.rng.gen_bool(this.profiles[i].beacon_reliability) {
+                    let best = endgame.0.beacon_recommendations(robot.alliance()).into_iter()
+                        .find(|r| r.placements.len() == 1 && r.placements[0].0 == robot);
+                    if let Some(recommendation) = best {
+                        let _ = endgame.cap_for(robot, recommendation.placements[0].1);
+                        return true;
+                    }
+                }
+            }
+            this.cycle(endgame, robot);
+            true
+        });
+        EndGame::end_match(endgame)
+    }
+
+    /// Runs robot cycles in time order until `end`.
+    /// `action` performs one cycle, and returns false if the robot is done for the period.
+    fn play_period<P: Phase>(
+        &mut self,
+        phase: &mut P,
+        clocks: &mut [f32; 4],
+        end: f32,
+        mut action: impl FnMut(&mut Self, &mut P, MatchIndex) -> bool,
+    ) {
+        loop {
+            let (i, time) = clocks.iter().copied().enumerate()
+                .min_by(|(_, a), (_, b)| a.total_cmp(b))
+                .unwrap();
+            if time >= end {
+                break;
+            }
+            let (min, max) = self.profiles[i].cycle_time;
+            let finished = time + self.rng.gen_range(min..=max);
+            // a cycle that would not finish in time is never scored
+            if finished > end || !action(self, phase, MatchIndex(i as u8)) {
+                clocks[i] = end;
+            } else {
+                clocks[i] = finished;
+            }
+        }
+    }
+
+    fn cycle(&mut self, phase: &mut impl Phase, robot: MatchIndex) {
+        let alliance = robot.alliance();
+        if self.rng.gen_bool(self.profiles[robot.0 as usize].defense_tendency) {
+            if let Some(junction) = self.defense_target(phase.field(), alliance.opponent()) {
+                phase.score_for(alliance, junction);
+                return;
+            }
+        }
+        let completion = phase.field().circuit_completion(alliance);
+        if let Some(&terminal) = completion.as_ref().and_then(|c| c.terminals.first()) {
+            phase.add_terminal_for(alliance, terminal);
+            return;
+        }
+        self.score_preferred(phase, robot, completion);
+    }
+
+    // scores on the robot's preferred level, on the circuit if possible
+    fn score_preferred(&mut self, phase: &mut impl Phase, robot: MatchIndex, completion: Option<CircuitCompletion>) {
+        let points = self.levels[robot.0 as usize].sample(&mut self.rng) as u8 + 2;
+        let planned = completion.and_then(|c| c.junctions.into_iter().find(|j| j.points() == points));
+        let target = planned.or_else(|| {
+            JUNCTIONS.into_iter()
+                .filter(|&j| j.points() == points && !phase.field().has_beacon_on(j))
+                .choose(&mut self.rng)
+        });
+        if let Some(junction) = target {
+            phase.score_for(robot.alliance(), junction);
+        }
+    }
+
+    // a junction that breaks the opponent's circuit if possible, and any other junction they own otherwise
+    fn defense_target(&mut self, field: &InternalTraditionalMatch, opponent: Alliance) -> Option<TraditionalJunction> {
+        field.circuit_cut(opponent)
+            .and_then(|cut| cut.first().copied())
+            .or_else(|| {
+                JUNCTIONS.into_iter()
+                    .filter(|&j| field.owner_of(j) == Some(opponent) && !field.has_beacon_on(j))
+                    .choose(&mut self.rng)
+            })
+    }
+}
@@ -0,0 +1,357 @@
+//! Strategy queries over a live traditional match.
+//!
+//! None of these modify the match; they only report what the field would score if the match ended
+//! right now, and what it would take to change that.
+use super::*;
+use TraditionalJunctionOrStart::*;
+
+/// The cheapest way for an alliance to complete a circuit from the current field state.
+#[derive(Debug, Eq, PartialEq, Clone, Hash)]
+pub struct CircuitCompletion {
+    /// Junctions the alliance does not currently own, but must own (by scoring a cone or a beacon on them).
+    /// These are in circuit order, starting from the junctions next to the alliance's starting terminal.
+    pub junctions: Vec<TraditionalJunction>,
+    /// Terminals that still need a cone from the alliance for the circuit to count.
+    pub terminals: Vec<Terminal>,
+}
+
+/// A possible placement of one or both of an alliance's remaining beacons.
+#[derive(Debug, Eq, PartialEq, Clone, Hash)]
+pub struct BeaconRecommendation {
+    /// The junction each robot would cap.
+    pub placements: Vec<(MatchIndex, TraditionalJunction)>,
+    /// The change in the alliance's score if the match ended right after these placements.
+    pub gain: i16,
+    /// The change in the alliance's lead over the opponent, which also counts the points the opponent loses.
+    pub margin: i16,
+    /// Whether these placements give the alliance a circuit it does not currently have.
+    pub creates_circuit: bool,
+    /// Points the alliance currently holds through these junctions that the opponent could take by topping them,
+    /// and which a beacon would protect.
+    pub protected_points: u16,
+}
+
+// the field as a graph, with a node for each alliance's starting terminal
+#[repr(u8)]
+#[derive(Eq, PartialEq, Copy, Clone, Debug, Hash)]
+// junction variants are only made by transmuting a TraditionalJunction
+#[allow(dead_code)]
+enum TraditionalJunctionOrStart {
+    // copied from TraditionalJunction and must be synced with it
+    V1 = 0b000_000, V2 = 0b000_001, V3 = 0b000_010, V4 = 0b000_011, V5 = 0b000_100,
+    W1 = 0b001_000, W2 = 0b001_001, W3 = 0b001_010, W4 = 0b001_011, W5 = 0b001_100,
+    X1 = 0b010_000, X2 = 0b010_001, X3 = 0b010_010, X4 = 0b010_011, X5 = 0b010_100,
+    Y1 = 0b011_000, Y2 = 0b011_001, Y3 = 0b011_010, Y4 = 0b011_011, Y5 = 0b011_100,
+    Z1 = 0b100_000, Z2 = 0b100_001, Z3 = 0b100_010, Z4 = 0b100_011, Z5 = 0b100_100,
+
+    RedStart = 0b11_0000,
+    BlueStart = 0b11_0001
+}
+
+impl TraditionalJunctionOrStart {
+    const RED_SUCCESS: fn(&Self) -> bool = Self::red_success;
+    const BLUE_SUCCESS: fn(&Self) -> bool = Self::blue_success;
+
+    fn red_success(&self) -> bool {
+        matches!(self, V5 | V4 | W5)
+    }
+    fn blue_success(&self) -> bool {
+        matches!(self, Z5 | Z4 | Y5)
+    }
+
+    fn coordinate(self) -> (u8, u8) {
+        (self as u8 >> 3, self as u8 & 0b111)
+    }
+    fn successors(self) -> Successors {
+        match self {
+            RedStart => Successors::new([Z1, Y1, Z2]),
+            BlueStart => Successors::new([V1, W1, V2]),
+            // corner cases (literally)
+            V1 => Successors::new([V2, W1, W2]),
+            Z1 => Successors::new([Y1, Y2, Z2]),
+            V5 => Successors::new([V4, W4, W5]),
+            Z5 => Successors::new([Y4, Y5, Z4]),
+            _ => {
+                let (row, col) = self.coordinate();
+                // TODO make better.
+                unsafe {
+                    if row == 0 {
+                        Successors::new([
+                            Self::from_coordinate((0, col - 1)),
+                            Self::from_coordinate((0, col + 1)),
+                            Self::from_coordinate((1, col - 1)),
+                            Self::from_coordinate((1, col)),
+                            Self::from_coordinate((1, col + 1))
+                        ])
+                    } else if row == 4 {
+                        Successors::new([
+                            Self::from_coordinate((4, col - 1)),
+                            Self::from_coordinate((4, col + 1)),
+                            Self::from_coordinate((3, col - 1)),
+                            Self::from_coordinate((3, col)),
+                            Self::from_coordinate((3, col + 1))
+                        ])
+                    } else if col == 0 {
+                        Successors::new([
+                            Self::from_coordinate((row - 1, 0)),
+                            Self::from_coordinate((row + 1, 0)),
+                            Self::from_coordinate((row - 1, 1)),
+                            Self::from_coordinate((row, 1)),
+                            Self::from_coordinate((row + 1, 1))
+                        ])
+                    } else if col == 4 {
+                        Successors::new([
+                            Self::from_coordinate((row - 1, 4)),
+                            Self::from_coordinate((row + 1, 4)),
+                            Self::from_coordinate((row - 1, 3)),
+                            Self::from_coordinate((row, 3)),
+                            Self::from_coordinate((row + 1, 3))
+                        ])
+                    } else {
+                        Successors::new([
+                            Self::from_coordinate((row - 1, col - 1)),
+                            Self::from_coordinate((row - 1, col)),
+                            Self::from_coordinate((row - 1, col + 1)),
+                            Self::from_coordinate((row, col - 1)),
+                            Self::from_coordinate((row, col + 1)),
+                            Self::from_coordinate((row + 1, col - 1)),
+                            Self::from_coordinate((row + 1, col)),
+                            Self::from_coordinate((row + 1, col + 1))
+                        ])
+                    }
+                }
+            }
+        }
+    }
+    unsafe fn from_coordinate((row, col): (u8, u8)) -> TraditionalJunctionOrStart {
+        transmute((row << 3) + col)
+    }
+    fn junction(self) -> Option<TraditionalJunction> {
+        match self {
+            RedStart | BlueStart => None,
+            _ => {
+                let (row, col) = self.coordinate();
+                Some(JUNCTIONS[(row * 5 + col) as usize])
+            }
+        }
+    }
+}
+
+// the neighbors of a junction, stored inline so that circuit search never allocates
+#[derive(Copy, Clone)]
+struct Successors {
+    items: [TraditionalJunctionOrStart; 8],
+    len: u8,
+}
+
+impl Successors {
+    fn new<const N: usize>(items: [TraditionalJunctionOrStart; N]) -> Self {
+        let mut padded = [RedStart; 8];
+        padded[..N].copy_from_slice(&items);
+        Self { items: padded, len: N as u8 }
+    }
+}
+
+impl IntoIterator for Successors {
+    type Item = TraditionalJunctionOrStart;
+    type IntoIter = core::iter::Take<core::array::IntoIter<TraditionalJunctionOrStart, 8>>;
+
+    #[inline]
+    fn into_iter(self) -> Self::IntoIter {
+        self.items.into_iter().take(self.len as usize)
+    }
+}
+
+impl From<TraditionalJunction> for TraditionalJunctionOrStart {
+    #[inline(always)]
+    fn from(junction: TraditionalJunction) -> Self {
+        unsafe { transmute(junction as u8 >> 2) }
+    }
+}
+
+impl InternalTraditionalMatch {
+    pub(super) fn circuit_completion(&self, alliance: Alliance) -> Option<CircuitCompletion> {
+        let (start, success) = match alliance {
+            Alliance::RED => (RedStart, TraditionalJunctionOrStart::RED_SUCCESS),
+            Alliance::BLUE => (BlueStart, TraditionalJunctionOrStart::BLUE_SUCCESS),
+        };
+        let opponent = self.data_of(alliance.opponent());
+        // entering a junction costs one cone, unless the alliance already owns it.
+        // opponent beacons cannot be removed, so those junctions are not traversable at all.
+        let (path, _) = pathfinding::prelude::dijkstra(
+            &start,
+            |&loc: &TraditionalJunctionOrStart| {
+                loc.successors().into_iter().filter_map(|next| {
+                    let junction = next.junction().expect("A start is never a successor.");
+                    if opponent.beacon_placements.contains(&Valid(junction)) {
+                        None
+                    } else if self.owner_of(junction) == Some(alliance) {
+                        Some((next, 0u8))
+                    } else {
+                        Some((next, 1))
+                    }
+                })
+            },
+            success,
+        )?;
+        let terminal_amounts = self.data_of(alliance).terminal_amounts;
+        Some(CircuitCompletion {
+            junctions: path.into_iter()
+                .filter_map(TraditionalJunctionOrStart::junction)
+                .filter(|&junction| self.owner_of(junction) != Some(alliance))
+                .collect(),
+            terminals: [(Terminal::Near, 1), (Terminal::Far, 0)].into_iter()
+                .filter(|&(_, i)| terminal_amounts[i] == 0)
+                .map(|(terminal, _)| terminal)
+                .collect(),
+        })
+    }
+
+    pub(super) fn circuit_cut(&self, alliance: Alliance) -> Option<Vec<TraditionalJunction>> {
+        // beacons cannot be removed, so their junctions can never be part of the cut.
+        // this is larger than the most junctions that could ever be cut.
+        const UNCUTTABLE: i16 = 64;
+        let (start, success) = match alliance {
+            Alliance::RED => (RedStart, TraditionalJunctionOrStart::RED_SUCCESS),
+            Alliance::BLUE => (BlueStart, TraditionalJunctionOrStart::BLUE_SUCCESS),
+        };
+        let beacons = self.data_of(alliance).beacon_placements;
+        let possessions: Vec<TraditionalJunction> = JUNCTIONS.into_iter()
+            .filter(|&junction| self.owner_of(junction) == Some(alliance))
+            .collect();
+        // every junction is split into an entry (false) and exit (true) node, joined by an edge whose
+        // capacity is the cost of flipping it. the start's exit is the source and its entry is the sink.
+        let mut vertices = vec![(start, true), (start, false)];
+        let mut capacities = vec![];
+        for &junction in &possessions {
+            let node: TraditionalJunctionOrStart = junction.into();
+            vertices.push((node, false));
+            vertices.push((node, true));
+            let cost = if beacons.contains(&Valid(junction)) { UNCUTTABLE } else { 1 };
+            capacities.push((((node, false), (node, true)), cost));
+            if success(&node) {
+                capacities.push((((node, true), (start, false)), UNCUTTABLE));
+            }
+            for next in node.successors() {
+                if possessions.contains(&next.junction().expect("A start is never a successor.")) {
+                    capacities.push((((node, true), (next, false)), UNCUTTABLE));
+                }
+            }
+        }
+        for next in start.successors() {
+            if possessions.contains(&next.junction().expect("A start is never a successor.")) {
+                capacities.push((((start, true), (next, false)), UNCUTTABLE));
+            }
+        }
+        let (_, max_flow, cut) = pathfinding::prelude::edmonds_karp_dense(
+            &vertices,
+            &(start, true),
+            &(start, false),
+            capacities,
+        );
+        if max_flow >= UNCUTTABLE {
+            None
+        } else {
+            Some(cut.into_iter()
+                .map(|(((node, _), _), _)| node.junction().expect("Only junctions can be cut."))
+                .collect())
+        }
+    }
+
+    pub(super) fn beacon_recommendations(&self, alliance: Alliance) -> Vec<BeaconRecommendation> {
+        let opponent = alliance.opponent();
+        let current = self.beacons();
+        let current_points = [
+            self.endgame_points(alliance, &current) as i16,
+            self.endgame_points(opponent, &current) as i16,
+        ];
+        let possessions = self.possessions[alliance as usize];
+        let scores_circuit = self.circuits[alliance as usize];
+        let robots: Vec<MatchIndex> = (0..2)
+            .filter(|&i| self.data_of(alliance).beacon_placements[i] == MaybeInvalid::None)
+            .map(|i| MatchIndex::new(alliance, i as u8))
+            .collect();
+        let legal: Vec<TraditionalJunction> = JUNCTIONS.into_iter()
+            .filter(|&junction| !self.has_beacon_on(junction))
+            .collect();
+
+        let evaluate = |placements: Vec<(MatchIndex, TraditionalJunction)>| {
+            let mut beacons = current;
+            for &(robot, junction) in &placements {
+                beacons[alliance as usize][robot.index()] = Valid(junction);
+            }
+            let gain = self.endgame_points(alliance, &beacons) as i16 - current_points[0];
+            let opponent_gain = self.endgame_points(opponent, &beacons) as i16 - current_points[1];
+            let protected_points = placements.iter()
+                .filter(|&&(_, junction)| self.owner_of(junction) == Some(alliance))
+                .map(|&(_, junction)| {
+                    // the opponent could take the junction, and the circuit with it if it has no way around
+                    let breaks_circuit = scores_circuit && !connects(alliance, possessions & !bit(junction));
+                    3 + breaks_circuit as u16 * 20
+                })
+                .sum();
+            BeaconRecommendation {
+                creates_circuit: !connects(alliance, possessions)
+                    && Self::has_circuit(alliance, |j| self.owner_with(j, &beacons)),
+                placements,
+                gain,
+                margin: gain - opponent_gain,
+                protected_points,
+            }
+        };
+
+        let mut recommendations = vec![];
+        for &robot in &robots {
+            for &junction in &legal {
+                recommendations.push(evaluate(vec![(robot, junction)]));
+            }
+        }
+        if let [first, second] = robots[..] {
+            for (i, &first_junction) in legal.iter().enumerate() {
+                for &second_junction in &legal[i + 1..] {
+                    recommendations.push(evaluate(vec![(first, first_junction), (second, second_junction)]));
+                }
+            }
+        }
+        recommendations.sort_by(|a, b| {
+            b.margin.cmp(&a.margin)
+                .then(b.protected_points.cmp(&a.protected_points))
+                .then(b.gain.cmp(&a.gain))
+        });
+        recommendations
+    }
+}
+
+macro_rules! strategy_impl {
+    ($struc:ty, $delegate:tt) => {
+        impl $struc {
+            /// Finds the fewest junctions `alliance` must newly own to form a circuit, along with any
+            /// terminals that still need a cone.
+            /// Junctions topped by the opponent count as one cone each, and junctions with an opponent beacon
+            /// are avoided entirely. Returns None if opponent beacons make a circuit impossible.
+            pub fn circuit_completion(&self, alliance: Alliance) -> Option<CircuitCompletion> {
+                self.$delegate.circuit_completion(alliance)
+            }
+
+            /// Finds the fewest junctions owned by `alliance` that would need to change ownership to break
+            /// every circuit `alliance` has. Junctions with a beacon cannot change ownership, and are never included.
+            /// Terminal cones are not considered, so this cuts every chain of junctions between `alliance`'s terminals
+            /// even while a terminal is empty. Returns an empty list if there is no such chain, and None if it cannot be broken.
+            pub fn circuit_cut(&self, alliance: Alliance) -> Option<Vec<TraditionalJunction>> {
+                self.$delegate.circuit_cut(alliance)
+            }
+
+            /// Ranks every legal placement of `alliance`'s remaining beacons, best first.
+            /// If both robots still have their beacon, placements of each beacon alone and of both together are
+            /// included. Placements are ranked by how much they change the alliance's lead,
+            /// then by how many points they protect from being descored.
+            pub fn beacon_recommendations(&self, alliance: Alliance) -> Vec<BeaconRecommendation> {
+                self.$delegate.beacon_recommendations(alliance)
+            }
+        }
+    };
+}
+
+strategy_impl!(TraditionalAuto, data);
+strategy_impl!(TraditionalTeleOp, 0);
+strategy_impl!(TraditionalEndGame, 0);
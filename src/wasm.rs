@@ -0,0 +1,247 @@
+//! JavaScript bindings for the scoring engine, built with wasm-bindgen.
+//!
+//! Every match is a `Match` object, created with `Match.traditional` or `Match.remote`
+//! and moved through its phases with `advancePhase`. Everything else is passed as strings that match
+//! the names used in Rust, ignoring case: alliances are `"red"` or `"blue"`, and junctions are names like `"W3"`.
+//! Robots are identified by their team number. Errors are thrown as `Error`s named by [`WasmError::name`],
+//! so an action the match rejects throws an `Error` named after the [`MatchError`], like `"WrongPhase"`.
+use crate::dynamic::*;
+use crate::remote::*;
+use crate::traditional::*;
+use crate::*;
+use js_sys::{Array, Object, Reflect};
+use wasm_bindgen::prelude::*;
+
+/// A match on any field, in any phase.
+#[wasm_bindgen(js_name = Match)]
+pub struct WasmMatch(AnyMatch);
+
+/// Every way a call from JavaScript can fail. Each is thrown as an `Error` with this error's name and message.
+#[derive(Eq, PartialEq, Clone, Debug, Hash)]
+pub enum WasmError {
+    /// A string argument does not name any value of its kind.
+    Invalid { kind: &'static str, name: String },
+    /// The match rejected the action.
+    Match(MatchError),
+    /// The robots passed to a constructor cannot make a match.
+    Teams(&'static str),
+}
+
+impl WasmError {
+    /// The `name` of the thrown `Error`: the [`MatchError`] variant for rejected actions, and `"TypeError"` otherwise.
+    pub fn name(&self) -> String {
+        match self {
+            WasmError::Match(error) => error.to_string(),
+            WasmError::Invalid { .. } | WasmError::Teams(_) => String::from("TypeError"),
+        }
+    }
+}
+
+impl Display for WasmError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        match self {
+            WasmError::Invalid { kind, name } => write!(f, "{name} is not a valid {kind}."),
+            WasmError::Match(error) => write!(f, "{error}"),
+            WasmError::Teams(message) => f.write_str(message),
+        }
+    }
+}
+
+impl From<MatchError> for WasmError {
+    #[inline]
+    fn from(value: MatchError) -> Self {
+        WasmError::Match(value)
+    }
+}
+
+impl From<WasmError> for JsValue {
+    fn from(value: WasmError) -> Self {
+        let error = js_sys::Error::new(&value.to_string());
+        error.set_name(&value.name());
+        error.into()
+    }
+}
+
+fn invalid(kind: &'static str, name: &str) -> WasmError {
+    WasmError::Invalid { kind, name: name.to_string() }
+}
+
+// finds the value whose name matches, ignoring case
+fn named<T: Display + Copy>(kind: &'static str, all: &[T], name: &str) -> Result<T, WasmError> {
+    all.iter()
+        .copied()
+        .find(|value| value.to_string().eq_ignore_ascii_case(name))
+        .ok_or_else(|| invalid(kind, name))
+}
+
+fn alliance(name: &str) -> Result<Alliance, WasmError> {
+    named("alliance", &[Alliance::RED, Alliance::BLUE], name)
+}
+
+fn signal_zone(name: &str) -> Result<SignalZone, WasmError> {
+    named("signal zone", &[SignalZone::Left, SignalZone::Middle, SignalZone::Right], name)
+}
+
+fn set(object: &Object, key: &str, value: impl Into<JsValue>) {
+    // setting a property on a plain object cannot fail
+    Reflect::set(object, &key.into(), &value.into()).unwrap();
+}
+
+fn alliance_object<const N: usize>(info: &AllianceInfo<N>) -> Object {
+    let object = Object::new();
+    let teams: Array = info.teams.iter().map(|team| JsValue::from(team.0)).collect();
+    set(&object, "teams", teams);
+    set(&object, "penaltyPoints", info.penalty_points);
+    set(&object, "autoPoints", info.auto_points);
+    set(&object, "teleOpPoints", info.teleop_points);
+    set(&object, "endGamePoints", info.endgame_points);
+    object
+}
+
+#[wasm_bindgen(js_class = Match)]
+impl WasmMatch {
+    /// Creates a traditional match in auto. Each array holds the values for the alliance's two robots.
+    pub fn traditional(
+        #[wasm_bindgen(js_name = redTeams)] red_teams: Vec<i32>,
+        #[wasm_bindgen(js_name = redSignalSleeves)] red_signal_sleeves: Vec<JsValue>,
+        #[wasm_bindgen(js_name = blueTeams)] blue_teams: Vec<i32>,
+        #[wasm_bindgen(js_name = blueSignalSleeves)] blue_signal_sleeves: Vec<JsValue>,
+        #[wasm_bindgen(js_name = signalZone)] signal_zone_name: &str,
+    ) -> Result<WasmMatch, WasmError> {
+        let robots = |teams: Vec<i32>, sleeves: Vec<JsValue>| -> Result<[(FtcTeamID, bool); 2], WasmError> {
+            match (&teams[..], &sleeves[..]) {
+                (&[team1, team2], [sleeve1, sleeve2]) => Ok([
+                    (FtcTeamID(team1), sleeve1.is_truthy()),
+                    (FtcTeamID(team2), sleeve2.is_truthy()),
+                ]),
+                _ => Err(WasmError::Teams("An alliance must have exactly two robots.")),
+            }
+        };
+        TraditionalAuto::try_from_teams(
+            robots(red_teams, red_signal_sleeves)?,
+            robots(blue_teams, blue_signal_sleeves)?,
+            signal_zone(signal_zone_name)?,
+        )
+            .map(|auto| WasmMatch(auto.into()))
+            .ok_or(WasmError::Teams("A team cannot be in a match more than once."))
+    }
+
+    /// Creates a remote match in auto, for a single team on the given alliance.
+    /// `circuitPattern` is the pattern number, from 1 to 6.
+    pub fn remote(
+        #[wasm_bindgen(js_name = alliance)] alliance_name: &str,
+        team: i32,
+        #[wasm_bindgen(js_name = hasSignalSleeve)] has_signal_sleeve: bool,
+        #[wasm_bindgen(js_name = signalZone)] signal_zone_name: &str,
+        #[wasm_bindgen(js_name = circuitPattern)] circuit_pattern: u8,
+    ) -> Result<WasmMatch, WasmError> {
+        use RemoteCircuitPattern::*;
+        let team = FtcTeamID(team);
+        let signal_zone = signal_zone(signal_zone_name)?;
+        let pattern = *[Pattern1, Pattern2, Pattern3, Pattern4, Pattern5, Pattern6]
+            .get(circuit_pattern.wrapping_sub(1) as usize)
+            .ok_or_else(|| invalid("circuit pattern", &circuit_pattern.to_string()))?;
+        Ok(WasmMatch(match alliance(alliance_name)? {
+            Alliance::RED => RedRemoteAuto::with_team(team, has_signal_sleeve, signal_zone, pattern).into(),
+            Alliance::BLUE => BlueRemoteAuto::with_team(team, has_signal_sleeve, signal_zone, pattern).into(),
+        }))
+    }
+
+    /// `"Traditional"`, `"RedRemote"`, or `"BlueRemote"`.
+    #[wasm_bindgen(getter, js_name = fieldType)]
+    pub fn field_type(&self) -> String {
+        self.0.field_type().to_string()
+    }
+
+    /// `"Auto"`, `"TeleOp"`, `"EndGame"`, or `"Finished"`.
+    #[wasm_bindgen(getter)]
+    pub fn phase(&self) -> String {
+        self.0.phase().to_string()
+    }
+
+    /// Moves the match to its next phase, ending it after end game, and returns the new phase.
+    #[wasm_bindgen(js_name = advancePhase)]
+    pub fn advance_phase(&mut self) -> Result<String, WasmError> {
+        self.0.advance_phase().map(|phase| phase.to_string()).map_err(WasmError::from)
+    }
+
+    fn junction(&self, name: &str) -> Result<AnyJunction, WasmError> {
+        match self.0.field_type() {
+            FieldType::Traditional => named("junction", TraditionalJunction::ALL, name).map(AnyJunction::from),
+            FieldType::RedRemote => named("junction", RedRemoteJunction::ALL, name).map(AnyJunction::from),
+            FieldType::BlueRemote => named("junction", BlueRemoteJunction::ALL, name).map(AnyJunction::from),
+        }
+    }
+
+    fn robot(&self, team: i32) -> Result<MatchIndex, WasmError> {
+        self.0.index_of(FtcTeamID(team)).ok_or(WasmError::Match(MatchError::NotInMatch))
+    }
+
+    pub fn score(&mut self, #[wasm_bindgen(js_name = alliance)] alliance_name: &str, junction: &str) -> Result<(), WasmError> {
+        let location = self.junction(junction)?;
+        self.0.score_for(alliance(alliance_name)?, location).map_err(WasmError::from)
+    }
+
+    /// Removes the top cone of a junction, returning the alliance it belonged to.
+    pub fn descore(&mut self, junction: &str) -> Result<String, WasmError> {
+        let location = self.junction(junction)?;
+        self.0.descore(location).map(|alliance| alliance.to_string()).map_err(WasmError::from)
+    }
+
+    /// Scores a cone in the `"near"` or `"far"` terminal.
+    #[wasm_bindgen(js_name = addTerminal)]
+    pub fn add_terminal(&mut self, #[wasm_bindgen(js_name = alliance)] alliance_name: &str, terminal: &str) -> Result<(), WasmError> {
+        let terminal = named("terminal", &[Terminal::Near, Terminal::Far], terminal)?;
+        self.0.add_terminal_for(alliance(alliance_name)?, terminal).map_err(WasmError::from)
+    }
+
+    /// Scores a team's beacon on a junction.
+    pub fn cap(&mut self, team: i32, junction: &str) -> Result<(), WasmError> {
+        let location = self.junction(junction)?;
+        self.0.cap_for(self.robot(team)?, location).map_err(WasmError::from)
+    }
+
+    pub fn penalize(&mut self, #[wasm_bindgen(js_name = alliance)] alliance_name: &str, points: u8) -> Result<(), WasmError> {
+        self.0.penalize(alliance(alliance_name)?, points).map_err(WasmError::from)
+    }
+
+    /// Parks a team at a location such as `"MiddleSignalZone"` or `"NearTerminal"`.
+    /// Robots can park anywhere in auto, but only in a terminal in end game.
+    pub fn park(&mut self, team: i32, location: &str) -> Result<(), WasmError> {
+        use ParkingLocation::*;
+        let location = named(
+            "parking location",
+            &[LeftSignalZone, MiddleSignalZone, RightSignalZone, NearTerminal, FarTerminal, Substation],
+            location,
+        )?;
+        self.0.park_for(self.robot(team)?, location).map_err(WasmError::from)
+    }
+
+    /// Gives a team a `"yellow"` or `"red"` card. A second yellow card in the same match becomes a red card.
+    pub fn card(&mut self, team: i32, card: &str) -> Result<(), WasmError> {
+        let card = named("card", &[Card::Yellow, Card::Red], card)?;
+        self.0.card_for(self.robot(team)?, card).map_err(WasmError::from)
+    }
+
+    /// The results of a finished match as `{ red, blue }`, or `null` if the match is not finished.
+    /// A remote match only has results for its own alliance.
+    /// Each alliance's results are `{ teams, penaltyPoints, autoPoints, teleOpPoints, endGamePoints }`.
+    pub fn results(&self) -> JsValue {
+        let results = Object::new();
+        match self.0.results() {
+            None => return JsValue::NULL,
+            Some(MatchResult::Traditional(red, blue)) => {
+                set(&results, "red", alliance_object(&red));
+                set(&results, "blue", alliance_object(&blue));
+            }
+            Some(MatchResult::Remote(info)) => {
+                let key = match info.alliance {
+                    Alliance::RED => "red",
+                    Alliance::BLUE => "blue",
+                };
+                set(&results, key, alliance_object(&info));
+            }
+        }
+        results.into()
+    }
+}